@@ -1,48 +1,186 @@
 // src/ast.rs
 
+use crate::node::{Ident, NodeId};
+use crate::span::Span;
+use std::fmt;
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stmt {
+    pub id: NodeId,
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
-pub enum Stmt {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StmtKind {
     VariableDecl {
+        docs: Option<String>,
+        attributes: Vec<Attribute>,
         is_mutable: bool,
-        name: String,
+        pattern: Pattern,
         type_annotation: Option<Type>,
         initializer: Option<Expr>,
     },
 
     FunctionDecl {
-        name: String,
+        docs: Option<String>,
+        attributes: Vec<Attribute>,
+        name: Ident,
+        type_params: Vec<Ident>, // `fn name<T, U>(...)`
+        bounds: Vec<TypeBound>,
         params: Vec<Parameter>,
         return_type: Option<Type>,
         body: Vec<Stmt>,
     },
 
     ClassDecl {
-        name: String,
+        docs: Option<String>,
+        attributes: Vec<Attribute>,
+        name: Ident,
+        implements: Vec<Ident>,
+        includes: Vec<Ident>,
         fields: Vec<Stmt>,  // e.g. variable declarations
-        methods: Vec<Stmt>, // function declarations
+        methods: Vec<Stmt>, // function declarations taking `self` first
+        static_fields: Vec<Stmt>,
+        static_methods: Vec<Stmt>,
+    },
+
+    InterfaceDecl {
+        docs: Option<String>,
+        name: Ident,
+        methods: Vec<MethodSignature>,
+    },
+
+    // a bundle of methods that classes pull in with `includes`
+    MixinDecl {
+        docs: Option<String>,
+        name: Ident,
+        methods: Vec<Stmt>,
     },
 
     ModuleDecl {
-        name: String,
+        docs: Option<String>,
+        name: Ident,
         declarations: Vec<Stmt>,
     },
 
-    Import(String),
+    // `type Name<T> = ...`
+    TypeAlias {
+        docs: Option<String>,
+        name: Ident,
+        params: Vec<Ident>,
+        ty: Type,
+    },
+
+    // `target = value`, or `target op= value` for compound assignment
+    Assign {
+        target: Expr,
+        op: Option<BinaryOp>,
+        value: Expr,
+    },
+
+    If {
+        condition: Expr,
+        then_branch: Vec<Stmt>,
+        else_branch: Option<Vec<Stmt>>, // `else if` is a single nested `If`
+    },
+
+    While {
+        condition: Expr,
+        body: Vec<Stmt>,
+    },
+
+    For {
+        pattern: Pattern,
+        iterable: Expr,
+        body: Vec<Stmt>,
+    },
+
+    Import(Ident),
     Expression(Expr),
     Return(Option<Expr>),
+    Break,
+    Continue,
+}
+
+// `@name` or `@name(args)` before a declaration
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribute {
+    pub kind: AttributeKind,
+    pub span: Span,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeKind {
+    Inline,
+    Deprecated(Option<String>), // the optional note, e.g. "use foo"
+    Native,
+    Test,
+}
+
+impl AttributeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeKind::Inline => "inline",
+            AttributeKind::Deprecated(_) => "deprecated",
+            AttributeKind::Native => "native",
+            AttributeKind::Test => "test",
+        }
+    }
 }
 
 #[allow(dead_code)]
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameter {
-    pub name: String,
+    pub pattern: Pattern,
     pub type_annotation: Option<Type>,
 }
 
+// a bodiless `fn name(params) -> type;`, as declared by interfaces
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MethodSignature {
+    pub docs: Option<String>,
+    pub name: Ident,
+    pub type_params: Vec<Ident>,
+    pub bounds: Vec<TypeBound>,
+    pub params: Vec<Parameter>,
+    pub return_type: Option<Type>,
+    pub span: Span,
+}
+
+// `T: Show + Eq`, from `fn name<T: Show + Eq>` or a `where` clause
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeBound {
+    pub param: Ident,
+    pub constraints: Vec<Ident>, // built-in constraints like `Num`, or interfaces
+    pub span: Span,
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
-pub enum Expr {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Expr {
+    pub id: NodeId,
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExprKind {
     Binary {
         left: Box<Expr>,
         op: BinaryOp,
@@ -55,18 +193,28 @@ pub enum Expr {
     },
 
     Literal(Literal),
-    Identifier(String),
+    Identifier(Ident),
     Grouping(Box<Expr>),
+    Tuple(Vec<Expr>),
 
     StructLiteral {
-        name: String,
-        fields: Vec<(String, Expr)>,
+        name: Ident,
+        fields: Vec<(Ident, Expr)>,
     },
 
+    // an anonymous `{ name: value, ... }` table
+    Record(Vec<(Ident, Expr)>),
+
     Block(Vec<Stmt>),
     PropertyAccess {
         object: Box<Expr>,
-        name: String,
+        name: Ident,
+    },
+
+    // `object?.name`, which is nil whenever `object` is
+    OptionalPropertyAccess {
+        object: Box<Expr>,
+        name: Ident,
     },
 
     Call {
@@ -78,10 +226,105 @@ pub enum Expr {
         params: Vec<Parameter>,
         body: Box<Expr>,
     },
+
+    // `start..end` or `start..=end`
+    Range {
+        start: Box<Expr>,
+        end: Box<Expr>,
+        inclusive: bool,
+    },
+
+    // `luau<T>(a, b) { ... }`, splicing raw Luau into the output
+    Luau {
+        return_type: Option<Type>,
+        bindings: Vec<(Ident, Span)>,
+        body: String,
+        body_span: Span, // includes the braces
+    },
+
+    // `match value { pattern => result, ... }`, trying each arm in order
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
 }
 
 #[allow(dead_code)]
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Expr,
+    pub span: Span,
+}
+
+impl Stmt {
+    /// A statement with a dummy id; see `node::number`.
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Self {
+            id: NodeId::DUMMY,
+            kind,
+            span,
+        }
+    }
+
+    /// The attributes on a function, class or variable declaration.
+    pub fn attributes(&self) -> &[Attribute] {
+        match &self.kind {
+            StmtKind::VariableDecl { attributes, .. }
+            | StmtKind::FunctionDecl { attributes, .. }
+            | StmtKind::ClassDecl { attributes, .. } => attributes,
+
+            _ => &[],
+        }
+    }
+
+    pub fn has_attribute(&self, kind: &AttributeKind) -> bool {
+        self.attributes()
+            .iter()
+            .any(|attribute| attribute.kind.name() == kind.name())
+    }
+
+    /// The `///` or `/** */` documentation on a declaration, as Markdown.
+    pub fn docs(&self) -> Option<&str> {
+        match &self.kind {
+            StmtKind::VariableDecl { docs, .. }
+            | StmtKind::FunctionDecl { docs, .. }
+            | StmtKind::ClassDecl { docs, .. }
+            | StmtKind::InterfaceDecl { docs, .. }
+            | StmtKind::MixinDecl { docs, .. }
+            | StmtKind::ModuleDecl { docs, .. }
+            | StmtKind::TypeAlias { docs, .. } => docs.as_deref(),
+
+            _ => None,
+        }
+    }
+
+    /// The note of a `@deprecated` declaration, or `None` if it isn't deprecated.
+    pub fn deprecation(&self) -> Option<Option<&str>> {
+        self.attributes()
+            .iter()
+            .find_map(|attribute| match &attribute.kind {
+                AttributeKind::Deprecated(note) => Some(note.as_deref()),
+
+                _ => None,
+            })
+    }
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self {
+            id: NodeId::DUMMY,
+            kind,
+            span,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOp {
     Add,
     Sub,
@@ -96,28 +339,329 @@ pub enum BinaryOp {
     GreaterEqual,
     And,
     Or,
+    Coalesce,
 }
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
     Negate,
     Not,
 }
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal {
     Number(f64),
     String(String),
     Boolean(bool),
+    Nil,
 }
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
-    Simple(String),
+    Simple(Ident),
     Function(Vec<Type>, Box<Type>), // parameter types, then return type
+    Tuple(Vec<Type>),
+
+    Generic {
+        name: Ident,
+        params: Vec<Type>,
+    },
+    Optional(Box<Type>), // `T?`, which also admits nil
+    Array(Box<Type>),    // `[T]`, a table indexed by 1, 2, 3...
+
+    // a `{ name: T, [K]: V }` table, as in Luau
+    Record {
+        fields: Vec<FieldType>,
+        indexer: Option<Box<(Type, Type)>>,
+    },
+
+    Union(Vec<Type>), // `A | B`
+    Literal(Literal), // `"loading"`, a type with a single value
+
+    // `Some(T)`, a constructor of an algebraic data type
+    Variant {
+        name: Ident,
+        fields: Vec<Type>,
+    },
+}
+
+impl Type {
+    /// The constructors of the algebraic data type this is the body of, with
+    /// the types of their fields. A type alias declares one when its body is a
+    /// `Name(T)` variant or a union with one, as in `Some(T) | None`, and the
+    /// bare names in such a union are constructors without fields.
+    pub fn variants(&self) -> Option<Vec<(Ident, &[Type])>> {
+        let members = match self {
+            Type::Union(members) => &members[..],
+            Type::Variant { .. } => std::slice::from_ref(self),
+
+            _ => return None,
+        };
+
+        if !members
+            .iter()
+            .any(|member| matches!(member, Type::Variant { .. }))
+        {
+            return None;
+        }
+
+        members
+            .iter()
+            .map(|member| match member {
+                Type::Variant { name, fields } => Some((*name, &fields[..])),
+                Type::Simple(name) => Some((*name, &[][..])),
+
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldType {
+    pub name: Ident,
+    pub ty: Type,
+    pub optional: bool, // `name?: T`
+    pub readonly: bool,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter<'_>, types: &[Type]) -> fmt::Result {
+            for (index, ty) in types.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+
+                write!(f, "{}", ty)?;
+            }
+
+            Ok(())
+        }
+
+        match self {
+            Type::Simple(name) => write!(f, "{}", name),
+            Type::Function(params, return_type) => {
+                write!(f, "(")?;
+                list(f, params)?;
+                write!(f, ") -> {}", return_type)
+            }
+
+            Type::Tuple(items) => {
+                write!(f, "(")?;
+                list(f, items)?;
+                write!(f, ")")
+            }
+
+            Type::Generic { name, params } => {
+                write!(f, "{}<", name)?;
+                list(f, params)?;
+                write!(f, ">")
+            }
+
+            Type::Optional(inner) => match **inner {
+                Type::Function(..) | Type::Union(_) => write!(f, "({})?", inner),
+                _ => write!(f, "{}?", inner),
+            },
+
+            Type::Array(element) => write!(f, "[{}]", element),
+
+            Type::Record { fields, indexer } => {
+                write!(f, "{{")?;
+
+                let mut first = true;
+
+                for field in fields {
+                    write!(f, "{}", if first { " " } else { ", " })?;
+                    first = false;
+
+                    if field.readonly {
+                        write!(f, "readonly ")?;
+                    }
+
+                    let optional = if field.optional { "?" } else { "" };
+                    write!(f, "{}{}: {}", field.name, optional, field.ty)?;
+                }
+
+                if let Some(indexer) = indexer {
+                    write!(f, "{}", if first { " " } else { ", " })?;
+                    first = false;
+
+                    write!(f, "[{}]: {}", indexer.0, indexer.1)?;
+                }
+
+                if first {
+                    write!(f, "}}")
+                } else {
+                    write!(f, " }}")
+                }
+            }
+
+            Type::Union(members) => {
+                for (index, member) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " | ")?;
+                    }
+
+                    match member {
+                        Type::Function(..) => write!(f, "({})", member)?,
+                        _ => write!(f, "{}", member)?,
+                    }
+                }
+
+                Ok(())
+            }
+
+            Type::Literal(value) => write!(f, "{}", value),
+            Type::Variant { name, fields } => {
+                write!(f, "{}(", name)?;
+                list(f, fields)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    pub id: NodeId,
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatternKind {
+    Wildcard,
+    Identifier(Ident),
+    Tuple(Vec<Pattern>),
+
+    // `{ name, age }` or `Person { name, age }`
+    Record {
+        name: Option<Ident>,
+        fields: Vec<FieldPattern>,
+    },
+
+    // `Some(value)`
+    Constructor {
+        name: Ident,
+        args: Vec<Pattern>,
+    },
+
+    Literal(Literal),
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldPattern {
+    pub name: Ident,
+    pub pattern: Pattern,
+}
+
+impl Pattern {
+    pub fn new(kind: PatternKind, span: Span) -> Self {
+        Self {
+            id: NodeId::DUMMY,
+            kind,
+            span,
+        }
+    }
+
+    /// Returns the first sub-pattern that can fail to match, if any.
+    pub fn refutable_part(&self) -> Option<&Pattern> {
+        match &self.kind {
+            PatternKind::Wildcard | PatternKind::Identifier(_) => None,
+            PatternKind::Tuple(items) => items.iter().find_map(Pattern::refutable_part),
+            PatternKind::Record { fields, .. } => fields
+                .iter()
+                .find_map(|field| field.pattern.refutable_part()),
+
+            PatternKind::Constructor { .. } | PatternKind::Literal(_) => Some(self),
+        }
+    }
+
+    pub fn is_irrefutable(&self) -> bool {
+        self.refutable_part().is_none()
+    }
+
+    /// Every name introduced by this pattern, in source order.
+    pub fn bindings(&self) -> Vec<&str> {
+        self.identifiers()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Like `bindings`, but with the span of each identifier sub-pattern.
+    pub fn identifiers(&self) -> Vec<(&str, &Span)> {
+        let mut names = Vec::new();
+        self.collect_identifiers(&mut names);
+
+        names
+    }
+
+    fn collect_identifiers<'a>(&'a self, names: &mut Vec<(&'a str, &'a Span)>) {
+        match &self.kind {
+            PatternKind::Identifier(name) => names.push((name, &self.span)),
+            PatternKind::Tuple(items) | PatternKind::Constructor { args: items, .. } => {
+                for item in items {
+                    item.collect_identifiers(names);
+                }
+            }
+
+            PatternKind::Record { fields, .. } => {
+                for field in fields {
+                    field.pattern.collect_identifiers(names);
+                }
+            }
+
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+    }
+}
+
+/// The fenced code blocks in a doc comment that hold exx code: those with no
+/// language tag, or tagged `exx`.
+pub fn doc_examples(docs: &str) -> Vec<String> {
+    let mut examples = Vec::new();
+
+    // `Some(None)` inside a block in another language
+    let mut block: Option<Option<String>> = None;
+
+    for line in docs.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+
+        match (&mut block, fence) {
+            (None, Some(tag)) => {
+                let is_exx = matches!(tag.trim(), "" | "exx");
+                block = Some(is_exx.then(String::new));
+            }
+
+            (Some(example), Some(_)) => {
+                examples.extend(example.take());
+                block = None;
+            }
+
+            (Some(Some(example)), None) => {
+                example.push_str(line);
+                example.push('\n');
+            }
+
+            (Some(None), None) | (None, None) => {}
+        }
+    }
 
-    Generic { name: String, params: Vec<Type> },
+    examples
 }
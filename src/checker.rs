@@ -0,0 +1,3528 @@
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::exhaustiveness::{self, Ctor, Pat, Variants};
+use crate::node::{Ident, NodeMap, Tables};
+use crate::resolver::edit_distance;
+use crate::span::Span;
+use crate::visit::{Visitor, walk_stmt};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Checks class declarations against the interfaces they implement and the
+/// mixins they include, and infers and checks the types of expressions.
+///
+/// Inference is Hindley–Milner style: unannotated parameters and return types
+/// start out as type variables that unification solves, and `let` bindings
+/// and `fn` declarations are generalised, so `fn identity(x) { return x; }`
+/// can be called with both numbers and strings.
+///
+/// Type parameters can be bounded, as in `fn sum<T: Num>`, by the built-in
+/// constraints `Num`, `Eq`, `Ord` and `Show` or by interfaces. A bounded
+/// parameter can be used as its bounds allow, and each use of a generic
+/// function is checked to meet them once inference is done.
+pub fn check(program: &[Stmt]) -> Vec<Diagnostic> {
+    check_with(program, &mut Tables::default())
+}
+
+/// Like `check`, but also records the type of every expression in `tables`.
+pub fn check_with(program: &[Stmt], tables: &mut Tables) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        classes: HashMap::new(),
+        interfaces: HashMap::new(),
+        mixins: HashMap::new(),
+        aliases: HashMap::new(),
+        constructors: HashMap::new(),
+        receiver: Ty::Unknown,
+        constructing: None,
+        scopes: vec![HashMap::new()],
+        return_types: vec![],
+        type_params: vec![],
+        constraints: vec![],
+        obligations: vec![],
+        vars: vec![],
+        level: 0,
+        signatures: NodeMap::new(),
+        types: std::mem::take(&mut tables.types),
+        diagnostics: vec![],
+    };
+
+    checker.collect(program);
+    checker.inclusion();
+    checker.conformance();
+    checker.stmts(program);
+    checker.discharge();
+
+    // only now are the variables in the recorded types as solved as they'll get
+    let mut types = NodeMap::new();
+
+    for (id, ty) in checker.types.iter() {
+        types.insert(id, checker.zonk(ty).widen());
+    }
+
+    tables.types = types;
+
+    checker.diagnostics
+}
+
+/// The parameters a call with `arguments` arguments leaves for a later call,
+/// when it passes fewer than `callee` takes. Leaving out nothing but optional
+/// parameters is still a full call, since Luau passes nil for them.
+pub fn partial(callee: &Ty, arguments: usize) -> Option<&[Ty]> {
+    let Ty::Function(params, _) = callee else {
+        return None;
+    };
+
+    let rest = params.get(arguments..)?;
+    let required = rest.iter().any(|param| !matches!(param, Ty::Optional(_)));
+
+    (arguments > 0 && required).then_some(rest)
+}
+
+/// The type of a value. `Unknown` is the type of anything the checker can't
+/// see into, such as Luau globals, and is compatible with every other type.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Ty {
+    Number,
+    String,
+    Boolean,
+    Class(String),
+    Interface(String),
+    Static(String), // the class itself, as in `Class.method`
+    Function(Vec<Ty>, Box<Ty>),
+    Tuple(Vec<Ty>),
+    Record {
+        fields: Vec<FieldTy>,
+        indexer: Option<Box<(Ty, Ty)>>,
+    },
+    Optional(Box<Ty>), // `T?`: a `T` or nil
+    Nil,
+    Adt {
+        name: String,
+        args: Vec<Ty>,
+    },
+    Union(Vec<Ty>),
+    Literal(Literal), // a string literal, or a literal in a type annotation
+    Var(u32),         // an inference variable, solved by unification
+    Param(String),    // a type parameter, opaque inside the function declaring it
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldTy {
+    pub name: String,
+    pub ty: Ty, // optional fields are `T?`
+    pub readonly: bool,
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter<'_>, types: &[Ty]) -> fmt::Result {
+            for (index, ty) in types.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+
+                write!(f, "{}", ty)?;
+            }
+
+            Ok(())
+        }
+
+        match self {
+            Ty::Number => write!(f, "number"),
+            Ty::String => write!(f, "string"),
+            Ty::Boolean => write!(f, "boolean"),
+            Ty::Class(name) | Ty::Interface(name) => write!(f, "{}", name),
+            Ty::Static(name) => write!(f, "typeof({})", name),
+            Ty::Function(params, return_type) => {
+                write!(f, "(")?;
+                list(f, params)?;
+                write!(f, ") -> {}", return_type)
+            }
+
+            Ty::Tuple(items) => {
+                write!(f, "(")?;
+                list(f, items)?;
+                write!(f, ")")
+            }
+
+            // how `[T]` is represented
+            Ty::Record {
+                fields,
+                indexer: Some(indexer),
+            } if fields.is_empty() && indexer.0 == Ty::Number => {
+                write!(f, "[{}]", indexer.1)
+            }
+
+            Ty::Record { fields, indexer } => {
+                write!(f, "{{ ")?;
+
+                for (index, field) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    if field.readonly {
+                        write!(f, "readonly ")?;
+                    }
+
+                    write!(f, "{}: {}", field.name, field.ty)?;
+                }
+
+                if let Some(indexer) = indexer {
+                    if !fields.is_empty() {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "[{}]: {}", indexer.0, indexer.1)?;
+                }
+
+                write!(f, " }}")
+            }
+
+            Ty::Optional(inner) => match **inner {
+                Ty::Function(..) | Ty::Union(_) => write!(f, "({})?", inner),
+                _ => write!(f, "{}?", inner),
+            },
+
+            Ty::Nil => write!(f, "nil"),
+            Ty::Adt { name, args } if args.is_empty() => write!(f, "{}", name),
+            Ty::Adt { name, args } => {
+                write!(f, "{}<", name)?;
+                list(f, args)?;
+                write!(f, ">")
+            }
+
+            Ty::Union(members) => {
+                for (index, member) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " | ")?;
+                    }
+
+                    write!(f, "{}", member)?;
+                }
+
+                Ok(())
+            }
+
+            Ty::Literal(value) => write!(f, "{}", value),
+            Ty::Var(var) => {
+                write!(f, "'{}", (b'a' + (var % 26) as u8) as char)?;
+
+                match var / 26 {
+                    0 => Ok(()),
+                    round => write!(f, "{}", round),
+                }
+            }
+
+            Ty::Param(name) => write!(f, "{}", name),
+            Ty::Unknown => write!(f, "_"),
+        }
+    }
+}
+
+impl Ty {
+    /// Calls `f` on this type and every type nested in it.
+    fn each(&self, f: &mut impl FnMut(&Ty)) {
+        f(self);
+
+        match self {
+            Ty::Function(params, return_type) => {
+                for param in params {
+                    param.each(f);
+                }
+
+                return_type.each(f);
+            }
+
+            Ty::Tuple(items) | Ty::Union(items) | Ty::Adt { args: items, .. } => {
+                for item in items {
+                    item.each(f);
+                }
+            }
+
+            Ty::Record { fields, indexer } => {
+                for field in fields {
+                    field.ty.each(f);
+                }
+
+                if let Some(indexer) = indexer {
+                    indexer.0.each(f);
+                    indexer.1.each(f);
+                }
+            }
+
+            Ty::Optional(inner) => inner.each(f),
+
+            _ => {}
+        }
+    }
+
+    /// Rebuilds this type, replacing each part `f` returns a type for.
+    fn map(&self, f: &mut impl FnMut(&Ty) -> Option<Ty>) -> Ty {
+        if let Some(ty) = f(self) {
+            return ty;
+        }
+
+        match self {
+            Ty::Function(params, return_type) => Ty::Function(
+                params.iter().map(|param| param.map(f)).collect(),
+                Box::new(return_type.map(f)),
+            ),
+
+            Ty::Tuple(items) => Ty::Tuple(items.iter().map(|item| item.map(f)).collect()),
+            Ty::Record { fields, indexer } => Ty::Record {
+                fields: fields
+                    .iter()
+                    .map(|field| FieldTy {
+                        ty: field.ty.map(f),
+                        ..field.clone()
+                    })
+                    .collect(),
+                indexer: indexer
+                    .as_ref()
+                    .map(|indexer| Box::new((indexer.0.map(f), indexer.1.map(f)))),
+            },
+
+            Ty::Optional(inner) => Ty::Optional(Box::new(inner.map(f))),
+            Ty::Adt { name, args } => Ty::Adt {
+                name: name.clone(),
+                args: args.iter().map(|arg| arg.map(f)).collect(),
+            },
+
+            Ty::Union(members) => Ty::Union(members.iter().map(|member| member.map(f)).collect()),
+
+            ty => ty.clone(),
+        }
+    }
+
+    /// This type with literals outside of unions replaced by their base type,
+    /// as for a `let` without an annotation: `let s = "a"` is a `string`.
+    pub fn widen(&self) -> Ty {
+        self.map(&mut |ty| match ty {
+            Ty::Literal(value) => Some(literal_base(value)),
+            Ty::Union(_) => Some(ty.clone()),
+
+            _ => None,
+        })
+    }
+}
+
+// an inference variable is either solved, or still open at the `let` depth it
+// was created at; generalising only quantifies variables deeper than itself
+#[derive(Clone)]
+enum Var {
+    Solved(Ty),
+    Open { level: u32 },
+}
+
+/// A possibly polymorphic type: `ty` with `vars` and `params` standing for any
+/// type, picked afresh at every use, so long as it meets `constraints`.
+#[derive(Clone, Default)]
+struct Scheme {
+    vars: Vec<u32>,
+    params: Vec<Ident>,
+    constraints: Vec<(Ident, Ident)>, // a parameter and a constraint on it
+    ty: Ty,
+}
+
+impl Scheme {
+    fn mono(ty: Ty) -> Self {
+        Self {
+            ty,
+            ..Self::default()
+        }
+    }
+}
+
+struct ClassInfo<'a> {
+    implements: &'a [Ident],
+    includes: &'a [Ident],
+    fields: HashMap<&'a str, FieldInfo<'a>>,
+    methods: HashMap<&'a str, &'a Stmt>,
+    static_fields: HashMap<&'a str, FieldInfo<'a>>,
+    static_methods: HashMap<&'a str, &'a Stmt>,
+    span: &'a Span,
+}
+
+struct FieldInfo<'a> {
+    ty: Option<&'a Type>,
+    inferred: Option<Ty>, // the type of the initializer, for a field without `ty`
+    mutable: bool,
+    initialized: bool, // whether it has a default, so struct literals can leave it out
+    span: &'a Span,
+}
+
+struct MixinInfo<'a> {
+    methods: HashMap<&'a str, &'a Stmt>,
+}
+
+struct InterfaceInfo<'a> {
+    methods: &'a [MethodSignature],
+}
+
+struct AliasInfo<'a> {
+    params: &'a [Ident],
+    ty: &'a Type,
+}
+
+// the return type of a function being checked, and whether its body has
+// returned a value yet
+struct Return {
+    ty: Ty,
+    seen: bool,
+}
+
+/// The part of a value that doesn't fit the type expected of it. `path` leads
+/// there from the whole value, as in `.address.zip`, and `found` is `None`
+/// when a field is missing altogether.
+struct Mismatch {
+    path: String,
+    expected: Ty,
+    found: Option<Ty>,
+}
+
+// a constraint on a type parameter that a use instantiated, checked once the
+// type standing in for it is known
+struct Obligation {
+    ty: Ty,
+    param: Ident,
+    constraint: Ident,
+    function: String,
+    given: Vec<(Ident, Ident)>, // the constraints in scope at the use
+    span: Span,
+}
+
+#[derive(Clone)]
+struct Binding {
+    scheme: Scheme,
+    deprecation: Option<Deprecation>,
+    narrows: Option<Box<Binding>>, // the binding a narrowed local stands in for
+}
+
+/// What a condition says about the locals it tests: their types where it
+/// holds, and where it doesn't.
+#[derive(Default)]
+struct Narrowing {
+    then: Vec<(String, Ty)>,
+    otherwise: Vec<(String, Ty)>,
+}
+
+impl Narrowing {
+    fn negate(self) -> Self {
+        Narrowing {
+            then: self.otherwise,
+            otherwise: self.then,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Deprecation {
+    note: Option<String>,
+    span: Span, // the `@deprecated` attribute
+}
+
+impl Deprecation {
+    fn of(stmt: &Stmt) -> Option<Self> {
+        stmt.attributes()
+            .iter()
+            .find_map(|attribute| match &attribute.kind {
+                AttributeKind::Deprecated(note) => Some(Deprecation {
+                    note: note.clone(),
+                    span: attribute.span.clone(),
+                }),
+
+                _ => None,
+            })
+    }
+}
+
+struct Checker<'a> {
+    classes: HashMap<&'a str, ClassInfo<'a>>,
+    interfaces: HashMap<&'a str, InterfaceInfo<'a>>,
+    mixins: HashMap<&'a str, MixinInfo<'a>>,
+    aliases: HashMap<&'a str, AliasInfo<'a>>,
+    constructors: HashMap<&'a str, &'a str>, // of algebraic data types, to the type
+    receiver: Ty,                            // the type of `self` in the method being checked
+    constructing: Option<String>,            // the class whose constructor is being checked
+    scopes: Vec<HashMap<String, Binding>>,
+    return_types: Vec<Return>,
+    type_params: Vec<Ident>, // of the functions being checked, innermost last
+    constraints: Vec<(Ident, Ident)>, // on `type_params`
+    obligations: Vec<Obligation>,
+    vars: Vec<Var>,          // indexed by `Ty::Var`
+    level: u32,              // how many generalisable bindings are being checked
+    signatures: NodeMap<Ty>, // hoisted function declarations, by statement
+    types: NodeMap<Ty>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// every class, mixin, interface and type alias, however deeply it's nested
+struct Declarations<'a>(Vec<&'a Stmt>);
+
+impl<'a> Visitor<'a> for Declarations<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::ClassDecl { .. }
+        | StmtKind::MixinDecl { .. }
+        | StmtKind::InterfaceDecl { .. }
+        | StmtKind::TypeAlias { .. } = stmt.kind
+        {
+            self.0.push(stmt);
+        }
+
+        walk_stmt(self, stmt);
+    }
+}
+
+impl<'a> Checker<'a> {
+    // declarations inside functions and blocks are collected too, since
+    // types are looked up by name wherever they're used
+    fn collect(&mut self, stmts: &'a [Stmt]) {
+        let mut declarations = Declarations(vec![]);
+
+        for stmt in stmts {
+            declarations.visit_stmt(stmt);
+        }
+
+        for stmt in declarations.0 {
+            match &stmt.kind {
+                StmtKind::ClassDecl {
+                    name,
+                    implements,
+                    includes,
+                    fields,
+                    methods,
+                    static_fields,
+                    static_methods,
+                    ..
+                } => {
+                    self.classes.insert(
+                        name,
+                        ClassInfo {
+                            implements,
+                            includes,
+                            fields: field_table(fields),
+                            methods: method_table(methods),
+                            static_fields: field_table(static_fields),
+                            static_methods: method_table(static_methods),
+                            span: &stmt.span,
+                        },
+                    );
+                }
+
+                StmtKind::MixinDecl { name, methods, .. } => {
+                    self.mixins.insert(
+                        name,
+                        MixinInfo {
+                            methods: method_table(methods),
+                        },
+                    );
+                }
+
+                StmtKind::InterfaceDecl { name, methods, .. } => {
+                    self.interfaces.insert(name, InterfaceInfo { methods });
+                }
+
+                StmtKind::TypeAlias {
+                    name, params, ty, ..
+                } => {
+                    self.aliases.insert(name, AliasInfo { params, ty });
+
+                    for (variant, _) in ty.variants().unwrap_or_default() {
+                        self.constructors.insert(variant.as_str(), name.as_str());
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn conformance(&mut self) {
+        let mut diagnostics = Vec::new();
+
+        for (class_name, class) in &self.classes {
+            for interface_name in class.implements {
+                let Some(interface) = self.interfaces.get(interface_name.as_str()) else {
+                    let message = if self.classes.contains_key(interface_name.as_str()) {
+                        format!("`{}` is a class, not an interface", interface_name)
+                    } else {
+                        format!("Cannot find interface `{}`", interface_name)
+                    };
+
+                    diagnostics.push(
+                        Diagnostic::error("E200", message)
+                            .with_label(class.span.clone(), "listed in `implements` here"),
+                    );
+
+                    continue;
+                };
+
+                for required in interface.methods {
+                    let Some(method) = self.find_method(class, &required.name) else {
+                        diagnostics.push(
+                            Diagnostic::error(
+                                "E201",
+                                format!(
+                                    "Class `{}` is missing method `{}` required by interface `{}`",
+                                    class_name, required.name, interface_name
+                                ),
+                            )
+                            .with_label(class.span.clone(), "missing method")
+                            .with_label(required.span.clone(), "required by this signature"),
+                        );
+
+                        continue;
+                    };
+
+                    let StmtKind::FunctionDecl {
+                        params,
+                        return_type,
+                        ..
+                    } = &method.kind
+                    else {
+                        unreachable!()
+                    };
+
+                    if !signatures_match(
+                        (&required.params, required.return_type.as_ref()),
+                        (params, return_type.as_ref()),
+                    ) {
+                        let expected = signature(&required.params, required.return_type.as_ref());
+                        let found = signature(params, return_type.as_ref());
+
+                        diagnostics.push(
+                            Diagnostic::error(
+                                "E202",
+                                format!(
+                                    "Method `{}` of class `{}` does not match interface `{}`: expected `{}`, found `{}`",
+                                    required.name, class_name, interface_name, expected, found
+                                ),
+                            )
+                            .with_label(method.span.clone(), format!("found `{}`", found))
+                            .with_label(required.span.clone(), format!("expected `{}`", expected)),
+                        );
+                    }
+                }
+            }
+        }
+
+        // keep the output stable regardless of hash map ordering
+        diagnostics.sort_by_key(|diagnostic| diagnostic.labels[0].span.start.absolute);
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports unknown mixins, and methods defined more than once between a
+    /// class and the mixins it includes.
+    fn inclusion(&mut self) {
+        let mut diagnostics = Vec::new();
+
+        for (class_name, class) in &self.classes {
+            // where each method name first came from: `None` for the class itself
+            let mut seen: HashMap<&str, (Option<&str>, &Stmt)> = class
+                .methods
+                .iter()
+                .map(|(name, method)| (*name, (None, *method)))
+                .collect();
+
+            for mixin_name in class.includes {
+                let Some(mixin) = self.mixins.get(mixin_name.as_str()) else {
+                    diagnostics.push(
+                        Diagnostic::error("E204", format!("Cannot find mixin `{}`", mixin_name))
+                            .with_label(class.span.clone(), "listed in `includes` here"),
+                    );
+
+                    continue;
+                };
+
+                let mut methods: Vec<_> = mixin.methods.iter().collect();
+                methods.sort_by_key(|(_, method)| method.span.start.absolute);
+
+                for (name, method) in methods {
+                    let Some((owner, existing)) = seen.get(name) else {
+                        seen.insert(name, (Some(mixin_name), method));
+                        continue;
+                    };
+
+                    let (message, label) = match owner {
+                        None => (
+                            format!(
+                                "Method `{}` of class `{}` conflicts with mixin `{}`",
+                                name, class_name, mixin_name
+                            ),
+                            format!("defined in class `{}` here", class_name),
+                        ),
+
+                        Some(owner) => (
+                            format!(
+                                "Mixins `{}` and `{}` both define method `{}` included by class `{}`",
+                                owner, mixin_name, name, class_name
+                            ),
+                            format!("defined in mixin `{}` here", owner),
+                        ),
+                    };
+
+                    diagnostics.push(
+                        Diagnostic::error("E205", message)
+                            .with_label(existing.span.clone(), label)
+                            .with_label(
+                                method.span.clone(),
+                                format!("also defined in mixin `{}` here", mixin_name),
+                            ),
+                    );
+                }
+            }
+        }
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.labels[0].span.start.absolute);
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Looks a method up on the class itself, then on its mixins in order.
+    fn find_method(&self, class: &ClassInfo<'a>, name: &str) -> Option<&'a Stmt> {
+        class.methods.get(name).copied().or_else(|| {
+            class.includes.iter().find_map(|mixin| {
+                self.mixins
+                    .get(mixin.as_str())
+                    .and_then(|mixin| mixin.methods.get(name).copied())
+            })
+        })
+    }
+
+    fn resolve(&self, ty: &Type) -> Ty {
+        self.resolve_in(ty, &HashMap::new(), &mut vec![])
+    }
+
+    /// Resolves `ty` with the type parameters in `params` bound, expanding
+    /// aliases. `expanding` holds the aliases being expanded, so that
+    /// recursive ones resolve to `Ty::Unknown` instead of looping.
+    fn resolve_in(&self, ty: &Type, params: &HashMap<&str, Ty>, expanding: &mut Vec<String>) -> Ty {
+        let mut resolve = |ty: &Type| self.resolve_in(ty, params, expanding);
+
+        match ty {
+            Type::Simple(name) => match name.as_str() {
+                _ if params.contains_key(name.as_str()) => params[name.as_str()].clone(),
+                _ if self.type_params.contains(name) => Ty::Param(name.to_string()),
+
+                "number" => Ty::Number,
+                "string" => Ty::String,
+                "boolean" => Ty::Boolean,
+
+                _ if self.classes.contains_key(name.as_str()) => Ty::Class(name.to_string()),
+                _ if self.interfaces.contains_key(name.as_str()) => Ty::Interface(name.to_string()),
+                _ => self.expand(name, &[], expanding),
+            },
+
+            Type::Function(params, return_type) => {
+                let params = params.iter().map(&mut resolve).collect();
+
+                Ty::Function(params, Box::new(resolve(return_type)))
+            }
+
+            Type::Tuple(items) => Ty::Tuple(items.iter().map(resolve).collect()),
+            Type::Generic { name, params } => {
+                let arguments: Vec<_> = params.iter().map(resolve).collect();
+
+                self.expand(name, &arguments, expanding)
+            }
+
+            Type::Optional(inner) => Ty::Optional(Box::new(resolve(inner))),
+            Type::Array(element) => array(resolve(element)),
+            Type::Union(members) => union(members.iter().map(resolve).collect()),
+            Type::Literal(Literal::Nil) => Ty::Nil,
+            Type::Literal(value) => Ty::Literal(value.clone()),
+
+            // only valid as the body of an algebraic data type
+            Type::Variant { .. } => Ty::Unknown,
+
+            Type::Record { fields, indexer } => Ty::Record {
+                fields: fields
+                    .iter()
+                    .map(|field| FieldTy {
+                        name: field.name.to_string(),
+                        ty: match resolve(&field.ty) {
+                            ty if field.optional => optional(ty),
+                            ty => ty,
+                        },
+                        readonly: field.readonly,
+                    })
+                    .collect(),
+                indexer: indexer
+                    .as_ref()
+                    .map(|indexer| Box::new((resolve(&indexer.0), resolve(&indexer.1)))),
+            },
+        }
+    }
+
+    // the type an alias stands for, given its type arguments
+    fn expand(&self, name: &str, arguments: &[Ty], expanding: &mut Vec<String>) -> Ty {
+        let Some(alias) = self.aliases.get(name) else {
+            return Ty::Unknown;
+        };
+
+        if alias.params.len() != arguments.len() || expanding.iter().any(|alias| alias == name) {
+            return Ty::Unknown;
+        }
+
+        // algebraic data types are nominal, and so never expanded
+        if alias.ty.variants().is_some() {
+            return Ty::Adt {
+                name: name.to_string(),
+                args: arguments.to_vec(),
+            };
+        }
+
+        let params = alias
+            .params
+            .iter()
+            .map(Ident::as_str)
+            .zip(arguments.iter().cloned())
+            .collect();
+
+        expanding.push(name.to_string());
+        let ty = self.resolve_in(alias.ty, &params, expanding);
+        expanding.pop();
+
+        ty
+    }
+
+    fn annotation(&self, ty: Option<&Type>) -> Ty {
+        ty.map_or(Ty::Unknown, |ty| self.resolve(ty))
+    }
+
+    fn function_type(&self, params: &[Parameter], return_type: Option<&Type>) -> Ty {
+        Ty::Function(
+            params
+                .iter()
+                .map(|param| self.annotation(param.type_annotation.as_ref()))
+                .collect(),
+            Box::new(self.annotation(return_type)),
+        )
+    }
+
+    // the type of a method or interface method seen from outside, with fresh
+    // variables for its type parameters
+    fn signature_type(
+        &mut self,
+        (type_params, bounds): (&[Ident], &[TypeBound]),
+        params: &[Parameter],
+        return_type: Option<&Type>,
+        (name, span): (&str, &Span),
+    ) -> Ty {
+        let ty = self.generic(type_params, bounds, |this| {
+            this.function_type(params, return_type)
+        });
+
+        let scheme = Scheme {
+            vars: vec![],
+            params: type_params.to_vec(),
+            constraints: constraints(bounds),
+            ty,
+        };
+
+        self.instantiate(&scheme, name, span)
+    }
+
+    /// The parameter and return types a function body is checked against,
+    /// with a fresh variable wherever there's no annotation to go by.
+    fn declared_type(&mut self, params: &[Parameter], return_type: Option<&Type>) -> (Vec<Ty>, Ty) {
+        let params = params
+            .iter()
+            .map(|param| match &param.type_annotation {
+                _ if is_receiver(param) => self.receiver.clone(),
+                Some(ty) => self.resolve(ty),
+                None => self.fresh(),
+            })
+            .collect();
+
+        let return_type = match return_type {
+            Some(ty) => self.resolve(ty),
+            None => self.fresh(),
+        };
+
+        (params, return_type)
+    }
+
+    // runs `f` one level deeper, with `type_params` and their bounds in scope
+    fn generic<T>(
+        &mut self,
+        type_params: &[Ident],
+        bounds: &[TypeBound],
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let depth = (self.type_params.len(), self.constraints.len());
+
+        self.type_params.extend(type_params.iter().cloned());
+        self.constraints.extend(constraints(bounds));
+        self.level += 1;
+
+        let result = f(self);
+
+        self.level -= 1;
+        self.type_params.truncate(depth.0);
+        self.constraints.truncate(depth.1);
+
+        result
+    }
+
+    fn fresh(&mut self) -> Ty {
+        self.vars.push(Var::Open { level: self.level });
+
+        Ty::Var(self.vars.len() as u32 - 1)
+    }
+
+    // follows solved variables until reaching something that isn't one
+    fn shallow(&self, ty: &Ty) -> Ty {
+        match ty {
+            Ty::Var(var) => match &self.vars[*var as usize] {
+                Var::Solved(ty) => self.shallow(ty),
+                Var::Open { .. } => ty.clone(),
+            },
+
+            ty => ty.clone(),
+        }
+    }
+
+    /// `ty` with every solved variable replaced by its solution.
+    fn zonk(&self, ty: &Ty) -> Ty {
+        ty.map(&mut |ty| match ty {
+            Ty::Var(var) => match &self.vars[*var as usize] {
+                Var::Solved(ty) => Some(self.zonk(ty)),
+                Var::Open { .. } => Some(ty.clone()),
+            },
+
+            _ => None,
+        })
+    }
+
+    /// Quantifies the variables in `ty` that were created deeper than the
+    /// current level, and so can't be mentioned by anything in scope.
+    fn generalise(&self, ty: &Ty, type_params: &[Ident], bounds: &[TypeBound]) -> Scheme {
+        let ty = self.zonk(ty);
+        let mut vars = Vec::new();
+
+        ty.each(&mut |ty| {
+            if let Ty::Var(var) = ty
+                && let Var::Open { level } = self.vars[*var as usize]
+                && level > self.level
+                && !vars.contains(var)
+            {
+                vars.push(*var);
+            }
+        });
+
+        Scheme {
+            vars,
+            params: type_params.to_vec(),
+            constraints: constraints(bounds),
+            ty,
+        }
+    }
+
+    // `name` is what's being used at `span`, which has to meet the scheme's constraints
+    fn instantiate(&mut self, scheme: &Scheme, name: &str, span: &Span) -> Ty {
+        if scheme.vars.is_empty() && scheme.params.is_empty() {
+            return scheme.ty.clone();
+        }
+
+        let vars: HashMap<u32, Ty> = scheme.vars.iter().map(|&var| (var, self.fresh())).collect();
+        let params: HashMap<&str, Ty> = scheme
+            .params
+            .iter()
+            .map(|param| (param.as_str(), self.fresh()))
+            .collect();
+
+        for (param, constraint) in &scheme.constraints {
+            let Some(ty) = params.get(param.as_str()) else {
+                continue;
+            };
+
+            self.obligations.push(Obligation {
+                ty: ty.clone(),
+                param: *param,
+                constraint: *constraint,
+                function: name.to_string(),
+                given: self.constraints.clone(),
+                span: span.clone(),
+            });
+        }
+
+        self.zonk(&scheme.ty).map(&mut |ty| match ty {
+            Ty::Var(var) => vars.get(var).cloned(),
+            Ty::Param(param) => params.get(param.as_str()).cloned(),
+
+            _ => None,
+        })
+    }
+
+    /// Makes `a` and `b` the same type by solving variables, returning
+    /// whether that was possible. `Unknown` unifies with anything. Values
+    /// flow from `b` into `a`, so `b` may be a plain `T` where `a` is `T?`
+    /// but not the other way around.
+    fn unify(&mut self, a: &Ty, b: &Ty) -> bool {
+        let (a, b) = (self.shallow(a), self.shallow(b));
+
+        match (&a, &b) {
+            (Ty::Unknown, _) | (_, Ty::Unknown) => true,
+            (Ty::Var(a), Ty::Var(b)) if a == b => true,
+            (Ty::Var(var), ty) | (ty, Ty::Var(var)) => self.solve(*var, ty),
+
+            // `b` flows into `a`, so parameters flow the other way
+            (Ty::Function(a_params, a_return), Ty::Function(b_params, b_return)) => {
+                a_params.len() == b_params.len()
+                    && a_params.iter().zip(b_params).all(|(a, b)| self.unify(b, a))
+                    && self.unify(a_return, b_return)
+            }
+
+            (Ty::Tuple(a), Ty::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.unify(a, b))
+            }
+
+            // nil and plain values both fit an optional, but an optional
+            // value only fits a plain slot once it's narrowed
+            (Ty::Optional(a), Ty::Optional(b)) => self.unify(a, b),
+            (Ty::Optional(_), Ty::Nil) => true,
+            (Ty::Optional(a), b) => self.unify(a, b),
+
+            // only fields both sides have are compared
+            (Ty::Record { fields: a, .. }, Ty::Record { fields: b, .. }) => a.iter().all(|a| {
+                b.iter()
+                    .find(|b| b.name == a.name)
+                    .is_none_or(|b| self.unify(&a.ty, &b.ty))
+            }),
+
+            (Ty::Interface(interface), ty) | (ty, Ty::Interface(interface)) => {
+                self.satisfies(ty, interface).is_ok()
+            }
+
+            (
+                Ty::Adt {
+                    name: a,
+                    args: a_args,
+                },
+                Ty::Adt {
+                    name: b,
+                    args: b_args,
+                },
+            ) => {
+                a == b
+                    && a_args.len() == b_args.len()
+                    && a_args.iter().zip(b_args).all(|(a, b)| self.unify(a, b))
+            }
+
+            // `expect` is stricter about literals where it matters
+            (Ty::Literal(value), ty) | (ty, Ty::Literal(value)) if *ty == literal_base(value) => {
+                true
+            }
+
+            (Ty::Union(a), Ty::Union(b)) => {
+                a.iter().all(|a| b.contains(a)) && b.iter().all(|b| a.contains(b))
+            }
+
+            (Ty::Union(members), ty) => members.iter().any(|member| self.unify(member, ty)),
+            (ty, Ty::Union(members)) => members.iter().any(|member| self.unify(ty, member)),
+
+            (a, b) => a == b,
+        }
+    }
+
+    fn solve(&mut self, var: u32, ty: &Ty) -> bool {
+        let ty = self.zonk(ty).widen();
+        let Var::Open { level } = self.vars[var as usize] else {
+            unreachable!("`unify` only solves open variables")
+        };
+
+        let mut occurs = false;
+        let mut inner = Vec::new();
+
+        ty.each(&mut |ty| match ty {
+            Ty::Var(other) if *other == var => occurs = true,
+            Ty::Var(other) => inner.push(*other),
+
+            _ => {}
+        });
+
+        // a variable can't contain itself, as in `(x) -> x(x)`
+        if occurs {
+            return false;
+        }
+
+        // what `var` stands for is now only as general as `var` itself
+        for other in inner {
+            if let Var::Open { level: other } = &mut self.vars[other as usize] {
+                *other = (*other).min(level);
+            }
+        }
+
+        self.vars[var as usize] = Var::Solved(ty);
+
+        true
+    }
+
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scopes.push(HashMap::new());
+        let result = f(self);
+        self.scopes.pop();
+
+        result
+    }
+
+    fn declare(&mut self, name: &str, ty: Ty) {
+        self.scopes.last_mut().unwrap().insert(
+            name.to_string(),
+            Binding {
+                scheme: Scheme::mono(ty),
+                deprecation: None,
+                narrows: None,
+            },
+        );
+    }
+
+    // declares a function, class or variable, remembering if it's `@deprecated`
+    fn declare_item(&mut self, name: &str, scheme: Scheme, stmt: &Stmt) {
+        self.scopes.last_mut().unwrap().insert(
+            name.to_string(),
+            Binding {
+                scheme,
+                deprecation: Deprecation::of(stmt),
+                narrows: None,
+            },
+        );
+    }
+
+    // gives locals a narrower type for the rest of the current scope
+    fn narrow(&mut self, narrowed: &[(String, Ty)]) {
+        for (name, ty) in narrowed {
+            let Some(binding) = self.binding(name).cloned() else {
+                continue;
+            };
+
+            self.scopes.last_mut().unwrap().insert(
+                name.clone(),
+                Binding {
+                    scheme: Scheme::mono(ty.clone()),
+                    deprecation: binding.deprecation.clone(),
+                    narrows: Some(Box::new(binding)),
+                },
+            );
+        }
+    }
+
+    // assigning to a narrowed local gives it back the type it was declared with
+    fn unnarrow(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            let Some(binding) = scope.get_mut(name) else {
+                continue;
+            };
+
+            if binding.narrows.is_none() {
+                return;
+            }
+
+            while let Some(original) = binding.narrows.take() {
+                *binding = *original;
+            }
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &Pattern, ty: Ty) {
+        match &pattern.kind {
+            PatternKind::Identifier(name) => self.declare(name, ty),
+
+            _ => {
+                for name in pattern.bindings() {
+                    self.declare(name, Ty::Unknown);
+                }
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Ty> {
+        self.binding(name)
+            .map(|binding| self.zonk(&binding.scheme.ty))
+    }
+
+    fn binding(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    // looks up a name at a use site, warning if it's deprecated
+    fn use_name(&mut self, name: &str, span: &Span) -> Option<Ty> {
+        let binding = self.binding(name)?;
+        let scheme = binding.scheme.clone();
+
+        if let Some(deprecation) = binding.deprecation.clone() {
+            self.deprecated(name, &deprecation, span);
+        }
+
+        Some(self.instantiate(&scheme, name, span))
+    }
+
+    fn deprecated(&mut self, name: &str, deprecation: &Deprecation, span: &Span) {
+        let message = match &deprecation.note {
+            Some(note) => format!("`{}` is deprecated: {}", name, note),
+            None => format!("`{}` is deprecated", name),
+        };
+
+        self.diagnostics.push(
+            Diagnostic::warning("W001", message)
+                .with_label(span.clone(), "used here")
+                .with_label(deprecation.span.clone(), "deprecated here"),
+        );
+    }
+
+    /// Checks that `@inline` and `@test` functions have a shape those
+    /// attributes can work with.
+    fn attributes(&mut self, stmt: &Stmt, is_method: bool) {
+        let StmtKind::FunctionDecl {
+            name, params, body, ..
+        } = &stmt.kind
+        else {
+            return;
+        };
+
+        for attribute in stmt.attributes() {
+            let problem = match attribute.kind {
+                AttributeKind::Inline if is_method => Some("methods can't be inlined"),
+                AttributeKind::Inline
+                    if !matches!(
+                        &body[..],
+                        [Stmt {
+                            kind: StmtKind::Return(Some(_)),
+                            ..
+                        }]
+                    ) =>
+                {
+                    Some("an inlined function's body must be a single `return`")
+                }
+
+                AttributeKind::Inline
+                    if params
+                        .iter()
+                        .any(|param| !matches!(param.pattern.kind, PatternKind::Identifier(_))) =>
+                {
+                    Some("an inlined function can't destructure its parameters")
+                }
+
+                AttributeKind::Test if is_method => Some("tests must be free functions"),
+                AttributeKind::Test if !params.is_empty() => Some("tests can't take parameters"),
+
+                _ => None,
+            };
+
+            if let Some(problem) = problem {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        "E209",
+                        format!(
+                            "`@{}` cannot be applied to `{}`",
+                            attribute.kind.name(),
+                            name
+                        ),
+                    )
+                    .with_label(attribute.span.clone(), problem),
+                );
+            }
+        }
+    }
+
+    /// Checks a function body against its parameter and return types. A body
+    /// without an annotated return type that never returns a value returns nil.
+    fn function(
+        &mut self,
+        params: &[Parameter],
+        (param_types, return_type): (Vec<Ty>, Ty),
+        annotated: bool,
+        f: impl FnOnce(&mut Self),
+    ) {
+        self.return_types.push(Return {
+            ty: return_type.clone(),
+            seen: false,
+        });
+
+        self.scoped(|this| {
+            for (param, ty) in params.iter().zip(param_types) {
+                this.declare_pattern(&param.pattern, ty);
+            }
+
+            f(this);
+        });
+
+        let returned = self.return_types.pop().is_some_and(|r| r.seen);
+
+        if !annotated && !returned {
+            self.unify(&return_type, &Ty::Nil);
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        // functions and classes can be used before their declaration, though
+        // functions are only polymorphic once their body has been checked
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::FunctionDecl {
+                    name,
+                    type_params,
+                    bounds,
+                    params,
+                    return_type,
+                    ..
+                } => {
+                    let (params, return_type) = self.generic(type_params, bounds, |this| {
+                        this.declared_type(params, return_type.as_ref())
+                    });
+                    let ty = Ty::Function(params, Box::new(return_type));
+
+                    self.signatures.insert(stmt.id, ty.clone());
+
+                    let scheme = Scheme {
+                        vars: vec![],
+                        params: type_params.clone(),
+                        constraints: constraints(bounds),
+                        ty,
+                    };
+
+                    self.declare_item(name, scheme, stmt);
+                }
+
+                StmtKind::ClassDecl { name, .. } => {
+                    self.declare_item(name, Scheme::mono(Ty::Static(name.to_string())), stmt)
+                }
+
+                // constructors are functions, or values when they have no fields
+                StmtKind::TypeAlias {
+                    name, params, ty, ..
+                } => {
+                    for (variant, fields) in ty.variants().unwrap_or_default() {
+                        let ty = self.generic(params, &[], |this| {
+                            let adt = Ty::Adt {
+                                name: name.to_string(),
+                                args: params
+                                    .iter()
+                                    .map(|param| Ty::Param(param.to_string()))
+                                    .collect(),
+                            };
+
+                            match fields {
+                                [] => adt,
+                                fields => Ty::Function(
+                                    fields.iter().map(|field| this.resolve(field)).collect(),
+                                    Box::new(adt),
+                                ),
+                            }
+                        });
+
+                        let scheme = Scheme {
+                            vars: vec![],
+                            params: params.clone(),
+                            constraints: vec![],
+                            ty,
+                        };
+
+                        self.declare_item(&variant, scheme, stmt);
+                    }
+                }
+
+                StmtKind::ModuleDecl { name, .. } | StmtKind::Import(name) => {
+                    self.declare(name, Ty::Unknown)
+                }
+
+                _ => {}
+            }
+        }
+
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn method(&mut self, method: &Stmt) {
+        self.attributes(method, true);
+
+        if let StmtKind::FunctionDecl {
+            type_params,
+            bounds,
+            params,
+            return_type,
+            body,
+            ..
+        } = &method.kind
+        {
+            self.bounds(bounds);
+
+            self.generic(type_params, bounds, |this| {
+                let signature = this.declared_type(params, return_type.as_ref());
+
+                this.function(params, signature, return_type.is_some(), |this| {
+                    this.stmts(body)
+                });
+            });
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::VariableDecl {
+                is_mutable,
+                pattern,
+                type_annotation,
+                initializer,
+                ..
+            } => {
+                let initialize = |this: &mut Self| {
+                    let declared = this.annotation(type_annotation.as_ref());
+
+                    match initializer {
+                        Some(initializer) => {
+                            let found = this.expr(initializer);
+                            this.expect(&declared, &found, &initializer.span);
+
+                            if type_annotation.is_some() {
+                                declared
+                            } else {
+                                this.zonk(&found).widen()
+                            }
+                        }
+
+                        None => declared,
+                    }
+                };
+
+                // a `var` can be reassigned, so its type has to stay put
+                let scheme = if *is_mutable {
+                    Scheme::mono(initialize(self))
+                } else {
+                    let ty = self.generic(&[], &[], initialize);
+                    self.generalise(&ty, &[], &[])
+                };
+
+                match &pattern.kind {
+                    PatternKind::Identifier(name) => self.declare_item(name, scheme, stmt),
+
+                    _ => self.declare_pattern(pattern, scheme.ty),
+                }
+            }
+
+            StmtKind::FunctionDecl {
+                name,
+                type_params,
+                bounds,
+                params,
+                return_type,
+                body,
+                ..
+            } => {
+                self.attributes(stmt, false);
+                self.bounds(bounds);
+
+                let ty = match self.signatures.get(stmt.id) {
+                    Some(ty) => ty.clone(),
+                    None => {
+                        let (params, return_type) = self.generic(type_params, bounds, |this| {
+                            this.declared_type(params, return_type.as_ref())
+                        });
+
+                        Ty::Function(params, Box::new(return_type))
+                    }
+                };
+
+                let Ty::Function(param_types, own_return) = &ty else {
+                    unreachable!("functions are declared with function types")
+                };
+                let signature = (param_types.clone(), *own_return.clone());
+
+                self.generic(type_params, bounds, |this| {
+                    this.function(params, signature, return_type.is_some(), |this| {
+                        this.stmts(body)
+                    });
+                });
+
+                let scheme = self.generalise(&ty, type_params, bounds);
+                self.declare_item(name, scheme, stmt);
+            }
+
+            StmtKind::ClassDecl {
+                name,
+                fields,
+                methods,
+                static_fields,
+                static_methods,
+                ..
+            } => {
+                for field in fields {
+                    self.field(name, field, false);
+                }
+
+                for field in static_fields {
+                    self.field(name, field, true);
+                }
+
+                // methods aren't in scope as bare names, only through `self` or the class
+                let receiver = std::mem::replace(&mut self.receiver, Ty::Class(name.to_string()));
+
+                for method in methods {
+                    self.scoped(|this| this.method(method));
+                }
+
+                for method in static_methods {
+                    let constructing = is_constructor(method).then(|| name.to_string());
+                    let constructing = std::mem::replace(&mut self.constructing, constructing);
+
+                    self.scoped(|this| this.method(method));
+
+                    self.constructing = constructing;
+                }
+
+                self.receiver = receiver;
+            }
+
+            StmtKind::MixinDecl { methods, .. } => {
+                let receiver = std::mem::replace(&mut self.receiver, Ty::Unknown);
+
+                for method in methods {
+                    self.scoped(|this| this.method(method));
+                }
+
+                self.receiver = receiver;
+            }
+
+            StmtKind::ModuleDecl { declarations, .. } => {
+                self.scoped(|this| this.stmts(declarations))
+            }
+
+            StmtKind::InterfaceDecl { .. } | StmtKind::Import(_) | StmtKind::TypeAlias { .. } => {}
+
+            StmtKind::Assign { target, value, .. } => {
+                let expected = match &target.kind {
+                    ExprKind::PropertyAccess { object, name } => {
+                        let (object, expected) = self.property_access(object, name, &target.span);
+                        self.readonly(&object, name, &target.span);
+
+                        expected
+                    }
+
+                    ExprKind::Identifier(name) => {
+                        self.unnarrow(name);
+                        self.expr(target)
+                    }
+
+                    _ => self.expr(target),
+                };
+
+                let found = self.expr(value);
+
+                self.expect(&expected, &found, &value.span);
+            }
+
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+
+                // `x != nil` narrows `x` in the `then` branch, `x == nil` in the `else` branch
+                let narrowing = self.narrowing(condition);
+
+                self.scoped(|this| {
+                    this.narrow(&narrowing.then);
+                    this.stmts(then_branch);
+                });
+
+                if let Some(else_branch) = else_branch {
+                    self.scoped(|this| {
+                        this.narrow(&narrowing.otherwise);
+                        this.stmts(else_branch);
+                    });
+                }
+
+                // past an `if` with a branch that always leaves, only the other one's narrowing holds
+                let then_exits = exits(then_branch);
+                let else_exits = else_branch.as_deref().is_some_and(exits);
+
+                if then_exits && !else_exits {
+                    self.narrow(&narrowing.otherwise);
+                } else if else_exits && !then_exits {
+                    self.narrow(&narrowing.then);
+                }
+            }
+
+            StmtKind::While { condition, body } => {
+                self.expr(condition);
+
+                let narrowing = self.narrowing(condition);
+
+                self.scoped(|this| {
+                    this.narrow(&narrowing.then);
+                    this.stmts(body);
+                });
+            }
+
+            StmtKind::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                let item = match &iterable.kind {
+                    ExprKind::Range { start, end, .. } => {
+                        self.expr(start);
+                        self.expr(end);
+
+                        Ty::Number
+                    }
+
+                    // `for (key, value) in t` walks a table, anything else walks an array
+                    _ => {
+                        let iterable = self.expr(iterable);
+
+                        match (self.zonk(&iterable), &pattern.kind) {
+                            (
+                                Ty::Record {
+                                    indexer: Some(indexer),
+                                    ..
+                                },
+                                PatternKind::Tuple(items),
+                            ) if items.len() == 2 => {
+                                let (key, value) = *indexer;
+                                Ty::Tuple(vec![key, value])
+                            }
+
+                            (
+                                Ty::Record {
+                                    indexer: Some(indexer),
+                                    ..
+                                },
+                                _,
+                            ) if indexer.0 == Ty::Number => indexer.1,
+
+                            _ => Ty::Unknown,
+                        }
+                    }
+                };
+
+                self.scoped(|this| {
+                    this.declare_pattern(pattern, item);
+                    this.stmts(body);
+                });
+            }
+
+            StmtKind::Expression(expr) => {
+                self.expr(expr);
+            }
+
+            StmtKind::Return(Some(expr)) => {
+                let found = self.expr(expr);
+                let expected = match self.return_types.last_mut() {
+                    Some(expected) => {
+                        expected.seen = true;
+                        expected.ty.clone()
+                    }
+
+                    None => Ty::Unknown,
+                };
+
+                self.expect(&expected, &found, &expr.span);
+            }
+
+            StmtKind::Return(None) | StmtKind::Break | StmtKind::Continue => {}
+        }
+    }
+
+    /// Works out what a condition says about the locals it tests. It
+    /// recognises `x == nil` on an optional, `x == "loading"` on a union of
+    /// literals, a bare optional `x`, and `!`, `&&` and `||` of those.
+    fn narrowing(&mut self, condition: &Expr) -> Narrowing {
+        match &condition.kind {
+            ExprKind::Grouping(inner) => self.narrowing(inner),
+            ExprKind::Unary {
+                op: UnaryOp::Not,
+                expr,
+            } => self.narrowing(expr).negate(),
+
+            ExprKind::Binary {
+                left,
+                op: BinaryOp::And,
+                right,
+            } => {
+                let left = self.narrowing(left).then;
+                let right = self.scoped(|this| {
+                    this.narrow(&left);
+                    this.narrowing(right).then
+                });
+
+                Narrowing {
+                    then: [left, right].concat(),
+                    otherwise: vec![],
+                }
+            }
+
+            ExprKind::Binary {
+                left,
+                op: BinaryOp::Or,
+                right,
+            } => {
+                let left = self.narrowing(left).otherwise;
+                let right = self.scoped(|this| {
+                    this.narrow(&left);
+                    this.narrowing(right).otherwise
+                });
+
+                Narrowing {
+                    then: vec![],
+                    otherwise: [left, right].concat(),
+                }
+            }
+
+            // `if x` rules out nil, unless `x` is a `boolean?` that could also be false
+            ExprKind::Identifier(name) => match self.lookup(name) {
+                Some(Ty::Optional(inner)) if *inner != Ty::Boolean => Narrowing {
+                    then: vec![(name.to_string(), *inner)],
+                    otherwise: vec![],
+                },
+
+                _ => Narrowing::default(),
+            },
+
+            ExprKind::Binary {
+                left,
+                op: op @ (BinaryOp::EqualEqual | BinaryOp::NotEqual),
+                right,
+            } => {
+                let (name, value) = match (&left.kind, &right.kind) {
+                    (ExprKind::Identifier(name), ExprKind::Literal(value))
+                    | (ExprKind::Literal(value), ExprKind::Identifier(name)) => (name, value),
+
+                    _ => return Narrowing::default(),
+                };
+
+                let Some((matching, rest)) = self.lookup(name).and_then(|ty| split(&ty, value))
+                else {
+                    return Narrowing::default();
+                };
+
+                let narrowing = Narrowing {
+                    then: vec![(name.to_string(), matching)],
+                    otherwise: vec![(name.to_string(), rest)],
+                };
+
+                match op {
+                    BinaryOp::NotEqual => narrowing.negate(),
+                    _ => narrowing,
+                }
+            }
+
+            _ => Narrowing::default(),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Ty {
+        let ty = self.infer(expr);
+        self.types.insert(expr.id, ty.clone());
+
+        ty
+    }
+
+    fn infer(&mut self, expr: &Expr) -> Ty {
+        match &expr.kind {
+            // the result is the left side once it's known not to be nil
+            ExprKind::Binary {
+                left,
+                op: BinaryOp::Coalesce,
+                right,
+            } => {
+                let left = self.expr(left);
+                let right = self.expr(right);
+
+                match self.zonk(&left) {
+                    Ty::Optional(inner) => *inner,
+                    Ty::Nil | Ty::Unknown => right,
+
+                    left => left,
+                }
+            }
+
+            ExprKind::Binary {
+                left: left_expr,
+                op,
+                right: right_expr,
+            } => {
+                let left = self.expr(left_expr);
+
+                // the right side of `x != nil && x.ready` only runs once the left is settled
+                let narrowed = match op {
+                    BinaryOp::And => self.narrowing(left_expr).then,
+                    BinaryOp::Or => self.narrowing(left_expr).otherwise,
+
+                    _ => vec![],
+                };
+
+                let right = self.scoped(|this| {
+                    this.narrow(&narrowed);
+                    this.expr(right_expr)
+                });
+
+                match op {
+                    // `+` also joins strings
+                    BinaryOp::Add
+                        if [&left, &right]
+                            .iter()
+                            .any(|ty| self.zonk(ty).widen() == Ty::String) =>
+                    {
+                        // a type parameter joins a string if it's `Show`
+                        for (ty, span) in [(&left, &left_expr.span), (&right, &right_expr.span)] {
+                            if !self.require(ty, "Show", span) {
+                                self.expect(&Ty::String, ty, span);
+                            }
+                        }
+
+                        Ty::String
+                    }
+
+                    BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul
+                    | BinaryOp::Div
+                    | BinaryOp::Mod => match self.param_operand(&left, &right) {
+                        // a type parameter bounded by `Num` stands for some kind of number
+                        Some(param) => {
+                            self.require(&param, "Num", &expr.span);
+                            self.expect(&param, &left, &left_expr.span);
+                            self.expect(&param, &right, &right_expr.span);
+
+                            param
+                        }
+
+                        None => {
+                            self.expect(&Ty::Number, &left, &left_expr.span);
+                            self.expect(&Ty::Number, &right, &right_expr.span);
+
+                            Ty::Number
+                        }
+                    },
+
+                    // both sides are compared, so they must be the same kind of thing
+                    BinaryOp::Less
+                    | BinaryOp::LessEqual
+                    | BinaryOp::Greater
+                    | BinaryOp::GreaterEqual => {
+                        let (left, right) = (self.zonk(&left).widen(), self.zonk(&right).widen());
+                        self.require(&left, "Ord", &expr.span);
+                        self.expect(&left, &right, &right_expr.span);
+
+                        Ty::Boolean
+                    }
+
+                    BinaryOp::EqualEqual | BinaryOp::NotEqual => {
+                        if let Some(param) = self.param_operand(&left, &right) {
+                            self.require(&param, "Eq", &expr.span);
+                        }
+
+                        Ty::Boolean
+                    }
+
+                    // Luau's `and` and `or` return one of their operands
+                    BinaryOp::And | BinaryOp::Or => match (self.zonk(&left), self.zonk(&right)) {
+                        (Ty::Boolean, Ty::Boolean) => Ty::Boolean,
+
+                        _ => Ty::Unknown,
+                    },
+
+                    _ => Ty::Boolean,
+                }
+            }
+
+            ExprKind::Unary { op, expr: operand } => {
+                let ty = self.expr(operand);
+
+                match op {
+                    UnaryOp::Negate => {
+                        self.expect(&Ty::Number, &ty, &operand.span);
+
+                        Ty::Number
+                    }
+
+                    UnaryOp::Not => Ty::Boolean,
+                }
+            }
+
+            ExprKind::Literal(Literal::Number(_)) => Ty::Number,
+            ExprKind::Literal(value @ Literal::String(_)) => Ty::Literal(value.clone()),
+            ExprKind::Literal(Literal::Boolean(_)) => Ty::Boolean,
+            ExprKind::Literal(Literal::Nil) => Ty::Nil,
+            ExprKind::Identifier(name) => match self.use_name(name, &expr.span) {
+                Some(ty) => ty,
+                None => {
+                    self.bare_member(name, &expr.span);
+
+                    Ty::Unknown
+                }
+            },
+            ExprKind::Grouping(inner) => self.expr(inner),
+            ExprKind::Tuple(items) => Ty::Tuple(items.iter().map(|item| self.expr(item)).collect()),
+
+            ExprKind::StructLiteral { name, fields } => {
+                self.use_name(name, &expr.span);
+
+                if self.classes.contains_key(name.as_str()) {
+                    self.struct_literal(name, fields, &expr.span);
+
+                    Ty::Class(name.to_string())
+                } else {
+                    for (_, value) in fields {
+                        self.expr(value);
+                    }
+
+                    Ty::Unknown
+                }
+            }
+
+            ExprKind::Record(fields) => Ty::Record {
+                fields: fields
+                    .iter()
+                    .map(|(name, value)| FieldTy {
+                        name: name.to_string(),
+                        ty: self.expr(value),
+                        readonly: false,
+                    })
+                    .collect(),
+                indexer: None,
+            },
+
+            ExprKind::Block(body) => {
+                self.scoped(|this| this.stmts(body));
+
+                Ty::Unknown
+            }
+
+            ExprKind::PropertyAccess { object, name } => {
+                self.property_access(object, name, &expr.span).1
+            }
+
+            ExprKind::OptionalPropertyAccess { object, name } => {
+                let object = self.expr(object);
+                let object = match self.zonk(&object) {
+                    Ty::Optional(inner) => *inner,
+
+                    object => object,
+                };
+
+                optional(self.member(&object, name, &expr.span))
+            }
+
+            ExprKind::Call { callee, arguments } => {
+                // `a?.f()` calls `f` only when `a` isn't nil
+                let is_optional = matches!(callee.kind, ExprKind::OptionalPropertyAccess { .. });
+
+                let callee = self.expr(callee);
+                let callee = match self.zonk(&callee) {
+                    Ty::Optional(inner) if is_optional => *inner,
+
+                    callee => callee,
+                };
+                let arguments: Vec<_> = arguments
+                    .iter()
+                    .map(|argument| (self.expr(argument), &argument.span))
+                    .collect();
+
+                let rest = partial(&callee, arguments.len())
+                    .filter(|_| !is_optional)
+                    .map(<[Ty]>::to_vec);
+
+                match callee {
+                    // calling something not yet known makes it a function
+                    Ty::Var(_) => {
+                        let return_type = self.fresh();
+                        let params = arguments.iter().map(|(ty, _)| ty.clone()).collect();
+
+                        let ty = Ty::Function(params, Box::new(return_type.clone()));
+                        self.expect(&callee, &ty, &expr.span);
+
+                        return_type
+                    }
+
+                    Ty::Function(params, return_type) => {
+                        for (param, (argument, span)) in params.iter().zip(&arguments) {
+                            self.expect(param, argument, span);
+                        }
+
+                        // only optional parameters can be left out of a call that isn't partial
+                        let required = params
+                            .iter()
+                            .rposition(|param| !matches!(param, Ty::Optional(_)))
+                            .map_or(0, |index| index + 1);
+
+                        if arguments.len() > params.len() {
+                            let span = arguments[params.len()].1.clone();
+                            self.arity(params.len(), arguments.len(), span);
+                        } else if rest.is_none() && arguments.len() < required {
+                            self.arity(required, arguments.len(), expr.span.clone());
+                        }
+
+                        // too few arguments apply the function partly, leaving a function of the rest
+                        if let Some(rest) = rest {
+                            return Ty::Function(rest, return_type);
+                        }
+
+                        if is_optional {
+                            optional(*return_type)
+                        } else {
+                            *return_type
+                        }
+                    }
+
+                    _ => Ty::Unknown,
+                }
+            }
+
+            // a block body returns with `return`, any other body is the result
+            ExprKind::Lambda { params, body } => {
+                let (param_types, return_type) = self.declared_type(params, None);
+                let ty = Ty::Function(param_types.clone(), Box::new(return_type.clone()));
+                let is_block = matches!(body.kind, ExprKind::Block(_));
+
+                self.function(
+                    params,
+                    (param_types, return_type.clone()),
+                    !is_block,
+                    |this| {
+                        let found = this.expr(body);
+
+                        if !is_block {
+                            this.expect(&return_type, &found, &body.span);
+                        }
+                    },
+                );
+
+                ty
+            }
+
+            ExprKind::Range { start, end, .. } => {
+                for bound in [start, end] {
+                    let ty = self.expr(bound);
+                    self.expect(&Ty::Number, &ty, &bound.span);
+                }
+
+                Ty::Unknown
+            }
+
+            // the body is opaque, so its result is whatever the block declares
+            ExprKind::Luau {
+                return_type,
+                bindings,
+                ..
+            } => {
+                for (name, span) in bindings {
+                    if self.lookup(name).is_none() {
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                "E208",
+                                format!("Cannot find local `{}` to pass into luau block", name),
+                            )
+                            .with_label(span.clone(), "not found in this scope"),
+                        );
+                    }
+                }
+
+                self.annotation(return_type.as_ref())
+            }
+
+            ExprKind::Match { scrutinee, arms } => {
+                let scrutinee_ty = self.expr(scrutinee);
+                let mut result: Option<Ty> = None;
+
+                // a literal arm sees just its literal, and a catch-all arm only
+                // what no arm above it took; a matched local is narrowed to match
+                let name = match &scrutinee.kind {
+                    ExprKind::Identifier(name) => Some(name),
+                    _ => None,
+                };
+
+                let mut remaining = self.zonk(&scrutinee_ty);
+
+                for arm in arms {
+                    let narrowed = match &arm.pattern.kind {
+                        PatternKind::Literal(value) => {
+                            split(&remaining, value).map(|(matching, rest)| {
+                                remaining = rest;
+                                matching
+                            })
+                        }
+
+                        PatternKind::Wildcard | PatternKind::Identifier(_)
+                            if remaining != self.zonk(&scrutinee_ty) =>
+                        {
+                            Some(remaining.clone())
+                        }
+
+                        _ => None,
+                    };
+
+                    let ty = self.scoped(|this| {
+                        this.pattern(&arm.pattern, &scrutinee_ty);
+
+                        if let Some(narrowed) = narrowed {
+                            if let PatternKind::Identifier(binding) = &arm.pattern.kind
+                                && !this.constructors.contains_key(binding.as_str())
+                            {
+                                this.declare(binding, narrowed.clone());
+                            }
+
+                            if let Some(name) = name {
+                                this.narrow(&[(name.to_string(), narrowed)]);
+                            }
+                        }
+
+                        this.expr(&arm.body)
+                    });
+
+                    result = Some(match result {
+                        Some(result) => self.join(result, ty, &arm.body.span),
+                        None => ty,
+                    });
+                }
+
+                self.exhaustiveness(scrutinee, &scrutinee_ty, arms);
+
+                result.unwrap_or_default()
+            }
+        }
+    }
+
+    /// Checks a `match` pattern against the type of the value being matched,
+    /// declaring the names it binds.
+    fn pattern(&mut self, pattern: &Pattern, ty: &Ty) {
+        match &pattern.kind {
+            PatternKind::Wildcard => {}
+
+            PatternKind::Identifier(name) if self.constructors.contains_key(name.as_str()) => {
+                self.constructor_pattern(pattern, name, &[], ty)
+            }
+
+            PatternKind::Identifier(name) => self.declare(name, ty.clone()),
+
+            PatternKind::Constructor { name, args } => {
+                self.constructor_pattern(pattern, name, args, ty)
+            }
+
+            PatternKind::Tuple(items) => {
+                let types = match self.zonk(ty) {
+                    Ty::Tuple(types) if types.len() == items.len() => types,
+                    ty => {
+                        let types: Vec<_> = items.iter().map(|_| self.fresh()).collect();
+                        self.expect(&ty, &Ty::Tuple(types.clone()), &pattern.span);
+
+                        types
+                    }
+                };
+
+                for (item, ty) in items.iter().zip(&types) {
+                    self.pattern(item, ty);
+                }
+            }
+
+            PatternKind::Record { fields, .. } => {
+                let object = self.zonk(ty);
+
+                for field in fields {
+                    let ty = self.member(&object, &field.name, &field.pattern.span);
+                    self.pattern(&field.pattern, &ty);
+                }
+            }
+
+            PatternKind::Literal(value) => {
+                let found = match value {
+                    Literal::Nil => Ty::Nil,
+                    value => Ty::Literal(value.clone()),
+                };
+
+                self.expect(ty, &found, &pattern.span);
+            }
+        }
+    }
+
+    fn constructor_pattern(&mut self, pattern: &Pattern, name: &str, args: &[Pattern], ty: &Ty) {
+        let (fields, adt) = match self.use_name(name, &pattern.span) {
+            Some(Ty::Function(fields, adt)) => (fields, *adt),
+            Some(adt) => (vec![], adt),
+            None => (vec![], Ty::Unknown),
+        };
+
+        self.expect(ty, &adt, &pattern.span);
+
+        if fields.len() != args.len() {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E215",
+                    format!(
+                        "This pattern has {} field(s), but `{}` has {}",
+                        args.len(),
+                        name,
+                        fields.len()
+                    ),
+                )
+                .with_label(pattern.span.clone(), format!("expected {}", fields.len())),
+            );
+        }
+
+        for (index, arg) in args.iter().enumerate() {
+            let field = fields.get(index).cloned().unwrap_or_default();
+            self.pattern(arg, &field);
+        }
+    }
+
+    // the type of a `match` with arms of types `a` and `b`; literals from
+    // different arms make a union, as in `match ok { true => "yes", false => "no" }`
+    fn join(&mut self, a: Ty, b: Ty, span: &Span) -> Ty {
+        let (a, b) = (self.zonk(&a), self.zonk(&b));
+
+        let literals = |ty: &Ty| match ty {
+            Ty::Literal(_) => Some(vec![ty.clone()]),
+            Ty::Union(members) if members.iter().all(|m| matches!(m, Ty::Literal(_))) => {
+                Some(members.clone())
+            }
+
+            _ => None,
+        };
+
+        if a != b
+            && let (Some(a), Some(b)) = (literals(&a), literals(&b))
+        {
+            return union(a.into_iter().chain(b).collect());
+        }
+
+        self.expect(&a, &b, span);
+
+        a
+    }
+
+    /// Reports values no arm of a `match` handles, and arms that can never be
+    /// reached because the arms above them already match everything they do.
+    fn exhaustiveness(&mut self, scrutinee: &Expr, ty: &Ty, arms: &[MatchArm]) {
+        let patterns: Vec<Pat> = arms.iter().map(|arm| self.shape(&arm.pattern)).collect();
+        let report = exhaustiveness::check(self, &patterns, &self.zonk(ty));
+
+        for index in report.unreachable {
+            self.diagnostics.push(
+                Diagnostic::warning("W002", "Unreachable pattern")
+                    .with_label(
+                        arms[index].pattern.span.clone(),
+                        "this arm is never reached",
+                    )
+                    .with_note("every value it matches is matched by an earlier arm"),
+            );
+        }
+
+        if report.missing.is_empty() {
+            return;
+        }
+
+        let missing: Vec<_> = report
+            .missing
+            .iter()
+            .map(|pat| format!("`{}`", pat))
+            .collect();
+
+        let listed = match &missing[..] {
+            [one] => format!("pattern {} not covered", one),
+            [init @ .., last] if missing.len() <= 3 => {
+                format!("patterns {} and {} not covered", init.join(", "), last)
+            }
+
+            _ => format!("patterns {} and more not covered", missing[..3].join(", ")),
+        };
+
+        self.diagnostics.push(
+            Diagnostic::error("E214", "Non-exhaustive match")
+                .with_label(scrutinee.span.clone(), listed)
+                .with_note(format!(
+                    "the value being matched has type `{}`",
+                    self.zonk(ty)
+                ))
+                .with_note("help: add an arm for each missing pattern, or a catch-all `_` arm"),
+        );
+    }
+
+    // a pattern as the exhaustiveness checker sees it
+    fn shape(&self, pattern: &Pattern) -> Pat {
+        let shapes = |patterns: &[Pattern]| patterns.iter().map(|p| self.shape(p)).collect();
+
+        match &pattern.kind {
+            PatternKind::Identifier(name) if self.constructors.contains_key(name.as_str()) => {
+                Pat::Ctor(Ctor::Variant(name.to_string()), vec![])
+            }
+
+            PatternKind::Wildcard | PatternKind::Identifier(_) => Pat::Wild,
+            PatternKind::Constructor { name, args } => {
+                Pat::Ctor(Ctor::Variant(name.to_string()), shapes(args))
+            }
+
+            PatternKind::Tuple(items) => Pat::Ctor(Ctor::Tuple(items.len()), shapes(items)),
+            PatternKind::Record { fields, .. } => Pat::Ctor(
+                Ctor::Record(fields.iter().map(|field| field.name.to_string()).collect()),
+                fields
+                    .iter()
+                    .map(|field| self.shape(&field.pattern))
+                    .collect(),
+            ),
+
+            PatternKind::Literal(value) => Pat::Ctor(Ctor::Literal(value.clone()), vec![]),
+        }
+    }
+
+    /// Checks a field's default value against its annotation. A field without
+    /// an annotation takes the type of its default from then on.
+    fn field(&mut self, class_name: &str, field: &Stmt, is_static: bool) {
+        let StmtKind::VariableDecl {
+            pattern:
+                Pattern {
+                    kind: PatternKind::Identifier(name),
+                    ..
+                },
+            type_annotation,
+            initializer: Some(initializer),
+            ..
+        } = &field.kind
+        else {
+            return;
+        };
+
+        let declared = self.annotation(type_annotation.as_ref());
+        let found = self.expr(initializer);
+        self.expect(&declared, &found, &initializer.span);
+
+        if type_annotation.is_some() {
+            return;
+        }
+
+        let ty = self.zonk(&found).widen();
+        let class = self.classes.get_mut(class_name).unwrap();
+        let fields = if is_static {
+            &mut class.static_fields
+        } else {
+            &mut class.fields
+        };
+
+        if let Some(field) = fields.get_mut(name.as_str()) {
+            field.inferred = Some(ty);
+        }
+    }
+
+    fn field_type(&self, field: &FieldInfo) -> Ty {
+        match field.ty {
+            Some(ty) => self.resolve(ty),
+            None => field.inferred.clone().unwrap_or(Ty::Unknown),
+        }
+    }
+
+    /// Checks `Name { field: value, ... }` against the fields `Name` declares:
+    /// each value has to fit its field, and every field without a default
+    /// has to be given.
+    fn struct_literal(&mut self, class_name: &str, fields: &[(Ident, Expr)], span: &Span) {
+        for (name, value) in fields {
+            let found = self.expr(value);
+            let class = &self.classes[class_name];
+
+            let Some(field) = class.fields.get(name.as_str()) else {
+                let mut diagnostic = Diagnostic::error(
+                    "E217",
+                    format!("Class `{}` has no field `{}`", class_name, name),
+                )
+                .with_label(value.span.clone(), "unknown field");
+
+                if class.static_fields.contains_key(name.as_str()) {
+                    diagnostic = diagnostic.with_note(format!(
+                        "`{}` is a static field, which belongs to the class rather than each instance",
+                        name
+                    ));
+                } else if let Some(similar) = closest(name, class.fields.keys().copied()) {
+                    diagnostic = diagnostic.with_note(format!("help: did you mean `{}`?", similar));
+                }
+
+                self.diagnostics.push(diagnostic);
+                continue;
+            };
+
+            let expected = self.field_type(field);
+            self.expect(&expected, &found, &value.span);
+        }
+
+        let class = &self.classes[class_name];
+
+        let mut missing: Vec<_> = class
+            .fields
+            .iter()
+            .filter(|(name, field)| {
+                !field.initialized && !fields.iter().any(|(given, _)| given == *name)
+            })
+            .map(|(name, field)| (field.span.start.absolute, format!("`{}`", name)))
+            .collect();
+
+        if missing.is_empty() {
+            return;
+        }
+
+        missing.sort();
+
+        let names: Vec<String> = missing.into_iter().map(|(_, name)| name).collect();
+        let (noun, list) = match &names[..] {
+            [name] => ("field", name.clone()),
+            [rest @ .., last] => ("fields", format!("{} and {}", rest.join(", "), last)),
+            [] => unreachable!(),
+        };
+
+        self.diagnostics.push(
+            Diagnostic::error(
+                "E218",
+                format!("Missing {} {} in `{}`", noun, list, class_name),
+            )
+            .with_label(span.clone(), format!("missing {}", list))
+            .with_note("only fields with a default value can be left out"),
+        );
+    }
+
+    /// Checks `object.name`, returning the type of `object` and of the member.
+    fn property_access(&mut self, object: &Expr, name: &str, span: &Span) -> (Ty, Ty) {
+        let object_ty = self.expr(object);
+        let object_ty = self.zonk(&object_ty);
+
+        if let Ty::Optional(inner) = &object_ty {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E210",
+                    format!(
+                        "Cannot access `{}` on optional type `{}` without narrowing",
+                        name, object_ty
+                    ),
+                )
+                .with_label(object.span.clone(), "this may be nil")
+                .with_note(format!(
+                    "help: use `?.{}`, or check that the value `!= nil` first",
+                    name
+                )),
+            );
+
+            let member = self.member(inner, name, span);
+
+            return (*inner.clone(), member);
+        }
+
+        let member = self.member(&object_ty, name, span);
+
+        (object_ty, member)
+    }
+
+    /// Reports assignments to `readonly` record fields, and to `let` fields of
+    /// classes outside their constructor.
+    fn readonly(&mut self, object: &Ty, name: &str, span: &Span) {
+        let field = match object {
+            Ty::Class(class_name) if self.constructing.as_ref() != Some(class_name) => {
+                let class = &self.classes[class_name.as_str()];
+                class.fields.get(name).map(|field| (class_name, field))
+            }
+
+            Ty::Static(class_name) => {
+                let class = &self.classes[class_name.as_str()];
+                class
+                    .static_fields
+                    .get(name)
+                    .map(|field| (class_name, field))
+            }
+
+            _ => None,
+        };
+
+        if let Some((class_name, field)) = field.filter(|(_, field)| !field.mutable) {
+            let note = match object {
+                Ty::Class(_) => format!(
+                    "`let` fields can only be assigned in the constructor, `{}.new`",
+                    class_name
+                ),
+
+                _ => "static `let` fields can't be assigned after their declaration".to_string(),
+            };
+
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E213",
+                    format!(
+                        "Cannot assign to immutable field `{}` of class `{}`",
+                        name, class_name
+                    ),
+                )
+                .with_label(span.clone(), "assigned here")
+                .with_label(field.span.clone(), "declared with `let` here")
+                .with_note(note)
+                .with_note(format!(
+                    "help: declare `{}` with `var` to make it mutable",
+                    name
+                )),
+            );
+
+            return;
+        }
+
+        let Ty::Record { fields, .. } = object else {
+            return;
+        };
+
+        if fields
+            .iter()
+            .any(|field| field.name == name && field.readonly)
+        {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E211",
+                    format!("Cannot assign to readonly field `{}`", name),
+                )
+                .with_label(span.clone(), "assigned here")
+                .with_note(format!("`{}` is declared `readonly` in `{}`", name, object)),
+            );
+        }
+    }
+
+    fn member(&mut self, object: &Ty, name: &str, span: &Span) -> Ty {
+        match object {
+            Ty::Record { fields, indexer } => {
+                match fields.iter().find(|field| field.name == name) {
+                    Some(field) => field.ty.clone(),
+                    None => match indexer.as_deref() {
+                        Some((Ty::String | Ty::Unknown, value)) => value.clone(),
+
+                        _ => Ty::Unknown,
+                    },
+                }
+            }
+
+            Ty::Class(class_name) => {
+                let class = &self.classes[class_name.as_str()];
+
+                if let Some(field) = class.fields.get(name) {
+                    return self.field_type(field);
+                }
+
+                if let Some(method) = self.find_method(class, name) {
+                    if let Some(deprecation) = Deprecation::of(method) {
+                        self.deprecated(name, &deprecation, span);
+                    }
+
+                    return self.method_type(method, true, name, span);
+                }
+
+                if class.static_fields.contains_key(name) || class.static_methods.contains_key(name)
+                {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            "E207",
+                            format!(
+                                "Static member `{}` of class `{}` cannot be accessed through an instance",
+                                name, class_name
+                            ),
+                        )
+                        .with_label(span.clone(), "accessed through an instance here")
+                        .with_note(format!("help: use `{}.{}` instead", class_name, name)),
+                    );
+
+                    return Ty::Unknown;
+                }
+
+                let members = self.instance_members(class);
+                self.unknown_member(format!("Class `{}`", class_name), name, members, span);
+
+                Ty::Unknown
+            }
+
+            Ty::Static(class_name) => {
+                let class = &self.classes[class_name.as_str()];
+
+                if let Some(field) = class.static_fields.get(name) {
+                    return self.field_type(field);
+                }
+
+                if let Some(&method) = class.static_methods.get(name) {
+                    if let Some(deprecation) = Deprecation::of(method) {
+                        self.deprecated(name, &deprecation, span);
+                    }
+
+                    return self.method_type(method, false, name, span);
+                }
+
+                if let Some(method) = self.find_method(class, name) {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            "E206",
+                            format!(
+                                "Instance method `{}` of class `{}` cannot be called without a receiver",
+                                name, class_name
+                            ),
+                        )
+                        .with_label(span.clone(), "accessed on the class here")
+                        .with_note(format!(
+                            "help: call it on an instance, as in `value.{}()`",
+                            name
+                        )),
+                    );
+
+                    // checked as the instance call it was meant to be
+                    return self.method_type(method, true, name, span);
+                }
+
+                let members = class
+                    .static_fields
+                    .keys()
+                    .chain(class.static_methods.keys())
+                    .copied()
+                    .collect();
+
+                self.unknown_member(format!("Class `{}`", class_name), name, members, span);
+
+                Ty::Unknown
+            }
+
+            Ty::Interface(interface) => {
+                let methods = self.interfaces[interface.as_str()].methods;
+
+                match methods.iter().find(|method| method.name == name) {
+                    Some(method) => self.signature_type(
+                        (&method.type_params, &method.bounds),
+                        &method.params[1..],
+                        method.return_type.as_ref(),
+                        (name, span),
+                    ),
+
+                    None => {
+                        let members = methods.iter().map(|method| method.name.as_str()).collect();
+                        self.unknown_member(
+                            format!("Interface `{}`", interface),
+                            name,
+                            members,
+                            span,
+                        );
+
+                        Ty::Unknown
+                    }
+                }
+            }
+
+            // a type parameter has the methods of the interfaces bounding it
+            Ty::Param(param) => {
+                let interface = self
+                    .constraints
+                    .iter()
+                    .rev()
+                    .filter(|(bounded, _)| bounded == param)
+                    .find(|(_, constraint)| {
+                        self.interfaces
+                            .get(constraint.as_str())
+                            .is_some_and(|info| {
+                                info.methods.iter().any(|method| method.name == name)
+                            })
+                    })
+                    .map(|(_, interface)| interface.to_string());
+
+                match interface {
+                    Some(interface) => self.member(&Ty::Interface(interface), name, span),
+                    None => Ty::Unknown,
+                }
+            }
+
+            _ => Ty::Unknown,
+        }
+    }
+
+    // the fields and methods of a class's instances, including the methods of its mixins
+    fn instance_members(&self, class: &ClassInfo<'a>) -> Vec<&'a str> {
+        let mixins = class
+            .includes
+            .iter()
+            .filter_map(|mixin| self.mixins.get(mixin.as_str()));
+
+        class
+            .fields
+            .keys()
+            .chain(class.methods.keys())
+            .chain(mixins.flat_map(|mixin| mixin.methods.keys()))
+            .copied()
+            .collect()
+    }
+
+    fn unknown_member(&mut self, owner: String, name: &str, members: Vec<&str>, span: &Span) {
+        let mut diagnostic =
+            Diagnostic::error("E216", format!("{} has no member named `{}`", owner, name))
+                .with_label(span.clone(), "unknown member");
+
+        if let Some(similar) = closest(name, members.into_iter()) {
+            diagnostic = diagnostic.with_note(format!("help: did you mean `{}`?", similar));
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Reports a field or method of the enclosing class named without `self.`.
+    fn bare_member(&mut self, name: &str, span: &Span) {
+        let Ty::Class(class_name) = &self.receiver else {
+            return;
+        };
+
+        let class = &self.classes[class_name.as_str()];
+
+        if class.fields.contains_key(name) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E206",
+                    format!(
+                        "Field `{}` of class `{}` cannot be used without a receiver",
+                        name, class_name
+                    ),
+                )
+                .with_label(span.clone(), "used without a receiver")
+                .with_note(format!("help: use it as `self.{}`", name)),
+            );
+
+            return;
+        }
+
+        if self.find_method(class, name).is_some() {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E206",
+                    format!(
+                        "Instance method `{}` of class `{}` cannot be called without a receiver",
+                        name, class_name
+                    ),
+                )
+                .with_label(span.clone(), "called without a receiver")
+                .with_note(format!("help: call it as `self.{}()`", name)),
+            );
+        }
+    }
+
+    // a method accessed through an instance has `self` already bound
+    fn method_type(&mut self, method: &Stmt, bound: bool, name: &str, span: &Span) -> Ty {
+        match &method.kind {
+            StmtKind::FunctionDecl {
+                type_params,
+                bounds,
+                params,
+                return_type,
+                ..
+            } => {
+                let params = if bound { &params[1..] } else { &params[..] };
+                self.signature_type(
+                    (type_params, bounds),
+                    params,
+                    return_type.as_ref(),
+                    (name, span),
+                )
+            }
+
+            _ => Ty::Unknown,
+        }
+    }
+
+    /// Reports `found` at `span` unless it fits where an `expected` is needed.
+    fn expect(&mut self, expected: &Ty, found: &Ty, span: &Span) {
+        let (expected, found) = (self.zonk(expected), self.zonk(found));
+
+        // nil always fits an optional slot, and an optional value fits it as a whole
+        if let Ty::Optional(inner) = &expected {
+            return match found {
+                Ty::Nil => {}
+                Ty::Optional(found) => self.expect(inner, &found, span),
+
+                found => self.expect(inner, &found, span),
+            };
+        }
+
+        // a union or literal only admits values it lists
+        if let Ty::Union(_) | Ty::Literal(_) = &expected
+            && !matches!(found, Ty::Var(_) | Ty::Unknown)
+        {
+            let members = match &expected {
+                Ty::Union(members) => members.clone(),
+                expected => vec![expected.clone()],
+            };
+
+            if !self.fits(&members, &found) {
+                self.mismatch(&expected, &found, span);
+            }
+
+            return;
+        }
+
+        if let Ty::Interface(interface) = &expected {
+            // literals only matter for their base type here
+            let found = found.widen();
+
+            if let Err(reason) = self.satisfies(&found, interface) {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        "E203",
+                        format!("`{}` does not satisfy interface `{}`", found, interface),
+                    )
+                    .with_label(span.clone(), reason),
+                );
+            }
+
+            return;
+        }
+
+        let Err(mismatch) = self.subtype(&found, &expected, "") else {
+            return;
+        };
+
+        let Mismatch {
+            path,
+            expected: part,
+            found: found_part,
+        } = mismatch;
+
+        let Some(found_part) = found_part else {
+            let label = format!("{}: missing, expected `{}`", path, part);
+            let note = format!(
+                "the value has type `{}`, which doesn't fit `{}`",
+                found.widen(),
+                expected
+            );
+
+            self.diagnostics.push(
+                Diagnostic::error("E212", "Mismatched types")
+                    .with_label(span.clone(), label)
+                    .with_note(note),
+            );
+
+            return;
+        };
+
+        let found = found.widen();
+        let whole = (!path.is_empty()).then_some((&expected, &found));
+        self.mismatch_at(&path, &part, &found_part, span, whole);
+    }
+
+    /// Checks that a `found` value fits where an `expected` one is needed.
+    /// Records with extra fields fit records with fewer, fields can be more
+    /// specific than expected, and a function fits if its parameters are at
+    /// least as general as expected and its result fits. A value that may be
+    /// nil only fits an optional slot. Anything else has to unify.
+    fn subtype(&mut self, found: &Ty, expected: &Ty, path: &str) -> Result<(), Mismatch> {
+        let (found, expected) = (self.shallow(found), self.shallow(expected));
+
+        let mismatch = |found: &Ty| {
+            Err(Mismatch {
+                path: path.to_string(),
+                expected: expected.clone(),
+                found: Some(found.widen()),
+            })
+        };
+
+        let missing = |name: &str, expected: &Ty| {
+            Err(Mismatch {
+                path: format!("{}.{}", path, name),
+                expected: expected.clone(),
+                found: None,
+            })
+        };
+
+        match (&found, &expected) {
+            (Ty::Var(_) | Ty::Unknown, _) | (_, Ty::Var(_) | Ty::Unknown) => {}
+
+            // nil and plain values both fit an optional
+            (Ty::Nil, Ty::Optional(_)) => return Ok(()),
+            (Ty::Optional(found), Ty::Optional(expected)) => {
+                return self.subtype(found, expected, path);
+            }
+            (found, Ty::Optional(expected)) => return self.subtype(found, expected, path),
+
+            // a union or literal only admits values it lists
+            (_, Ty::Union(_) | Ty::Literal(_)) => {
+                let members = match &expected {
+                    Ty::Union(members) => members.clone(),
+                    expected => vec![expected.clone()],
+                };
+
+                if !self.fits(&members, &found) {
+                    return Err(Mismatch {
+                        path: path.to_string(),
+                        expected: expected.clone(),
+                        found: Some(found.clone()),
+                    });
+                }
+
+                return Ok(());
+            }
+
+            // a value that may be nil has to be narrowed before it fits a plain slot
+            (Ty::Optional(_) | Ty::Nil, _) => return mismatch(&found),
+
+            (
+                Ty::Record {
+                    fields: found_fields,
+                    indexer: found_indexer,
+                },
+                Ty::Record { fields, indexer },
+            ) => {
+                for field in fields {
+                    let path = format!("{}.{}", path, field.name);
+
+                    match found_fields.iter().find(|found| found.name == field.name) {
+                        Some(found) => self.subtype(&found.ty, &field.ty, &path)?,
+
+                        // reading a field that isn't there just gives nil
+                        None if matches!(field.ty, Ty::Optional(_)) => {}
+                        None => return missing(&field.name, &field.ty),
+                    }
+                }
+
+                // the fields `expected` doesn't name have to fit its indexer
+                if let Some((key, value)) = indexer.as_deref() {
+                    match found_indexer.as_deref() {
+                        Some((found_key, found_value)) => {
+                            if !self.unify(key, found_key) {
+                                return mismatch(&found);
+                            }
+
+                            self.subtype(found_value, value, &format!("{}[{}]", path, key))?;
+                        }
+
+                        None if self.unify(key, &Ty::String) => {
+                            let extra = found_fields.iter().filter(|found| {
+                                fields.iter().all(|field| field.name != found.name)
+                            });
+
+                            for found in extra {
+                                self.subtype(
+                                    &found.ty,
+                                    value,
+                                    &format!("{}.{}", path, found.name),
+                                )?;
+                            }
+                        }
+
+                        None => return mismatch(&found),
+                    }
+                }
+
+                return Ok(());
+            }
+
+            // an instance is a table with its class's fields
+            (Ty::Class(class_name), Ty::Record { fields, .. }) => {
+                for field in fields {
+                    let class = &self.classes[class_name.as_str()];
+
+                    match class.fields.get(field.name.as_str()) {
+                        Some(found) => {
+                            let found = self.field_type(found);
+                            self.subtype(&found, &field.ty, &format!("{}.{}", path, field.name))?;
+                        }
+
+                        None if matches!(field.ty, Ty::Optional(_)) => {}
+                        None => return missing(&field.name, &field.ty),
+                    }
+                }
+
+                return Ok(());
+            }
+
+            // parameters are checked the other way around, since the function receives them
+            (Ty::Function(found_params, found_return), Ty::Function(params, return_type)) => {
+                let fits = found_params.len() == params.len()
+                    && params
+                        .iter()
+                        .zip(found_params)
+                        .all(|(param, found)| self.subtype(param, found, "").is_ok())
+                    && self.subtype(found_return, return_type, "").is_ok();
+
+                return if fits { Ok(()) } else { mismatch(&found) };
+            }
+
+            (Ty::Tuple(found_items), Ty::Tuple(items)) if found_items.len() == items.len() => {
+                for (index, (found, expected)) in found_items.iter().zip(items).enumerate() {
+                    self.subtype(found, expected, &format!("{}.{}", path, index))?;
+                }
+
+                return Ok(());
+            }
+
+            _ => {}
+        }
+
+        if self.unify(&expected, &found) {
+            Ok(())
+        } else {
+            mismatch(&found)
+        }
+    }
+
+    fn arity(&mut self, expected: usize, found: usize, span: Span) {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+
+        self.diagnostics.push(
+            Diagnostic::error(
+                "E221",
+                format!(
+                    "Expected {} argument{}, found {}",
+                    expected,
+                    plural(expected),
+                    found
+                ),
+            )
+            .with_label(
+                span,
+                format!("this call passes {} argument{}", found, plural(found)),
+            ),
+        );
+    }
+
+    fn mismatch(&mut self, expected: &Ty, found: &Ty, span: &Span) {
+        self.mismatch_at("", expected, found, span, None);
+    }
+
+    // `path` and `whole` say where in a larger value the mismatch is
+    fn mismatch_at(
+        &mut self,
+        path: &str,
+        expected: &Ty,
+        found: &Ty,
+        span: &Span,
+        whole: Option<(&Ty, &Ty)>,
+    ) {
+        let label = format!("expected `{}`, found `{}`", expected, found);
+        let label = match path {
+            "" => label,
+            path => format!("{}: {}", path, label),
+        };
+
+        let mut diagnostic =
+            Diagnostic::error("E212", "Mismatched types").with_label(span.clone(), label);
+
+        if let Some((expected, found)) = whole {
+            diagnostic = diagnostic.with_note(format!(
+                "the value has type `{}`, which doesn't fit `{}`",
+                found, expected
+            ));
+        }
+
+        if let (Ty::Class(_), Ty::Class(_)) = (expected, found) {
+            diagnostic = diagnostic.with_note(format!(
+                "classes only match themselves, so a `{}` is never a `{}`, even with the same fields",
+                found, expected
+            ));
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    // whether every value of type `found` is one of `members`
+    fn fits(&mut self, members: &[Ty], found: &Ty) -> bool {
+        match found {
+            Ty::Union(found) => found.iter().all(|found| self.fits(members, found)),
+            Ty::Optional(inner) => self.fits(members, &Ty::Nil) && self.fits(members, inner),
+
+            found => members.iter().any(|member| match member {
+                Ty::Literal(_) => member == found,
+
+                member => self.unify(member, found),
+            }),
+        }
+    }
+
+    /// Classes satisfy an interface by implementing it, records by providing
+    /// every method it declares.
+    fn satisfies(&self, found: &Ty, interface: &str) -> Result<(), String> {
+        match found {
+            Ty::Unknown | Ty::Var(_) => Ok(()),
+            Ty::Interface(name) if name == interface => Ok(()),
+
+            // a type parameter bounded by the interface stands for something implementing it
+            Ty::Param(param) => {
+                let bounded = self
+                    .constraints
+                    .iter()
+                    .any(|(bounded, bound)| bounded == param && bound == interface);
+
+                if bounded {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "help: add the bound to the type parameter, as in `{}: {}`",
+                        param, interface
+                    ))
+                }
+            }
+
+            Ty::Class(class) => {
+                let implements = self.classes[class.as_str()]
+                    .implements
+                    .iter()
+                    .any(|name| name == interface);
+
+                if implements {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "class `{}` does not declare `implements {}`",
+                        class, interface
+                    ))
+                }
+            }
+
+            Ty::Record { fields, .. } => {
+                for required in self.interfaces[interface].methods {
+                    let Some(FieldTy { ty, .. }) =
+                        fields.iter().find(|field| field.name == required.name)
+                    else {
+                        return Err(format!("missing method `{}`", required.name));
+                    };
+
+                    match ty {
+                        Ty::Function(params, _) if params.len() != required.params.len() => {
+                            return Err(format!(
+                                "`{}` takes {} parameter(s), but the interface expects {}",
+                                required.name,
+                                params.len(),
+                                required.params.len()
+                            ));
+                        }
+
+                        Ty::Function(..) | Ty::Unknown => {}
+
+                        _ => return Err(format!("`{}` is not a function", required.name)),
+                    }
+                }
+
+                Ok(())
+            }
+
+            _ => Err(format!("expected `{}`, found `{}`", interface, found)),
+        }
+    }
+
+    // reports bounds naming neither a built-in constraint nor an interface
+    fn bounds(&mut self, bounds: &[TypeBound]) {
+        for bound in bounds {
+            for constraint in &bound.constraints {
+                if BUILTIN_CONSTRAINTS.contains(&constraint.as_str())
+                    || self.interfaces.contains_key(constraint.as_str())
+                {
+                    continue;
+                }
+
+                self.diagnostics.push(
+                    Diagnostic::error("E219", format!("Cannot find constraint `{}`", constraint))
+                        .with_label(
+                            bound.span.clone(),
+                            "not a built-in constraint or an interface",
+                        )
+                        .with_note(
+                            "help: the built-in constraints are `Num`, `Eq`, `Ord` and `Show`",
+                        ),
+                );
+            }
+        }
+    }
+
+    /// Checks the constraints every use of a generic function put on the
+    /// types it was used with, now that inference has solved them.
+    fn discharge(&mut self) {
+        for obligation in std::mem::take(&mut self.obligations) {
+            let ty = self.zonk(&obligation.ty).widen();
+
+            let Err(reason) = self.meets(&ty, &obligation.constraint, &obligation.given) else {
+                continue;
+            };
+
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E220",
+                    format!(
+                        "Type `{}` does not satisfy constraint `{}`",
+                        ty, obligation.constraint
+                    ),
+                )
+                .with_label(
+                    obligation.span,
+                    format!(
+                        "`{}` requires `{}: {}`",
+                        obligation.function, obligation.param, obligation.constraint
+                    ),
+                )
+                .with_note(reason),
+            );
+        }
+    }
+
+    /// Whether `ty` meets a built-in constraint or an interface. A type
+    /// parameter meets what `given`, the constraints in scope, says it does.
+    fn meets(&self, ty: &Ty, constraint: &str, given: &[(Ident, Ident)]) -> Result<(), String> {
+        match ty {
+            Ty::Var(_) | Ty::Unknown => return Ok(()),
+
+            Ty::Param(param) => {
+                let declared = given
+                    .iter()
+                    .any(|(bounded, bound)| bounded == param && implies(bound, constraint));
+
+                return if declared {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "help: add the bound to the type parameter, as in `{}: {}`",
+                        param, constraint
+                    ))
+                };
+            }
+
+            Ty::Union(members) => {
+                return members
+                    .iter()
+                    .try_for_each(|member| self.meets(member, constraint, given));
+            }
+
+            _ => {}
+        }
+
+        if self.interfaces.contains_key(constraint) {
+            return self.satisfies(ty, constraint);
+        }
+
+        let (met, reason) = match constraint {
+            "Num" => (
+                *ty == Ty::Number,
+                "`Num` is only met by numbers, which arithmetic works on",
+            ),
+            "Ord" => (
+                matches!(ty, Ty::Number | Ty::String),
+                "`Ord` is only met by numbers and strings, which `<` can compare",
+            ),
+            "Eq" => (
+                !matches!(ty, Ty::Function(..)),
+                "`Eq` is met by everything but functions, which `==` can't compare",
+            ),
+            "Show" => (
+                match ty {
+                    Ty::Optional(inner) => self.meets(inner, constraint, given).is_ok(),
+                    ty => matches!(ty, Ty::Number | Ty::String | Ty::Boolean | Ty::Nil),
+                },
+                "`Show` is only met by numbers, strings, booleans and nil, which have a text form",
+            ),
+
+            // an unknown constraint was already reported where it was declared
+            _ => (true, ""),
+        };
+
+        if met { Ok(()) } else { Err(reason.to_string()) }
+    }
+
+    // the first of an operator's operands that's a type parameter
+    fn param_operand(&self, left: &Ty, right: &Ty) -> Option<Ty> {
+        [left, right]
+            .into_iter()
+            .map(|ty| self.zonk(ty))
+            .find(|ty| matches!(ty, Ty::Param(_)))
+    }
+
+    // reports using a type parameter in a way its bounds don't allow, as in `a + b` without `Num`
+    fn require(&mut self, ty: &Ty, constraint: &str, span: &Span) -> bool {
+        let Ty::Param(param) = self.zonk(ty) else {
+            return false;
+        };
+
+        if let Err(reason) =
+            self.meets(&Ty::Param(param.to_string()), constraint, &self.constraints)
+        {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E220",
+                    format!(
+                        "Type `{}` does not satisfy constraint `{}`",
+                        param, constraint
+                    ),
+                )
+                .with_label(
+                    span.clone(),
+                    format!("this needs `{}: {}`", param, constraint),
+                )
+                .with_note(reason),
+            );
+        }
+
+        true
+    }
+}
+
+// `A | B`, flattened and without duplicates; a union with nil is an optional
+fn union(members: Vec<Ty>) -> Ty {
+    let mut flat: Vec<Ty> = Vec::new();
+    let mut nil = false;
+
+    for member in members {
+        let members = match member {
+            Ty::Union(members) => members,
+            Ty::Optional(inner) => {
+                nil = true;
+                vec![*inner]
+            }
+
+            member => vec![member],
+        };
+
+        for member in members {
+            match member {
+                Ty::Unknown => return Ty::Unknown,
+                Ty::Nil => nil = true,
+                member if !flat.contains(&member) => flat.push(member),
+
+                _ => {}
+            }
+        }
+    }
+
+    let ty = match flat.len() {
+        0 => return Ty::Nil,
+        1 => flat.pop().unwrap(),
+
+        _ => Ty::Union(flat),
+    };
+
+    if nil { optional(ty) } else { ty }
+}
+
+// splits `ty` into the part equal to `value` and the rest, when `ty` is an
+// optional or a union of literals that `value` is one of
+fn split(ty: &Ty, value: &Literal) -> Option<(Ty, Ty)> {
+    let (inner, nil) = match ty {
+        Ty::Optional(inner) => (&**inner, true),
+        ty => (ty, false),
+    };
+
+    if let Literal::Nil = value {
+        return nil.then(|| (Ty::Nil, inner.clone()));
+    }
+
+    let members = match inner {
+        Ty::Union(members) => members.clone(),
+        Ty::Literal(_) => vec![inner.clone()],
+
+        _ => return None,
+    };
+
+    let matching = Ty::Literal(value.clone());
+
+    if !members.contains(&matching) {
+        return None;
+    }
+
+    let rest = members
+        .into_iter()
+        .filter(|member| *member != matching)
+        .chain(nil.then_some(Ty::Nil))
+        .collect();
+
+    Some((matching, union(rest)))
+}
+
+// whether running `stmts` always leaves the block, by returning, breaking or continuing
+fn exits(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Return(_) | StmtKind::Break | StmtKind::Continue => true,
+        StmtKind::If {
+            then_branch,
+            else_branch: Some(else_branch),
+            ..
+        } => exits(then_branch) && exits(else_branch),
+
+        _ => false,
+    })
+}
+
+// the built-in constraints a type parameter can be bounded by
+const BUILTIN_CONSTRAINTS: [&str; 4] = ["Num", "Eq", "Ord", "Show"];
+
+// the constraints on each type parameter in `bounds`, one pair per constraint
+fn constraints(bounds: &[TypeBound]) -> Vec<(Ident, Ident)> {
+    bounds
+        .iter()
+        .flat_map(|bound| {
+            bound
+                .constraints
+                .iter()
+                .map(|&constraint| (bound.param, constraint))
+        })
+        .collect()
+}
+
+// whether a parameter bounded by `given` also meets `wanted`; numbers can be
+// compared and shown, and anything ordered can be checked for equality
+fn implies(given: &str, wanted: &str) -> bool {
+    given == wanted
+        || (given == "Num" && matches!(wanted, "Eq" | "Ord" | "Show"))
+        || (given == "Ord" && wanted == "Eq")
+}
+
+// the type a literal belongs to, as `string` for `"a"`
+fn literal_base(value: &Literal) -> Ty {
+    match value {
+        Literal::Number(_) => Ty::Number,
+        Literal::String(_) => Ty::String,
+        Literal::Boolean(_) => Ty::Boolean,
+        Literal::Nil => Ty::Nil,
+    }
+}
+
+// `T?`, without doubling up on types that already admit nil
+fn optional(ty: Ty) -> Ty {
+    match ty {
+        Ty::Optional(_) | Ty::Nil | Ty::Unknown => ty,
+
+        ty => Ty::Optional(Box::new(ty)),
+    }
+}
+
+// `[T]`, which Luau represents as a table indexed by numbers
+fn array(element: Ty) -> Ty {
+    Ty::Record {
+        fields: vec![],
+        indexer: Some(Box::new((Ty::Number, element))),
+    }
+}
+
+// the candidate closest to `name`, if it's close enough to plausibly be a typo
+fn closest<'n>(name: &str, candidates: impl Iterator<Item = &'n str>) -> Option<&'n str> {
+    let limit = (name.chars().count() / 3).max(1);
+
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn field_table(fields: &[Stmt]) -> HashMap<&str, FieldInfo<'_>> {
+    fields
+        .iter()
+        .filter_map(|field| match &field.kind {
+            StmtKind::VariableDecl {
+                is_mutable,
+                pattern:
+                    Pattern {
+                        kind: PatternKind::Identifier(name),
+                        ..
+                    },
+                type_annotation,
+                initializer,
+                ..
+            } => Some((
+                name.as_str(),
+                FieldInfo {
+                    ty: type_annotation.as_ref(),
+                    inferred: None,
+                    mutable: *is_mutable,
+                    initialized: initializer.is_some(),
+                    span: &field.span,
+                },
+            )),
+
+            _ => None,
+        })
+        .collect()
+}
+
+fn method_table(methods: &[Stmt]) -> HashMap<&str, &Stmt> {
+    methods
+        .iter()
+        .filter_map(|method| match &method.kind {
+            StmtKind::FunctionDecl { name, .. } => Some((name.as_str(), method)),
+
+            _ => None,
+        })
+        .collect()
+}
+
+// a class's constructor is its static `new` method
+fn is_constructor(method: &Stmt) -> bool {
+    matches!(&method.kind, StmtKind::FunctionDecl { name, .. } if name == "new")
+}
+
+fn is_receiver(param: &Parameter) -> bool {
+    matches!(&param.pattern.kind, PatternKind::Identifier(name) if name == "self")
+}
+
+// unannotated positions match anything, since nothing is inferred yet
+fn signatures_match(
+    expected: (&[Parameter], Option<&Type>),
+    found: (&[Parameter], Option<&Type>),
+) -> bool {
+    let (expected_params, expected_return) = expected;
+    let (found_params, found_return) = found;
+
+    let compatible = |a: Option<&Type>, b: Option<&Type>| match (a, b) {
+        (Some(a), Some(b)) => a == b,
+
+        _ => true,
+    };
+
+    expected_params.len() == found_params.len()
+        && expected_params
+            .iter()
+            .zip(found_params)
+            .all(|(expected, found)| {
+                is_receiver(expected) == is_receiver(found)
+                    && compatible(
+                        expected.type_annotation.as_ref(),
+                        found.type_annotation.as_ref(),
+                    )
+            })
+        && compatible(expected_return, found_return)
+}
+
+fn signature(params: &[Parameter], return_type: Option<&Type>) -> String {
+    let params: Vec<_> = params
+        .iter()
+        .map(|param| match &param.type_annotation {
+            _ if is_receiver(param) => "self".to_string(),
+            Some(ty) => ty.to_string(),
+            None => "_".to_string(),
+        })
+        .collect();
+
+    match return_type {
+        Some(ty) => format!("({}) -> {}", params.join(", "), ty),
+        None => format!("({})", params.join(", ")),
+    }
+}
+
+impl Variants for Checker<'_> {
+    fn variants(&self, adt: &str, args: &[Ty]) -> Vec<(String, Vec<Ty>)> {
+        let Some(alias) = self.aliases.get(adt) else {
+            return vec![];
+        };
+
+        let params = alias
+            .params
+            .iter()
+            .enumerate()
+            .map(|(index, param)| (param.as_str(), args.get(index).cloned().unwrap_or_default()))
+            .collect();
+
+        alias
+            .ty
+            .variants()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, fields)| {
+                let fields = fields
+                    .iter()
+                    .map(|field| self.resolve_in(field, &params, &mut vec![]))
+                    .collect();
+
+                (name.to_string(), fields)
+            })
+            .collect()
+    }
+
+    fn adt(&self, constructor: &str) -> Option<&str> {
+        self.constructors.get(constructor).copied()
+    }
+}
@@ -0,0 +1,2007 @@
+use crate::ast::*;
+use crate::checker::{self, Ty};
+use crate::node::{Ident, NodeId, NodeMap};
+use crate::visit::{Visitor, walk_expr, walk_pattern, walk_stmt};
+use std::collections::{HashMap, HashSet};
+
+const LUAU_KEYWORDS: [&str; 22] = [
+    "and", "break", "continue", "do", "else", "elseif", "end", "false", "for", "function", "if",
+    "in", "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Renames identifiers that would collide with Luau keywords.
+pub fn mangle(name: &str) -> String {
+    if LUAU_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// where a declaration ends up in the generated Luau
+enum Scope {
+    Local,
+    Declared, // a local declared ahead of its item, which only assigns it
+    Table(String),
+    Instance(String), // methods called with `:`, with `self` implicit
+}
+
+// the right-hand side of a destructuring binding
+enum Value<'a> {
+    Expr(&'a Expr),
+    Path(String),
+}
+
+pub struct Codegen<'a> {
+    output: String,
+    indent: usize,
+    temp_count: usize,
+    instance_methods: HashSet<Ident>,
+    types: HashSet<Ident>,
+    constructors: HashSet<Ident>, // of algebraic data types
+    defaults: HashMap<Ident, Vec<(&'a str, &'a Expr)>>, // instance field values, by class
+    inline: HashMap<Ident, &'a Stmt>,
+    substitutions: HashMap<Ident, String>, // parameters of the function being inlined
+    bound: HashSet<String>,                // every name the top-level statement being emitted binds
+    inlining: HashSet<Ident>,
+    native: bool,
+    checked: Option<&'a NodeMap<Ty>>, // expression types, when the checker has run
+}
+
+impl Default for Codegen<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Codegen<'a> {
+    pub fn new() -> Self {
+        Codegen {
+            output: String::new(),
+            indent: 0,
+            temp_count: 0,
+            instance_methods: HashSet::new(),
+            types: HashSet::new(),
+            constructors: HashSet::new(),
+            defaults: HashMap::new(),
+            inline: HashMap::new(),
+            substitutions: HashMap::new(),
+            bound: HashSet::new(),
+            inlining: HashSet::new(),
+            native: false,
+            checked: None,
+        }
+    }
+
+    /// Uses the types the checker recorded, which tell which calls apply a
+    /// function only partly.
+    pub fn with_types(mut self, types: &'a NodeMap<Ty>) -> Self {
+        self.checked = Some(types);
+        self
+    }
+
+    pub fn generate(mut self, program: &'a [Stmt]) -> String {
+        self.collect(program);
+
+        // Luau only supports native code generation per script
+        if self.native {
+            self.line("--!native");
+            self.blank_line();
+        }
+
+        self.stmts(program);
+
+        let len = self.output.trim_end().len();
+        self.output.truncate(len);
+
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+
+        self.output
+    }
+
+    // records which calls need a `:` receiver, since codegen has no type information,
+    // along with the functions to inline and whether any code asks to be native
+    fn collect(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            self.native |= stmt.has_attribute(&AttributeKind::Native);
+
+            match &stmt.kind {
+                StmtKind::FunctionDecl { name, .. }
+                    if stmt.has_attribute(&AttributeKind::Inline) =>
+                {
+                    self.inline.insert(*name, stmt);
+                }
+
+                StmtKind::ClassDecl {
+                    name,
+                    fields,
+                    methods,
+                    static_methods,
+                    ..
+                } => {
+                    self.types.insert(*name);
+
+                    let defaults = fields.iter().filter_map(|field| match &field.kind {
+                        StmtKind::VariableDecl {
+                            pattern:
+                                Pattern {
+                                    kind: PatternKind::Identifier(name),
+                                    ..
+                                },
+                            initializer: Some(value),
+                            ..
+                        } => Some((name.as_str(), value)),
+
+                        _ => None,
+                    });
+
+                    self.defaults.insert(*name, defaults.collect());
+
+                    for method in methods.iter().chain(static_methods) {
+                        self.native |= method.has_attribute(&AttributeKind::Native);
+                    }
+
+                    for method in methods {
+                        if let StmtKind::FunctionDecl { name, .. } = &method.kind {
+                            self.instance_methods.insert(*name);
+                        }
+                    }
+                }
+
+                StmtKind::MixinDecl { name, methods, .. } => {
+                    self.types.insert(*name);
+
+                    for method in methods {
+                        self.native |= method.has_attribute(&AttributeKind::Native);
+
+                        if let StmtKind::FunctionDecl { name, .. } = &method.kind {
+                            self.instance_methods.insert(*name);
+                        }
+                    }
+                }
+
+                StmtKind::InterfaceDecl { methods, .. } => {
+                    for method in methods {
+                        self.instance_methods.insert(method.name);
+                    }
+                }
+
+                StmtKind::TypeAlias { ty, .. } => {
+                    for (variant, _) in ty.variants().unwrap_or_default() {
+                        self.constructors.insert(variant);
+                    }
+                }
+
+                StmtKind::ModuleDecl { declarations, .. } => self.collect(declarations),
+
+                _ => {}
+            }
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.output.push_str("    ");
+        }
+
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    fn blank_line(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    // doc comments are kept as plain `--` comments above the declaration
+    fn docs(&mut self, docs: Option<&str>) {
+        for line in docs.into_iter().flat_map(str::lines) {
+            if line.is_empty() {
+                self.line("--");
+            } else {
+                self.line(&format!("-- {}", line));
+            }
+        }
+    }
+
+    fn fresh_temp(&mut self) -> String {
+        self.temp_count += 1;
+
+        format!("_exx{}", self.temp_count)
+    }
+
+    // runs `f` one level deeper and returns what it emitted instead of appending it
+    fn capture(&mut self, f: impl FnOnce(&mut Self)) -> String {
+        let saved = std::mem::take(&mut self.output);
+
+        self.indent += 1;
+        f(self);
+        self.indent -= 1;
+
+        std::mem::replace(&mut self.output, saved)
+    }
+
+    // runs `f` and returns the lines it emitted ahead of its value, rather than appending them
+    fn split(&mut self, f: impl FnOnce(&mut Self) -> String) -> (String, String) {
+        let saved = std::mem::take(&mut self.output);
+        let value = f(self);
+
+        (value, std::mem::replace(&mut self.output, saved))
+    }
+
+    // like `split`, but one level deeper, for code that only runs on some paths
+    fn branch(&mut self, f: impl FnOnce(&mut Self) -> String) -> (String, String) {
+        let mut value = String::new();
+        let lines = self.capture(|this| value = f(this));
+
+        (value, lines)
+    }
+
+    // puts `value` in a local ahead of the current statement
+    fn spill(&mut self, value: &str) -> String {
+        let temp = self.fresh_temp();
+        self.line(&format!("local {} = {}", temp, value));
+
+        temp
+    }
+
+    /// Lowers the value `f` returns after `earlier`. Expressions such as `??`
+    /// or `match` emit lines ahead of the statement they're in, which would
+    /// run before the values to their left, so those that aren't names or
+    /// literals go into locals first.
+    fn after<'v>(
+        &mut self,
+        earlier: impl IntoIterator<Item = &'v mut String>,
+        f: impl FnOnce(&mut Self) -> String,
+    ) -> String {
+        let (value, lines) = self.split(f);
+
+        if !lines.is_empty() {
+            for earlier in earlier.into_iter().filter(|value| !is_settled(value)) {
+                *earlier = self.spill(earlier);
+            }
+
+            self.output.push_str(&lines);
+        }
+
+        value
+    }
+
+    // lowers `exprs` left to right, each with `lower`, which is also told its position
+    fn sequence<'e>(
+        &mut self,
+        exprs: impl IntoIterator<Item = &'e Expr>,
+        mut lower: impl FnMut(&mut Self, usize, &'e Expr) -> String,
+    ) -> Vec<String> {
+        let mut values = Vec::new();
+
+        for (index, expr) in exprs.into_iter().enumerate() {
+            let value = self.after(&mut values, |this| lower(this, index, expr));
+            values.push(value);
+        }
+
+        values
+    }
+
+    /// `local temp = value`, then `if <test temp> then temp = other end`, for
+    /// the right of `&&`, `||` and `??` when it needs lines of its own, which
+    /// must only run when it does. `other` comes from `branch`.
+    fn assign_if(
+        &mut self,
+        value: &str,
+        test: impl FnOnce(&str) -> String,
+        (other, lines): (String, String),
+    ) -> String {
+        let temp = self.spill(value);
+
+        self.line(&format!("if {} then", test(&temp)));
+        self.output.push_str(&lines);
+        self.indent += 1;
+        self.line(&format!("{} = {}", temp, other));
+        self.indent -= 1;
+        self.line("end");
+
+        temp
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, scope: &Scope) {
+        // anything emitted at the top level isn't nested in another statement
+        if self.indent == 0 {
+            let mut binders = Binders::default();
+            binders.visit_stmt(stmt);
+
+            self.bound = binders.0.into_iter().map(str::to_string).collect();
+        }
+
+        match &stmt.kind {
+            StmtKind::VariableDecl {
+                pattern,
+                initializer,
+                ..
+            } => {
+                self.docs(stmt.docs());
+                self.variable(pattern, initializer.as_ref(), scope);
+            }
+
+            StmtKind::FunctionDecl {
+                name, params, body, ..
+            } => {
+                let (header, params) = match scope {
+                    Scope::Local => (format!("local function {}", mangle(name)), &params[..]),
+                    Scope::Declared => (format!("function {}", mangle(name)), &params[..]),
+                    Scope::Table(table) => (format!("function {}.{}", table, name), &params[..]),
+                    Scope::Instance(table) => {
+                        (format!("function {}:{}", table, name), &params[1..])
+                    }
+                };
+
+                self.blank_line();
+                self.docs(stmt.docs());
+                self.function(&header, params, body);
+                self.blank_line();
+            }
+
+            StmtKind::ClassDecl {
+                name,
+                includes,
+                methods,
+                static_fields,
+                static_methods,
+                ..
+            } => {
+                let class = mangle(name);
+
+                self.blank_line();
+                self.docs(stmt.docs());
+                self.declare(&class, "{}", scope);
+                self.line(&format!("{}.__index = {}", class, class));
+
+                for mixin in includes {
+                    self.line(&format!("for key, value in pairs({}) do", mangle(mixin)));
+                    self.indent += 1;
+                    self.line(&format!("{}[key] = value", class));
+                    self.indent -= 1;
+                    self.line("end");
+                }
+
+                for field in static_fields {
+                    self.stmt(field, &Scope::Table(class.clone()));
+                }
+
+                for method in static_methods {
+                    self.stmt(method, &Scope::Table(class.clone()));
+                }
+
+                for method in methods {
+                    self.stmt(method, &Scope::Instance(class.clone()));
+                }
+
+                self.blank_line();
+            }
+
+            StmtKind::MixinDecl { name, methods, .. } => {
+                let mixin = mangle(name);
+
+                self.blank_line();
+                self.docs(stmt.docs());
+                self.declare(&mixin, "{}", scope);
+
+                for method in methods {
+                    self.stmt(method, &Scope::Instance(mixin.clone()));
+                }
+
+                self.blank_line();
+            }
+
+            // interfaces only exist at compile time
+            StmtKind::InterfaceDecl { .. } => {}
+
+            // Luau type aliases are block scoped, so they stay put even inside modules
+            StmtKind::TypeAlias {
+                name, params, ty, ..
+            } => {
+                let params = if params.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", params.join(", "))
+                };
+
+                self.docs(stmt.docs());
+
+                let Some(variants) = ty.variants() else {
+                    self.line(&format!(
+                        "type {}{} = {}",
+                        mangle(name),
+                        params,
+                        luau_type(ty)
+                    ));
+
+                    return;
+                };
+
+                // values of an algebraic data type are tables tagged with their constructor
+                let types: Vec<_> = variants
+                    .iter()
+                    .map(|(variant, fields)| variant_type(variant, fields))
+                    .collect();
+
+                self.line(&format!(
+                    "type {}{} = {}",
+                    mangle(name),
+                    params,
+                    types.join(" | ")
+                ));
+
+                for (variant, fields) in variants {
+                    let tag = literal(&Literal::String(variant.to_string()));
+
+                    let value = if fields.is_empty() {
+                        format!("{{ tag = {} }}", tag)
+                    } else {
+                        format!("function(...) return {{ tag = {}, ... }} end", tag)
+                    };
+
+                    self.declare(&mangle(&variant), &value, scope);
+                }
+            }
+
+            StmtKind::ModuleDecl {
+                name, declarations, ..
+            } => {
+                let module = mangle(name);
+
+                self.blank_line();
+                self.docs(stmt.docs());
+                self.declare(&module, "{}", scope);
+
+                for declaration in declarations {
+                    self.stmt(declaration, &Scope::Table(module.clone()));
+                }
+
+                self.blank_line();
+            }
+
+            StmtKind::Import(name) => {
+                let value = format!("require(script.Parent.{})", name);
+                self.declare(&mangle(name), &value, scope);
+            }
+
+            StmtKind::Assign { target, op, value } => {
+                // the checker only lets a string be added to a string
+                let joins = self.checked.and_then(|types| types.get(value.id)) == Some(&Ty::String);
+
+                // a field is assigned, so it stays a field even if it names a method
+                let target = match &target.kind {
+                    ExprKind::PropertyAccess { object, name } => {
+                        property(&self.callee(object), name)
+                    }
+
+                    _ => self.expr(target),
+                };
+                let value = self.expr(value);
+
+                let operator = match op {
+                    Some(BinaryOp::Add) if joins => "..=".into(),
+                    Some(op) => format!("{}=", binary_operator(op)),
+                    None => "=".into(),
+                };
+
+                self.line(&format!("{} {} {}", target, operator, value));
+            }
+
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = self.expr(condition);
+                self.line(&format!("if {} then", condition));
+                self.if_tail(then_branch, else_branch.as_deref());
+            }
+
+            // a condition that needs lines of its own runs them at the top of every iteration
+            StmtKind::While { condition, body } => {
+                let (condition, lines) = self.branch(|this| this.expr(condition));
+
+                if lines.is_empty() && !condition.contains('\n') {
+                    self.line(&format!("while {} do", condition));
+                } else {
+                    self.line("while true do");
+                    self.output.push_str(&lines);
+                    self.indent += 1;
+                    self.line(&format!("if not {} then break end", grouped(condition)));
+                    self.indent -= 1;
+                }
+
+                self.block(body);
+                self.line("end");
+            }
+
+            StmtKind::For {
+                pattern,
+                iterable,
+                body,
+            } => self.for_loop(pattern, iterable, body),
+
+            StmtKind::Expression(expr) => self.expression_statement(expr),
+
+            StmtKind::Return(Some(expr)) => {
+                let value = self.expr(expr);
+                self.line(&format!("return {}", value));
+            }
+
+            StmtKind::Return(None) => self.line("return"),
+            StmtKind::Break => self.line("break"),
+            StmtKind::Continue => self.line("continue"),
+        }
+    }
+
+    // an expression whose value goes unused, which Luau only allows for calls
+    fn expression_statement(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Block(body) => {
+                self.line("do");
+                self.block(body);
+                self.line("end");
+            }
+
+            ExprKind::Match { scrutinee, arms } => {
+                let value = self.place(Value::Expr(scrutinee));
+                self.arms(&value, arms, None);
+            }
+
+            // without a result, raw Luau is spliced in as-is
+            ExprKind::Luau {
+                return_type: None,
+                body,
+                ..
+            } => {
+                for line in dedent(body) {
+                    self.line(line);
+                }
+            }
+
+            // `a?.f()` on its own line can't be an `and` expression, so it becomes an `if`
+            ExprKind::Call { callee, arguments }
+                if matches!(callee.kind, ExprKind::OptionalPropertyAccess { .. }) =>
+            {
+                let ExprKind::OptionalPropertyAccess { object, name } = &callee.kind else {
+                    unreachable!()
+                };
+
+                let (guards, receiver) = self.guarded(object);
+                let (call, lines) =
+                    self.branch(|this| this.method_call(object, &receiver, name, arguments));
+
+                if lines.is_empty() && !call.contains('\n') {
+                    self.line(&format!("if {} then {} end", guards, call));
+                    return;
+                }
+
+                self.line(&format!("if {} then", guards));
+                self.output.push_str(&lines);
+                self.indent += 1;
+                self.line(&call);
+                self.indent -= 1;
+                self.line("end");
+            }
+
+            // a partial call is a closure, which Luau can't have as a statement
+            ExprKind::Call { callee, arguments } if self.missing(callee, arguments).is_none() => {
+                let call = self.expr(expr);
+                self.line(&call);
+            }
+
+            _ => {
+                let value = self.expr(expr);
+                self.line(&format!("local _ = {}", value));
+            }
+        }
+    }
+
+    /// Lowers match arms to an `if` chain testing `value`, stopping at the
+    /// first arm that always matches. Each arm's body runs as statements, or
+    /// is assigned to `result` for a match used as a value.
+    fn arms(&mut self, value: &str, arms: &[MatchArm], result: Option<&str>) {
+        let mut open = false;
+
+        for arm in arms {
+            let mut conditions = Vec::new();
+            let mut bindings = Vec::new();
+            self.pattern_test(&arm.pattern, value, &mut conditions, &mut bindings);
+
+            let header = match (open, conditions.is_empty()) {
+                (false, true) => "do".into(),
+                (true, true) => "else".into(),
+                (false, false) => format!("if {} then", conditions.join(" and ")),
+                (true, false) => format!("elseif {} then", conditions.join(" and ")),
+            };
+
+            self.line(&header);
+            self.indent += 1;
+            self.bind(bindings);
+
+            match (&arm.body.kind, result) {
+                (ExprKind::Block(body), _) => self.stmts(body),
+
+                (_, Some(result)) => {
+                    let value = self.expr(&arm.body);
+                    self.line(&format!("{} = {}", result, value));
+                }
+
+                (_, None) => self.expression_statement(&arm.body),
+            }
+
+            self.indent -= 1;
+            open = !conditions.is_empty();
+
+            if !open {
+                break;
+            }
+        }
+
+        if !arms.is_empty() {
+            self.line("end");
+        }
+    }
+
+    /// Adds the checks `value` has to pass to match `pattern` to `conditions`,
+    /// and the locals the pattern binds to `bindings`.
+    fn pattern_test(
+        &self,
+        pattern: &Pattern,
+        value: &str,
+        conditions: &mut Vec<String>,
+        bindings: &mut Vec<(String, String)>,
+    ) {
+        let tag = |name: &str| {
+            format!(
+                "{}.tag == {}",
+                value,
+                literal(&Literal::String(name.to_string()))
+            )
+        };
+
+        match &pattern.kind {
+            PatternKind::Wildcard => {}
+
+            PatternKind::Identifier(name) if self.constructors.contains(name) => {
+                conditions.push(tag(name))
+            }
+
+            PatternKind::Identifier(name) => bindings.push((mangle(name), value.to_string())),
+
+            PatternKind::Constructor { name, args } => {
+                conditions.push(tag(name));
+
+                for (index, arg) in args.iter().enumerate() {
+                    let value = format!("{}[{}]", value, index + 1);
+                    self.pattern_test(arg, &value, conditions, bindings);
+                }
+            }
+
+            PatternKind::Tuple(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    let value = format!("{}[{}]", value, index + 1);
+                    self.pattern_test(item, &value, conditions, bindings);
+                }
+            }
+
+            PatternKind::Record { name, fields } => {
+                if let Some(name) = name.as_ref().filter(|name| self.types.contains(*name)) {
+                    conditions.push(format!("getmetatable({}) == {}", value, mangle(name)));
+                }
+
+                for field in fields {
+                    let value = property(value, &field.name);
+                    self.pattern_test(&field.pattern, &value, conditions, bindings);
+                }
+            }
+
+            PatternKind::Literal(literal) => {
+                conditions.push(format!("{} == {}", value, self::literal(literal)))
+            }
+        }
+    }
+
+    fn block(&mut self, body: &[Stmt]) {
+        self.indent += 1;
+        self.stmts(body);
+        self.indent -= 1;
+    }
+
+    // functions can name items declared further down, so those get a `local` up front
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        let forward = forward_declared(stmts);
+
+        if !forward.is_empty() {
+            let names: Vec<_> = stmts
+                .iter()
+                .filter(|stmt| forward.contains(&stmt.id))
+                .flat_map(item_names)
+                .map(mangle)
+                .collect();
+
+            self.line(&format!("local {}", names.join(", ")));
+        }
+
+        for stmt in stmts {
+            let scope = if forward.contains(&stmt.id) {
+                Scope::Declared
+            } else {
+                Scope::Local
+            };
+
+            self.stmt(stmt, &scope);
+        }
+    }
+
+    // emits an `if` body and its `elseif`/`else` chain, up to and including `end`
+    fn if_tail(&mut self, then_branch: &[Stmt], else_branch: Option<&[Stmt]>) {
+        self.block(then_branch);
+
+        match else_branch {
+            Some(
+                [
+                    Stmt {
+                        kind:
+                            StmtKind::If {
+                                condition,
+                                then_branch,
+                                else_branch,
+                            },
+                        ..
+                    },
+                ],
+            ) => {
+                let (condition, lines) = self.branch(|this| this.expr(condition));
+
+                if lines.is_empty() && !condition.contains('\n') {
+                    self.line(&format!("elseif {} then", condition));
+                    self.if_tail(then_branch, else_branch.as_deref());
+                    return;
+                }
+
+                // the lines a condition needs can only go in an `else` of their own
+                self.line("else");
+                self.output.push_str(&lines);
+                self.indent += 1;
+                self.line(&format!("if {} then", condition));
+                self.if_tail(then_branch, else_branch.as_deref());
+                self.indent -= 1;
+                self.line("end");
+            }
+
+            Some(else_branch) => {
+                self.line("else");
+                self.block(else_branch);
+                self.line("end");
+            }
+
+            None => self.line("end"),
+        }
+    }
+
+    fn for_loop(&mut self, pattern: &Pattern, iterable: &Expr, body: &[Stmt]) {
+        let mut destructured = Vec::new();
+
+        let header = if let ExprKind::Range {
+            start,
+            end,
+            inclusive,
+        } = &iterable.kind
+        {
+            let variable = self.loop_variable(pattern, &mut destructured);
+            let bounds =
+                self.sequence([&**start, &**end], |this, index, bound| match &bound.kind {
+                    _ if index == 0 || *inclusive => this.expr(bound),
+                    ExprKind::Literal(Literal::Number(n)) => (n - 1.0).to_string(),
+
+                    _ => format!("{} - 1", this.operand(bound, ADDITIVE_PRECEDENCE, false)),
+                });
+
+            format!("for {} = {}, {} do", variable, bounds[0], bounds[1])
+        } else {
+            // `for (key, value) in t` walks a table, anything else walks an array
+            let (key, value, iterator) = match &pattern.kind {
+                PatternKind::Tuple(items) if items.len() == 2 => (
+                    self.loop_variable(&items[0], &mut destructured),
+                    self.loop_variable(&items[1], &mut destructured),
+                    "pairs",
+                ),
+
+                _ => (
+                    "_".into(),
+                    self.loop_variable(pattern, &mut destructured),
+                    "ipairs",
+                ),
+            };
+
+            let iterable = self.expr(iterable);
+
+            format!("for {}, {} in {}({}) do", key, value, iterator, iterable)
+        };
+
+        self.line(&header);
+        self.indent += 1;
+
+        for (pattern, temp) in destructured {
+            let mut bindings = Vec::new();
+            self.destructure(pattern, Value::Path(temp), &mut bindings);
+            self.bind(bindings);
+        }
+
+        self.indent -= 1;
+        self.block(body);
+        self.line("end");
+    }
+
+    fn loop_variable<'p>(
+        &mut self,
+        pattern: &'p Pattern,
+        destructured: &mut Vec<(&'p Pattern, String)>,
+    ) -> String {
+        match &pattern.kind {
+            PatternKind::Identifier(name) => mangle(name),
+            PatternKind::Wildcard => "_".into(),
+
+            _ => {
+                let temp = self.fresh_temp();
+                destructured.push((pattern, temp.clone()));
+
+                temp
+            }
+        }
+    }
+
+    fn bind(&mut self, bindings: Vec<(String, String)>) {
+        if !bindings.is_empty() {
+            let (names, values): (Vec<_>, Vec<_>) = bindings.into_iter().unzip();
+            self.line(&format!(
+                "local {} = {}",
+                names.join(", "),
+                values.join(", ")
+            ));
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: &str, scope: &Scope) {
+        match scope {
+            Scope::Local => self.line(&format!("local {} = {}", name, value)),
+            Scope::Declared => self.line(&format!("{} = {}", name, value)),
+            Scope::Table(table) | Scope::Instance(table) => {
+                self.line(&format!("{}.{} = {}", table, name, value))
+            }
+        }
+    }
+
+    fn variable(&mut self, pattern: &Pattern, initializer: Option<&Expr>, scope: &Scope) {
+        let Some(initializer) = initializer else {
+            // the parser only allows a missing initializer on plain identifiers
+            if let PatternKind::Identifier(name) = &pattern.kind {
+                match scope {
+                    Scope::Local => self.line(&format!("local {}", mangle(name))),
+                    Scope::Declared => {}
+                    Scope::Table(_) | Scope::Instance(_) => {
+                        self.declare(&mangle(name), "nil", scope)
+                    }
+                }
+            }
+
+            return;
+        };
+
+        let mut bindings = Vec::new();
+        self.destructure(pattern, Value::Expr(initializer), &mut bindings);
+
+        if bindings.is_empty() {
+            return;
+        }
+
+        let (names, values): (Vec<_>, Vec<_>) = bindings.into_iter().unzip();
+
+        // members go straight into their table, since a local of the same name would
+        // shadow whatever the rest of the block means by it
+        let Scope::Table(table) = scope else {
+            self.line(&format!(
+                "local {} = {}",
+                names.join(", "),
+                values.join(", ")
+            ));
+
+            return;
+        };
+
+        if let [name] = &names[..]
+            && name != "_"
+        {
+            self.line(&format!("{}.{} = {}", table, name, values[0]));
+            return;
+        }
+
+        let temps: Vec<_> = names
+            .iter()
+            .map(|name| match name.as_str() {
+                "_" => name.clone(),
+                _ => self.fresh_temp(),
+            })
+            .collect();
+
+        self.line(&format!(
+            "local {} = {}",
+            temps.join(", "),
+            values.join(", ")
+        ));
+
+        for (name, temp) in names.iter().zip(&temps).filter(|(name, _)| *name != "_") {
+            self.line(&format!("{}.{} = {}", table, name, temp));
+        }
+    }
+
+    /// Flattens an irrefutable pattern into `(local, value)` pairs, so that
+    /// destructuring costs no more than the equivalent hand-written locals.
+    fn destructure(&mut self, pattern: &Pattern, value: Value, out: &mut Vec<(String, String)>) {
+        match &pattern.kind {
+            PatternKind::Identifier(name) => {
+                let earlier = out.iter_mut().map(|(_, value)| value);
+                let value = self.after(earlier, |this| this.value(value));
+                out.push((mangle(name), value));
+            }
+
+            PatternKind::Wildcard => {
+                // only keep the value around if evaluating it might do something
+                if let Value::Expr(expr) = value
+                    && !is_place(expr)
+                {
+                    let earlier = out.iter_mut().map(|(_, value)| value);
+                    let value = self.after(earlier, |this| this.expr(expr));
+                    out.push(("_".into(), value));
+                }
+            }
+
+            PatternKind::Tuple(items) => {
+                if let Value::Expr(Expr {
+                    kind: ExprKind::Tuple(values),
+                    ..
+                }) = value
+                {
+                    let distributable = values.len() == items.len()
+                        && items.iter().zip(values).all(|(item, value)| {
+                            matches!(
+                                item.kind,
+                                PatternKind::Identifier(_) | PatternKind::Wildcard
+                            ) || is_place(value)
+                        });
+
+                    if distributable {
+                        for (item, value) in items.iter().zip(values) {
+                            self.destructure(item, Value::Expr(value), out);
+                        }
+
+                        return;
+                    }
+                }
+
+                let base = self.place(value);
+
+                for (index, item) in items.iter().enumerate() {
+                    self.destructure(item, Value::Path(format!("{}[{}]", base, index + 1)), out);
+                }
+            }
+
+            PatternKind::Record { fields, .. } => {
+                let base = self.place(value);
+
+                for field in fields {
+                    let path = property(&base, &field.name);
+                    self.destructure(&field.pattern, Value::Path(path), out);
+                }
+            }
+
+            PatternKind::Constructor { .. } | PatternKind::Literal(_) => {
+                unreachable!("refutable patterns are rejected by the parser")
+            }
+        }
+    }
+
+    fn value(&mut self, value: Value) -> String {
+        match value {
+            Value::Expr(expr) => self.expr(expr),
+            Value::Path(path) => path,
+        }
+    }
+
+    // makes `value` safe to index repeatedly, spilling it into a temporary if needed
+    fn place(&mut self, value: Value) -> String {
+        match value {
+            Value::Expr(expr) if is_place(expr) => self.expr(expr),
+            Value::Expr(expr) => {
+                let value = self.expr(expr);
+                self.spill(&value)
+            }
+
+            Value::Path(path) => path,
+        }
+    }
+
+    fn function(&mut self, header: &str, params: &[Parameter], body: &[Stmt]) {
+        let (names, prologue) = self.parameters(params);
+
+        self.line(&format!("{}({})", header, names.join(", ")));
+        self.output.push_str(&prologue);
+
+        self.indent += 1;
+        self.stmts(body);
+        self.indent -= 1;
+        self.line("end");
+    }
+
+    // returns the Luau parameter list plus the locals that unpack destructured parameters
+    fn parameters(&mut self, params: &[Parameter]) -> (Vec<String>, String) {
+        let mut names = Vec::new();
+        let mut destructured = Vec::new();
+
+        for param in params {
+            match &param.pattern.kind {
+                PatternKind::Identifier(name) => names.push(mangle(name)),
+                PatternKind::Wildcard => names.push("_".into()),
+
+                _ => {
+                    let temp = self.fresh_temp();
+
+                    names.push(temp.clone());
+                    destructured.push((&param.pattern, temp));
+                }
+            }
+        }
+
+        let prologue = self.capture(|this| {
+            for (pattern, temp) in destructured {
+                let mut bindings = Vec::new();
+                this.destructure(pattern, Value::Path(temp), &mut bindings);
+                this.bind(bindings);
+            }
+        });
+
+        (names, prologue)
+    }
+
+    fn expr(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            // the right only runs when the left is nil, so lines it needs go in an `if`
+            ExprKind::Binary {
+                left,
+                op: BinaryOp::Coalesce,
+                right,
+            } => {
+                let left = self.expr(left);
+                let (right, lines) = self.branch(|this| this.expr(right));
+
+                if !lines.is_empty() || right.contains('\n') {
+                    return self.assign_if(
+                        &left,
+                        |temp| format!("{} == nil", temp),
+                        (right, lines),
+                    );
+                }
+
+                let left = if is_settled(&left) {
+                    left
+                } else {
+                    self.spill(&left)
+                };
+
+                format!("if {} ~= nil then {} else {}", left, left, right)
+            }
+
+            // `+` on strings joins them, which Luau spells `..`
+            ExprKind::Binary { left, right, .. } if self.is_concat(expr) => {
+                let operands = self.sequence([&**left, &**right], |this, _, operand| {
+                    this.concat_operand(operand)
+                });
+
+                operands.join(" .. ")
+            }
+
+            ExprKind::Binary {
+                left,
+                op: op @ (BinaryOp::And | BinaryOp::Or),
+                right,
+            } => {
+                let precedence = binary_precedence(op);
+
+                let left = self.operand(left, precedence, false);
+                let (value, lines) = self.branch(|this| this.expr(right));
+
+                if lines.is_empty() && !value.contains('\n') {
+                    let right = self.parenthesize(right, value, precedence, true);
+
+                    return format!("{} {} {}", left, binary_operator(op), right);
+                }
+
+                let negate = matches!(op, BinaryOp::Or);
+
+                self.assign_if(
+                    &left,
+                    |temp| match negate {
+                        true => format!("not {}", temp),
+                        false => temp.to_string(),
+                    },
+                    (value, lines),
+                )
+            }
+
+            ExprKind::Binary { left, op, right } => {
+                let precedence = binary_precedence(op);
+
+                let operands = self.sequence([&**left, &**right], |this, index, operand| {
+                    this.operand(operand, precedence, index == 1)
+                });
+
+                format!("{} {} {}", operands[0], binary_operator(op), operands[1])
+            }
+
+            ExprKind::Unary { op, expr } => {
+                let operand = self.operand(expr, UNARY_PRECEDENCE, false);
+
+                match op {
+                    UnaryOp::Negate => format!("-{}", operand),
+                    UnaryOp::Not => format!("not {}", operand),
+                }
+            }
+
+            ExprKind::Literal(literal) => self::literal(literal),
+            ExprKind::Identifier(name) => match self.substitutions.get(name) {
+                Some(value) => value.clone(),
+                None => mangle(name),
+            },
+            ExprKind::Grouping(inner) => format!("({})", self.expr(inner)),
+
+            ExprKind::Tuple(items) => {
+                let items = self.sequence(items, |this, _, item| this.expr(item));
+
+                format!("{{{}}}", items.join(", "))
+            }
+
+            // fields left out get their default, evaluated afresh for every instance
+            ExprKind::StructLiteral { name, fields } => {
+                let given = fields.iter().map(|(field, value)| (field.as_str(), value));
+                let defaults: Vec<_> = self
+                    .defaults
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(field, _)| fields.iter().all(|(given, _)| given != field))
+                    .copied()
+                    .collect();
+
+                let table = self.table(given.chain(defaults));
+
+                format!("setmetatable({}, {})", table, mangle(name))
+            }
+
+            ExprKind::Record(fields) => {
+                self.table(fields.iter().map(|(field, value)| (field.as_str(), value)))
+            }
+
+            // a block runs where it stands, just like one on its own line, and has no value
+            ExprKind::Block(body) => {
+                self.line("do");
+                self.block(body);
+                self.line("end");
+
+                "nil".into()
+            }
+
+            // a method taken as a value keeps the receiver it was taken from
+            ExprKind::PropertyAccess { object, name } if self.is_method_call(object, name) => {
+                let mut receiver = self.callee(object);
+
+                if !is_place(object) {
+                    receiver = self.spill(&receiver);
+                }
+
+                format!("function(...) return {}:{}(...) end", receiver, name)
+            }
+
+            ExprKind::PropertyAccess { object, name } => {
+                let object = self.callee(object);
+
+                property(&object, name)
+            }
+
+            ExprKind::OptionalPropertyAccess { object, name } => {
+                let (guards, path) = self.guarded(object);
+
+                if self.is_method_call(object, name) {
+                    return format!(
+                        "{} and function(...) return {}:{}(...) end",
+                        guards, path, name
+                    );
+                }
+
+                format!("{} and {}", guards, property(&path, name))
+            }
+
+            // the arguments only run when the receiver isn't nil
+            ExprKind::Call { callee, arguments }
+                if matches!(callee.kind, ExprKind::OptionalPropertyAccess { .. }) =>
+            {
+                let ExprKind::OptionalPropertyAccess { object, name } = &callee.kind else {
+                    unreachable!()
+                };
+
+                let (guards, receiver) = self.guarded(object);
+                let (call, lines) =
+                    self.branch(|this| this.method_call(object, &receiver, name, arguments));
+
+                if lines.is_empty() && !call.contains('\n') {
+                    return format!("{} and {}", guards, call);
+                }
+
+                self.assign_if(&guards, str::to_string, (call, lines))
+            }
+
+            ExprKind::Call { callee, arguments } => {
+                if let Some(missing) = self.missing(callee, arguments) {
+                    return self.partial_call(callee, arguments, missing);
+                }
+
+                if let Some(inlined) = self.inline_call(callee, arguments) {
+                    return inlined;
+                }
+
+                let lower = |this: &mut Self, index, expr| match index {
+                    0 => this.callee(expr),
+                    _ => this.expr(expr),
+                };
+
+                let (callee, arguments) = match &callee.kind {
+                    ExprKind::PropertyAccess { object, name }
+                        if self.is_method_call(object, name) =>
+                    {
+                        let mut values =
+                            self.sequence(std::iter::once(&**object).chain(arguments), lower);
+                        let object = values.remove(0);
+
+                        (format!("{}:{}", object, name), values)
+                    }
+
+                    _ => {
+                        let mut values =
+                            self.sequence(std::iter::once(&**callee).chain(arguments), lower);
+
+                        (values.remove(0), values)
+                    }
+                };
+
+                format!("{}({})", callee, arguments.join(", "))
+            }
+
+            // a body that comes down to `return value` stays on one line
+            ExprKind::Lambda { params, body } => {
+                let (names, prologue) = self.parameters(params);
+
+                let inner = self.capture(|this| match &body.kind {
+                    ExprKind::Block(stmts) => this.stmts(stmts),
+
+                    _ => {
+                        let value = this.expr(body);
+                        this.line(&format!("return {}", value));
+                    }
+                });
+
+                if prologue.is_empty()
+                    && !matches!(body.kind, ExprKind::Block(_))
+                    && inner.lines().count() == 1
+                {
+                    return format!("function({}) {} end", names.join(", "), inner.trim());
+                }
+
+                format!(
+                    "function({})\n{}{}{}end",
+                    names.join(", "),
+                    prologue,
+                    inner,
+                    self.indentation()
+                )
+            }
+
+            ExprKind::Range { .. } => {
+                unreachable!("the parser only produces ranges as `for` loop iterables")
+            }
+
+            ExprKind::Luau { bindings, body, .. } => {
+                let names: Vec<_> = bindings.iter().map(|(name, _)| mangle(name)).collect();
+                let names = names.join(", ");
+                let lines = dedent(body);
+
+                if let [line] = lines[..] {
+                    return format!("(function({}) {} end)({})", names, line, names);
+                }
+
+                let inner = self.capture(|this| {
+                    for line in lines {
+                        this.line(line);
+                    }
+                });
+
+                format!(
+                    "(function({})\n{}{}end)({})",
+                    names,
+                    inner,
+                    self.indentation(),
+                    names
+                )
+            }
+
+            // the arms run where the match stands, leaving their value in a local
+            ExprKind::Match { scrutinee, arms } => {
+                let value = self.place(Value::Expr(scrutinee));
+                let result = self.fresh_temp();
+
+                self.line(&format!("local {}", result));
+                self.arms(&value, arms, Some(&result));
+
+                result
+            }
+        }
+    }
+
+    fn table<'e>(&mut self, fields: impl Iterator<Item = (&'e str, &'e Expr)>) -> String {
+        let (keys, values): (Vec<_>, Vec<_>) = fields.unzip();
+        let values = self.sequence(values, |this, _, value| this.expr(value));
+
+        let fields: Vec<_> = keys
+            .iter()
+            .zip(values)
+            .map(|(field, value)| format!("{} = {}", table_key(field), value))
+            .collect();
+
+        if fields.is_empty() {
+            return "{}".into();
+        }
+
+        format!("{{ {} }}", fields.join(", "))
+    }
+
+    fn indentation(&self) -> String {
+        "    ".repeat(self.indent)
+    }
+
+    /// Expands a call to an `@inline` function into its returned expression.
+    /// Arguments that aren't plain names or literals keep the call, so that
+    /// they're still evaluated exactly once. So does a call where a local
+    /// might capture a name the body uses, or the body might capture one an
+    /// argument uses.
+    fn inline_call(&mut self, callee: &Expr, arguments: &[Expr]) -> Option<String> {
+        let ExprKind::Identifier(name) = &callee.kind else {
+            return None;
+        };
+
+        let function = *self.inline.get(name)?;
+
+        let StmtKind::FunctionDecl { params, body, .. } = &function.kind else {
+            return None;
+        };
+
+        let [
+            Stmt {
+                kind: StmtKind::Return(Some(result)),
+                ..
+            },
+        ] = &body[..]
+        else {
+            return None;
+        };
+
+        let simple = |expr: &Expr| is_place(expr) || matches!(expr.kind, ExprKind::Literal(_));
+
+        if self.inlining.contains(name)
+            || params.len() != arguments.len()
+            || !arguments.iter().all(simple)
+            || !self.hygienic(name, params, result, arguments)
+        {
+            return None;
+        }
+
+        let mut substitutions = HashMap::new();
+
+        for (param, argument) in params.iter().zip(arguments) {
+            let PatternKind::Identifier(param) = &param.pattern.kind else {
+                return None;
+            };
+
+            substitutions.insert(*param, self.expr(argument));
+        }
+
+        let saved = std::mem::replace(&mut self.substitutions, substitutions);
+        self.inlining.insert(*name);
+
+        let value = self.expr(result);
+
+        self.inlining.remove(name);
+        self.substitutions = saved;
+
+        if simple(result) {
+            Some(value)
+        } else {
+            Some(format!("({})", value))
+        }
+    }
+
+    // inline functions are top-level, so the names their body uses besides its parameters
+    // are top-level too, and mean something else wherever a local of the same name is
+    fn hygienic(&self, name: &str, params: &[Parameter], body: &Expr, arguments: &[Expr]) -> bool {
+        let mut used = Names::default();
+        used.visit_expr(body);
+
+        let mut binders = Binders::default();
+        binders.visit_expr(body);
+
+        let inner = binders.0.clone();
+
+        for param in params {
+            binders.visit_parameter(param);
+        }
+
+        let mut passed = Names::default();
+
+        for argument in arguments {
+            passed.visit_expr(argument);
+        }
+
+        let mut free = used.0.iter().filter(|name| !binders.0.contains(*name));
+
+        !self.bound.contains(name)
+            && free.all(|name| !self.bound.contains(*name))
+            && passed.0.iter().all(|name| !inner.contains(name))
+    }
+
+    // how many parameters a call leaves for later, going by the checker's types
+    fn missing(&self, callee: &Expr, arguments: &[Expr]) -> Option<usize> {
+        let callee = self.checked?.get(callee.id)?;
+
+        checker::partial(callee, arguments.len()).map(<[Ty]>::len)
+    }
+
+    /// `f(1)` of a three-parameter `f` becomes a closure taking the other two.
+    /// Arguments other than literals are evaluated once, into locals ahead of
+    /// the statement, and so are a callee that isn't a name or a property and
+    /// the receiver of a property that isn't on a name.
+    fn partial_call(&mut self, callee: &Expr, arguments: &[Expr], missing: usize) -> String {
+        let callee = match &callee.kind {
+            ExprKind::PropertyAccess { object, name } => {
+                let mut receiver = self.callee(object);
+
+                if !is_place(object) {
+                    receiver = self.spill(&receiver);
+                }
+
+                if self.is_method_call(object, name) {
+                    format!("{}:{}", receiver, name)
+                } else {
+                    property(&receiver, name)
+                }
+            }
+
+            ExprKind::Identifier(_) => self.callee(callee),
+
+            _ => {
+                let value = self.expr(callee);
+                self.spill(&value)
+            }
+        };
+
+        let mut values = Vec::new();
+
+        for argument in arguments {
+            let value = self.expr(argument);
+
+            if let ExprKind::Literal(_) = argument.kind {
+                values.push(value);
+            } else {
+                values.push(self.spill(&value));
+            }
+        }
+
+        let params: Vec<String> = (0..missing).map(|_| self.fresh_temp()).collect();
+        values.extend(params.iter().cloned());
+
+        format!(
+            "function({}) return {}({}) end",
+            params.join(", "),
+            callee,
+            values.join(", ")
+        )
+    }
+
+    // `value.method()` passes `value` as `self`, while `Class.method()` doesn't
+    fn is_method_call(&self, object: &Expr, name: &str) -> bool {
+        if !self.instance_methods.contains(name) || LUAU_KEYWORDS.contains(&name) {
+            return false;
+        }
+
+        // with the checker's types, only class instances are receivers; a mixin's
+        // `self` stays unknown until it's mixed in, so it goes by name
+        if let Some(ty) = self.checked.and_then(|types| types.get(object.id)) {
+            let is_self = matches!(&object.kind, ExprKind::Identifier(object) if object == "self");
+
+            return is_instance(ty) || (is_self && *ty == Ty::Unknown);
+        }
+
+        !matches!(&object.kind, ExprKind::Identifier(object) if self.types.contains(object))
+    }
+
+    // `receiver.name(arguments)`, where `receiver` is the already lowered `object`
+    fn method_call(
+        &mut self,
+        object: &Expr,
+        receiver: &str,
+        name: &str,
+        arguments: &[Expr],
+    ) -> String {
+        let callee = if self.is_method_call(object, name) {
+            format!("{}:{}", receiver, name)
+        } else {
+            property(receiver, name)
+        };
+
+        let arguments = self.sequence(arguments, |this, _, arg| this.expr(arg));
+
+        format!("{}({})", callee, arguments.join(", "))
+    }
+
+    // what has to be non-nil for `object?.` to go on, joined with `and`, and the path
+    // to `object`: a chain of names, or a local holding anything else
+    fn guarded(&mut self, object: &Expr) -> (String, String) {
+        if let Some((mut guards, path)) = self.chain(object) {
+            guards.push(path.clone());
+
+            return (guards.join(" and "), path);
+        }
+
+        let value = self.expr(object);
+        let temp = self.spill(&value);
+
+        (temp.clone(), temp)
+    }
+
+    /// Splits a `a?.b.c?.d` style chain of names into the prefixes that must be
+    /// non-nil and the path they lead to, so it can be re-evaluated without a temp.
+    fn chain(&mut self, expr: &Expr) -> Option<(Vec<String>, String)> {
+        match &expr.kind {
+            ExprKind::Identifier(_) => Some((vec![], self.expr(expr))),
+
+            ExprKind::PropertyAccess { object, name } => {
+                let (guards, path) = self.chain(object)?;
+
+                guards.is_empty().then(|| (guards, property(&path, name)))
+            }
+
+            ExprKind::OptionalPropertyAccess { object, name } => {
+                let (mut guards, path) = self.chain(object)?;
+                let property = property(&path, name);
+                guards.push(path);
+
+                Some((guards, property))
+            }
+
+            _ => None,
+        }
+    }
+
+    // Luau only allows names, calls, indexing and parenthesised expressions as prefixes
+    fn callee(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Identifier(_)
+            | ExprKind::Grouping(_)
+            | ExprKind::PropertyAccess { .. }
+            | ExprKind::Call { .. } => self.expr(expr),
+
+            _ => {
+                let value = self.expr(expr);
+
+                if is_name(&value) {
+                    value
+                } else {
+                    format!("({})", value)
+                }
+            }
+        }
+    }
+
+    // an addition the checker typed as a string
+    fn is_concat(&self, expr: &Expr) -> bool {
+        matches!(
+            &expr.kind,
+            ExprKind::Binary {
+                op: BinaryOp::Add,
+                ..
+            }
+        ) && self.checked.and_then(|types| types.get(expr.id)) == Some(&Ty::String)
+    }
+
+    // `..` only takes strings and numbers, so a `Show` type parameter goes through `tostring`
+    fn concat_operand(&mut self, expr: &Expr) -> String {
+        match self.checked.and_then(|types| types.get(expr.id)) {
+            Some(Ty::Param(_)) => format!("tostring({})", self.expr(expr)),
+
+            _ => self.operand(expr, CONCAT_PRECEDENCE, false),
+        }
+    }
+
+    fn operand(&mut self, expr: &Expr, parent: u8, is_right: bool) -> String {
+        let operand = self.expr(expr);
+
+        self.parenthesize(expr, operand, parent, is_right)
+    }
+
+    // wraps `operand`, lowered from `expr`, if it would bind less tightly than `parent`
+    fn parenthesize(&self, expr: &Expr, operand: String, parent: u8, is_right: bool) -> String {
+        let precedence = match &expr.kind {
+            ExprKind::Binary { .. } if self.is_concat(expr) => CONCAT_PRECEDENCE,
+            ExprKind::Binary { op, .. } => binary_precedence(op),
+            ExprKind::Unary { .. } => UNARY_PRECEDENCE,
+            ExprKind::Lambda { .. } => 0,
+
+            // optional chains lower to `a and a.b`
+            ExprKind::OptionalPropertyAccess { .. } => binary_precedence(&BinaryOp::And),
+            ExprKind::Call { callee, .. }
+                if matches!(callee.kind, ExprKind::OptionalPropertyAccess { .. }) =>
+            {
+                binary_precedence(&BinaryOp::And)
+            }
+
+            _ => u8::MAX,
+        };
+
+        // a local left by lowering needs no parentheses whatever it came from
+        if !is_name(&operand) && (precedence < parent || (is_right && precedence == parent)) {
+            format!("({})", operand)
+        } else {
+            operand
+        }
+    }
+}
+
+const CONCAT_PRECEDENCE: u8 = 4; // between comparisons and `+`, and associative
+const ADDITIVE_PRECEDENCE: u8 = 5;
+const UNARY_PRECEDENCE: u8 = 7;
+
+fn binary_precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Coalesce => 0, // lowers to an `if` expression, which extends as far as it can
+        BinaryOp::Or => 1,
+        BinaryOp::And => 2,
+        BinaryOp::EqualEqual
+        | BinaryOp::NotEqual
+        | BinaryOp::Less
+        | BinaryOp::LessEqual
+        | BinaryOp::Greater
+        | BinaryOp::GreaterEqual => 3,
+        BinaryOp::Add | BinaryOp::Sub => ADDITIVE_PRECEDENCE,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 6,
+    }
+}
+
+fn binary_operator(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::EqualEqual => "==",
+        BinaryOp::NotEqual => "~=",
+        BinaryOp::Less => "<",
+        BinaryOp::LessEqual => "<=",
+        BinaryOp::Greater => ">",
+        BinaryOp::GreaterEqual => ">=",
+        BinaryOp::And => "and",
+        BinaryOp::Or => "or",
+        BinaryOp::Coalesce => unreachable!("`??` lowers to an `if` expression"),
+    }
+}
+
+fn literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Number(n) => n.to_string(),
+        Literal::Boolean(b) => b.to_string(),
+        Literal::Nil => "nil".into(),
+        Literal::String(s) => {
+            let mut quoted = String::from('"');
+
+            for c in s.chars() {
+                match c {
+                    '"' => quoted.push_str("\\\""),
+                    '\\' => quoted.push_str("\\\\"),
+                    '\n' => quoted.push_str("\\n"),
+                    '\t' => quoted.push_str("\\t"),
+                    '\r' => quoted.push_str("\\r"),
+
+                    _ => quoted.push(c),
+                }
+            }
+
+            quoted.push('"');
+            quoted
+        }
+    }
+}
+
+/// Spells an exx type as a Luau type annotation.
+fn luau_type(ty: &Type) -> String {
+    fn list(types: &[Type]) -> String {
+        types.iter().map(luau_type).collect::<Vec<_>>().join(", ")
+    }
+
+    match ty {
+        Type::Simple(name) => name.to_string(),
+        Type::Function(params, return_type) => {
+            format!("({}) -> {}", list(params), luau_type(return_type))
+        }
+
+        // tuples lower to arrays
+        Type::Tuple(items) => {
+            let mut elements: Vec<String> = Vec::new();
+
+            for item in items.iter().map(luau_type) {
+                if !elements.contains(&item) {
+                    elements.push(item);
+                }
+            }
+
+            format!("{{ {} }}", elements.join(" | "))
+        }
+
+        Type::Generic { name, params } => format!("{}<{}>", name, list(params)),
+        Type::Optional(inner) => match **inner {
+            Type::Function(..) => format!("({})?", luau_type(inner)),
+            _ => format!("{}?", luau_type(inner)),
+        },
+
+        Type::Array(element) => format!("{{ {} }}", luau_type(element)),
+
+        Type::Record { fields, indexer } => {
+            let mut entries: Vec<_> = fields
+                .iter()
+                .map(|field| {
+                    let access = if field.readonly { "read " } else { "" };
+
+                    let ty = match &field.ty {
+                        ty @ Type::Optional(_) => luau_type(ty),
+                        ty if field.optional => luau_type(&Type::Optional(Box::new(ty.clone()))),
+                        ty => luau_type(ty),
+                    };
+
+                    format!("{}{}: {}", access, table_key(&field.name), ty)
+                })
+                .collect();
+
+            if let Some(indexer) = indexer {
+                entries.push(format!(
+                    "[{}]: {}",
+                    luau_type(&indexer.0),
+                    luau_type(&indexer.1)
+                ));
+            }
+
+            if entries.is_empty() {
+                "{}".into()
+            } else {
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+
+        Type::Union(members) => members
+            .iter()
+            .map(|member| match member {
+                Type::Function(..) => format!("({})", luau_type(member)),
+                _ => luau_type(member),
+            })
+            .collect::<Vec<_>>()
+            .join(" | "),
+
+        // Luau only has singleton types for strings and booleans
+        Type::Literal(Literal::Number(_)) => "number".into(),
+        Type::Literal(value) => literal(value),
+
+        Type::Variant { name, fields } => variant_type(name, fields),
+    }
+}
+
+// a constructor's values are `{ tag = "Name", ...fields }`
+fn variant_type(name: &str, fields: &[Type]) -> String {
+    let tag = literal(&Literal::String(name.to_string()));
+
+    if fields.is_empty() {
+        return format!("{{ tag: {} }}", tag);
+    }
+
+    let mut elements: Vec<String> = Vec::new();
+
+    for field in fields.iter().map(luau_type) {
+        if !elements.contains(&field) {
+            elements.push(field);
+        }
+    }
+
+    format!("{{ tag: {}, [number]: {} }}", tag, elements.join(" | "))
+}
+
+// the lines of a raw Luau body, minus surrounding blank lines and common indentation
+fn dedent(body: &str) -> Vec<&str> {
+    let lines: Vec<_> = body.lines().collect();
+
+    let Some(first) = lines.iter().position(|line| !line.trim().is_empty()) else {
+        return vec![];
+    };
+
+    let last = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .unwrap();
+
+    let indent = lines[first..=last]
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines[first..=last]
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect()
+}
+
+fn property(object: &str, name: &str) -> String {
+    if LUAU_KEYWORDS.contains(&name) {
+        format!("{}[\"{}\"]", object, name)
+    } else {
+        format!("{}.{}", object, name)
+    }
+}
+
+fn table_key(name: &str) -> String {
+    if LUAU_KEYWORDS.contains(&name) {
+        format!("[\"{}\"]", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// expressions that can be re-evaluated freely: plain names and field paths
+fn is_place(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Identifier(_) => true,
+        ExprKind::PropertyAccess { object, .. } => is_place(object),
+
+        _ => false,
+    }
+}
+
+fn is_name(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// a lowered value that code running ahead of it can't change: a literal, or a name or a
+// path of names, which are taken to hold still as they are everywhere else
+fn is_settled(value: &str) -> bool {
+    let is_string = || {
+        let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+            return false;
+        };
+
+        let mut escaped = false;
+
+        inner.chars().all(|c| {
+            let quoted = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+
+            quoted
+        })
+    };
+
+    value.split('.').all(is_name) || value.parse::<f64>().is_ok() || is_string()
+}
+
+// `value` ready to have an operator put in front of it
+fn grouped(value: String) -> String {
+    if is_settled(&value) {
+        value
+    } else {
+        format!("({})", value)
+    }
+}
+
+// values whose methods take `self`: class instances, the interfaces they implement, and
+// type parameters, which only have methods through an interface bound
+fn is_instance(ty: &Ty) -> bool {
+    match ty {
+        Ty::Class(_) | Ty::Interface(_) | Ty::Param(_) => true,
+        Ty::Optional(inner) => is_instance(inner),
+        Ty::Union(members) => members.iter().all(is_instance),
+
+        _ => false,
+    }
+}
+
+// the locals an item declares, which the whole block it's in can name
+fn item_names(stmt: &Stmt) -> Vec<&str> {
+    match &stmt.kind {
+        StmtKind::FunctionDecl { name, .. }
+        | StmtKind::ClassDecl { name, .. }
+        | StmtKind::MixinDecl { name, .. }
+        | StmtKind::ModuleDecl { name, .. }
+        | StmtKind::Import(name) => vec![name],
+
+        StmtKind::TypeAlias { ty, .. } => ty
+            .variants()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(variant, _)| variant.as_str())
+            .collect(),
+
+        _ => vec![],
+    }
+}
+
+// the items named by a statement before theirs, which the resolver only allows from
+// inside functions
+fn forward_declared(stmts: &[Stmt]) -> HashSet<NodeId> {
+    let mut named = Names::default();
+    let mut forward = HashSet::new();
+
+    for stmt in stmts {
+        if item_names(stmt).iter().any(|name| named.0.contains(name)) {
+            forward.insert(stmt.id);
+        }
+
+        named.visit_stmt(stmt);
+    }
+
+    forward
+}
+
+// every name an expression uses, however deeply it's nested
+#[derive(Default)]
+struct Names<'a>(HashSet<&'a str>);
+
+impl<'a> Visitor<'a> for Names<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(name) | ExprKind::StructLiteral { name, .. } => {
+                self.0.insert(name);
+            }
+
+            _ => {}
+        }
+
+        walk_expr(self, expr);
+    }
+}
+
+// every name a statement or expression binds, however deeply it's nested
+#[derive(Default)]
+struct Binders<'a>(HashSet<&'a str>);
+
+impl<'a> Visitor<'a> for Binders<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        self.0.extend(item_names(stmt));
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        if let PatternKind::Identifier(name) = &pattern.kind {
+            self.0.insert(name);
+        }
+
+        walk_pattern(self, pattern);
+    }
+}
@@ -0,0 +1,828 @@
+//! A lossless concrete syntax tree, built by the parser alongside the AST.
+//!
+//! The green tree is immutable and position independent: every node only
+//! knows its kind, its width and its children, so subtrees can be shared.
+//! The red tree (`SyntaxNode`, `SyntaxToken`) wraps it with absolute offsets
+//! and parent pointers, and is what tools walk. Every token and trivia item of
+//! the source is a leaf, so the text of the root is the source, byte for byte.
+//!
+//! The parser makes a node each time it finishes an AST node, out of whatever
+//! it read for it, so the two trees have the same shape. Trivia between two
+//! nodes belongs to their parent. A declaration that fails to parse leaves an
+//! `Error` node behind and parsing carries on, so broken source still gets a
+//! whole tree.
+//!
+//! The typed nodes (`FunctionDecl`, `Binary`, ...) wrap a `SyntaxNode` of
+//! their kind and read their parts straight from it. `Parse::node` goes the
+//! other way, from a node of the `ast` to the syntax it was parsed from.
+
+use crate::ast::{self, ExprKind, StmtKind};
+use crate::lexer::Lexer;
+use crate::node::{NodeId, NodeMap};
+use crate::parser::{ParseError, Parser, ParserToken};
+use crate::span::Span;
+use crate::token::Token;
+use crate::tokentype::TokenType;
+use crate::visit::{Visitor, walk_expr, walk_pattern, walk_stmt};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Program,
+
+    VariableDecl,
+    FunctionDecl,
+    ClassDecl,
+    InterfaceDecl,
+    MixinDecl,
+    ModuleDecl,
+    TypeAlias,
+    Assign,
+    If,
+    While,
+    For,
+    Import,
+    ExpressionStmt,
+    Return,
+    Break,
+    Continue,
+
+    Binary,
+    Unary,
+    Literal,
+    Identifier,
+    Grouping,
+    Tuple,
+    StructLiteral,
+    Record,
+    Block,
+    PropertyAccess,
+    OptionalPropertyAccess,
+    Call,
+    Lambda,
+    Range,
+    Luau,
+    Match,
+    MatchArm,
+
+    Pattern,
+    Attribute,
+    MethodSignature,
+    Type,
+
+    Error, // a declaration that failed to parse, and the tokens skipped after it
+}
+
+impl From<&StmtKind> for SyntaxKind {
+    fn from(kind: &StmtKind) -> Self {
+        match kind {
+            StmtKind::VariableDecl { .. } => SyntaxKind::VariableDecl,
+            StmtKind::FunctionDecl { .. } => SyntaxKind::FunctionDecl,
+            StmtKind::ClassDecl { .. } => SyntaxKind::ClassDecl,
+            StmtKind::InterfaceDecl { .. } => SyntaxKind::InterfaceDecl,
+            StmtKind::MixinDecl { .. } => SyntaxKind::MixinDecl,
+            StmtKind::ModuleDecl { .. } => SyntaxKind::ModuleDecl,
+            StmtKind::TypeAlias { .. } => SyntaxKind::TypeAlias,
+            StmtKind::Assign { .. } => SyntaxKind::Assign,
+            StmtKind::If { .. } => SyntaxKind::If,
+            StmtKind::While { .. } => SyntaxKind::While,
+            StmtKind::For { .. } => SyntaxKind::For,
+            StmtKind::Import(_) => SyntaxKind::Import,
+            StmtKind::Expression(_) => SyntaxKind::ExpressionStmt,
+            StmtKind::Return(_) => SyntaxKind::Return,
+            StmtKind::Break => SyntaxKind::Break,
+            StmtKind::Continue => SyntaxKind::Continue,
+        }
+    }
+}
+
+impl From<&ExprKind> for SyntaxKind {
+    fn from(kind: &ExprKind) -> Self {
+        match kind {
+            ExprKind::Binary { .. } => SyntaxKind::Binary,
+            ExprKind::Unary { .. } => SyntaxKind::Unary,
+            ExprKind::Literal(_) => SyntaxKind::Literal,
+            ExprKind::Identifier(_) => SyntaxKind::Identifier,
+            ExprKind::Grouping(_) => SyntaxKind::Grouping,
+            ExprKind::Tuple(_) => SyntaxKind::Tuple,
+            ExprKind::StructLiteral { .. } => SyntaxKind::StructLiteral,
+            ExprKind::Record(_) => SyntaxKind::Record,
+            ExprKind::Block(_) => SyntaxKind::Block,
+            ExprKind::PropertyAccess { .. } => SyntaxKind::PropertyAccess,
+            ExprKind::OptionalPropertyAccess { .. } => SyntaxKind::OptionalPropertyAccess,
+            ExprKind::Call { .. } => SyntaxKind::Call,
+            ExprKind::Lambda { .. } => SyntaxKind::Lambda,
+            ExprKind::Range { .. } => SyntaxKind::Range,
+            ExprKind::Luau { .. } => SyntaxKind::Luau,
+            ExprKind::Match { .. } => SyntaxKind::Match,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    width: usize,
+    children: Vec<GreenElement>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+    token_type: TokenType,
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let width = children.iter().map(GreenElement::width).sum();
+
+        Self {
+            kind,
+            width,
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+
+impl GreenToken {
+    pub fn new(token_type: TokenType, text: impl Into<String>) -> Self {
+        Self {
+            token_type,
+            text: text.into(),
+        }
+    }
+
+    pub fn token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.token_type,
+            TokenType::Whitespace | TokenType::Comment(_)
+        )
+    }
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+}
+
+/// A node of the red tree: a green node at a known offset, with its parent.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: GreenNode) -> Self {
+        Self(Rc::new(NodeData {
+            green: Rc::new(green),
+            offset: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    /// The byte range this node covers in the source.
+    pub fn text_range(&self) -> ops::Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.width
+    }
+
+    pub fn text(&self) -> String {
+        self.tokens()
+            .map(|token| token.green.text.clone())
+            .collect()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + use<> {
+        let parent = self.clone();
+        let mut offset = self.0.offset;
+
+        (0..self.0.green.children.len()).map(move |index| {
+            let child = &parent.0.green.children[index];
+            let start = offset;
+            offset += child.width();
+
+            match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    offset: start,
+                    parent: Some(parent.clone()),
+                }))),
+
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    offset: start,
+                    parent: parent.clone(),
+                }),
+            }
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + use<> {
+        self.children_with_tokens()
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+    }
+
+    /// This node and every node below it, in preorder.
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut nodes = vec![self.clone()];
+
+        for child in self.children() {
+            nodes.extend(child.descendants());
+        }
+
+        nodes
+    }
+
+    /// Every token below this node in source order, trivia included.
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+
+        tokens.into_iter()
+    }
+
+    fn collect_tokens(&self, tokens: &mut Vec<SyntaxToken>) {
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// The deepest node whose range contains `range`.
+    pub fn covering_node(&self, range: ops::Range<usize>) -> SyntaxNode {
+        self.children()
+            .find(|child| {
+                let own = child.text_range();
+                own.start <= range.start && range.end <= own.end
+            })
+            .map_or_else(|| self.clone(), |child| child.covering_node(range))
+    }
+}
+
+impl SyntaxToken {
+    pub fn token_type(&self) -> &TokenType {
+        &self.green.token_type
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn text_range(&self) -> ops::Range<usize> {
+        self.offset..self.offset + self.green.text.len()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        self.green.is_trivia()
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.text_range())
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{:?} {:?}",
+            self.token_type(),
+            self.text_range(),
+            self.text()
+        )
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// Builds the green tree while the parser reads tokens, bottom-up: once the
+/// parser has finished a node, everything read since it began is gathered
+/// into it.
+pub(crate) struct Sink {
+    tokens: Vec<(usize, Rc<GreenToken>)>, // every token of the source, trivia included
+    next: usize,
+    children: Vec<(usize, GreenElement)>, // what isn't in a node yet, each with its offset
+}
+
+impl Sink {
+    pub(crate) fn new(source: &str, tokens: &[Token]) -> Self {
+        let tokens = tokens
+            .iter()
+            .map(|token| token.span.start.absolute..token.span.end.absolute)
+            .zip(tokens)
+            .filter(|(range, _)| !range.is_empty())
+            .map(|(range, token)| {
+                let green = GreenToken::new(token.token_type.clone(), &source[range.clone()]);
+
+                (range.start, Rc::new(green))
+            })
+            .collect();
+
+        Self {
+            tokens,
+            next: 0,
+            children: vec![],
+        }
+    }
+
+    /// Adds the parser's next token, after any trivia in front of it.
+    pub(crate) fn token(&mut self) {
+        while let Some((offset, token)) = self.tokens.get(self.next).cloned() {
+            self.next += 1;
+
+            let trivia = token.is_trivia();
+            self.children.push((offset, GreenElement::Token(token)));
+
+            if !trivia {
+                break;
+            }
+        }
+    }
+
+    /// Gathers everything from offset `start` on into a node of `kind`.
+    pub(crate) fn node(&mut self, kind: SyntaxKind, start: usize) {
+        let first = self.children.partition_point(|(offset, _)| *offset < start);
+        let offset = self
+            .children
+            .get(first)
+            .map_or(start, |(offset, _)| *offset);
+        let children = self
+            .children
+            .drain(first..)
+            .map(|(_, element)| element)
+            .collect();
+
+        let node = GreenNode::new(kind, children);
+        self.children
+            .push((offset, GreenElement::Node(Rc::new(node))));
+    }
+
+    /// The whole tree, with any tokens the parser never read at its end.
+    pub(crate) fn finish(mut self) -> GreenNode {
+        let rest = self.tokens.drain(self.next..);
+        self.children
+            .extend(rest.map(|(offset, token)| (offset, GreenElement::Token(token))));
+
+        let children = self
+            .children
+            .into_iter()
+            .map(|(_, element)| element)
+            .collect();
+
+        GreenNode::new(SyntaxKind::Program, children)
+    }
+}
+
+/// A syntax node of one known kind, with accessors for its parts.
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+
+    fn text(&self) -> String {
+        self.syntax().text()
+    }
+}
+
+macro_rules! ast_nodes {
+    ($($kind:ident),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone)]
+            pub struct $kind(SyntaxNode);
+
+            impl AstNode for $kind {
+                fn can_cast(kind: SyntaxKind) -> bool {
+                    kind == SyntaxKind::$kind
+                }
+
+                fn cast(node: SyntaxNode) -> Option<Self> {
+                    Self::can_cast(node.kind()).then(|| Self(node))
+                }
+
+                fn syntax(&self) -> &SyntaxNode {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+// one typed node for each kind of node in one of these enums
+macro_rules! ast_enum {
+    ($name:ident { $($kind:ident),* $(,)? }) => {
+        #[derive(Debug, Clone)]
+        pub enum $name {
+            $($kind($kind),)*
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                matches!(kind, $(SyntaxKind::$kind)|*)
+            }
+
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                match node.kind() {
+                    $(SyntaxKind::$kind => Some($name::$kind($kind(node))),)*
+
+                    _ => None,
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $($name::$kind(node) => &node.0,)*
+                }
+            }
+        }
+    };
+}
+
+ast_nodes!(
+    Program,
+    VariableDecl,
+    FunctionDecl,
+    ClassDecl,
+    InterfaceDecl,
+    MixinDecl,
+    ModuleDecl,
+    TypeAlias,
+    Assign,
+    If,
+    While,
+    For,
+    Import,
+    ExpressionStmt,
+    Return,
+    Break,
+    Continue,
+    Binary,
+    Unary,
+    Literal,
+    Identifier,
+    Grouping,
+    Tuple,
+    StructLiteral,
+    Record,
+    Block,
+    PropertyAccess,
+    OptionalPropertyAccess,
+    Call,
+    Lambda,
+    Range,
+    Luau,
+    Match,
+    MatchArm,
+    Pattern,
+    Attribute,
+    MethodSignature,
+    Type,
+    Error,
+);
+
+ast_enum!(Stmt {
+    VariableDecl,
+    FunctionDecl,
+    ClassDecl,
+    InterfaceDecl,
+    MixinDecl,
+    ModuleDecl,
+    TypeAlias,
+    Assign,
+    If,
+    While,
+    For,
+    Import,
+    ExpressionStmt,
+    Return,
+    Break,
+    Continue,
+});
+
+ast_enum!(Expr {
+    Binary,
+    Unary,
+    Literal,
+    Identifier,
+    Grouping,
+    Tuple,
+    StructLiteral,
+    Record,
+    Block,
+    PropertyAccess,
+    OptionalPropertyAccess,
+    Call,
+    Lambda,
+    Range,
+    Luau,
+    Match,
+});
+
+impl Program {
+    /// The declarations that parsed; the ones that didn't are `Error` nodes.
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> + use<> {
+        children(&self.0)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = Error> + use<> {
+        children(&self.0)
+    }
+}
+
+impl VariableDecl {
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+
+    pub fn type_annotation(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn initializer(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl FunctionDecl {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        identifier(&self.0)
+    }
+
+    pub fn body(&self) -> impl Iterator<Item = Stmt> + use<> {
+        children(&self.0)
+    }
+}
+
+impl ClassDecl {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        identifier(&self.0)
+    }
+
+    /// Fields and methods, static or not, in source order.
+    pub fn members(&self) -> impl Iterator<Item = Stmt> + use<> {
+        children(&self.0)
+    }
+}
+
+impl ExpressionStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Return {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Binary {
+    pub fn left(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn operator(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .find_map(|element| match element {
+                SyntaxElement::Token(token) if !token.is_trivia() => Some(token),
+                _ => None,
+            })
+    }
+
+    pub fn right(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl Identifier {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        identifier(&self.0)
+    }
+}
+
+impl PropertyAccess {
+    pub fn object(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        identifier(&self.0)
+    }
+}
+
+impl Call {
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arguments(&self) -> impl Iterator<Item = Expr> + use<> {
+        children(&self.0).skip(1)
+    }
+}
+
+fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+    parent.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(parent: &SyntaxNode) -> impl Iterator<Item = N> + use<N> {
+    parent.children().filter_map(N::cast)
+}
+
+// the first name directly under `parent`, which for a declaration is its own
+fn identifier(parent: &SyntaxNode) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .find_map(|element| match element {
+            SyntaxElement::Token(token)
+                if matches!(token.token_type(), TokenType::Identifier(_)) =>
+            {
+                Some(token)
+            }
+
+            _ => None,
+        })
+}
+
+/// The result of parsing a file: the AST, the syntax tree behind it, and
+/// everything that went wrong along the way.
+pub struct Parse {
+    pub program: Vec<ast::Stmt>, // the declarations that parsed
+    pub syntax: SyntaxNode,
+    pub errors: Vec<ParseError>,
+    nodes: NodeMap<SyntaxNode>,
+}
+
+impl Parse {
+    pub fn tree(&self) -> Program {
+        Program(self.syntax.clone())
+    }
+
+    /// The syntax the `Stmt`, `Expr` or `Pattern` numbered `id` was parsed from.
+    pub fn node(&self, id: NodeId) -> Option<&SyntaxNode> {
+        self.nodes.get(id)
+    }
+}
+
+/// Parses `source` into both an AST and a lossless syntax tree. Parsing
+/// doesn't stop at the first error, so there is a tree even for broken code.
+pub fn parse(source: &str) -> Parse {
+    let tokens: Vec<Token> = Lexer::with_trivia(source).collect();
+
+    let parser_tokens: Vec<ParserToken> = tokens
+        .iter()
+        .filter(|token| {
+            !matches!(
+                token.token_type,
+                TokenType::Whitespace | TokenType::Comment(_)
+            )
+        })
+        .cloned()
+        .map(ParserToken::from)
+        .collect();
+
+    let mut parser = Parser::new(parser_tokens).with_sink(Sink::new(source, &tokens));
+    let (program, errors) = parser.parse_recovering();
+    let syntax = SyntaxNode::new_root(parser.into_sink().unwrap().finish());
+
+    let mut nodes = Nodes::new(&syntax);
+    for stmt in &program {
+        nodes.visit_stmt(stmt);
+    }
+
+    Parse {
+        program,
+        syntax,
+        errors,
+        nodes: nodes.found,
+    }
+}
+
+// pairs each numbered AST node with the syntax node the parser made for it,
+// which has the same kind and covers the same source
+struct Nodes {
+    syntax: HashMap<(SyntaxKind, ops::Range<usize>), SyntaxNode>,
+    found: NodeMap<SyntaxNode>,
+}
+
+impl Nodes {
+    fn new(root: &SyntaxNode) -> Self {
+        let mut syntax = HashMap::new();
+
+        // preorder, so of two nodes that look the same the outer one wins
+        for node in root.descendants() {
+            syntax
+                .entry((node.kind(), node.text_range()))
+                .or_insert(node);
+        }
+
+        Self {
+            syntax,
+            found: NodeMap::new(),
+        }
+    }
+
+    fn pair(&mut self, id: NodeId, kind: SyntaxKind, span: &Span) {
+        let range = span.start.absolute..span.end.absolute;
+
+        if let Some(node) = self.syntax.get(&(kind, range)) {
+            self.found.insert(id, node.clone());
+        }
+    }
+}
+
+impl Visitor<'_> for Nodes {
+    fn visit_stmt(&mut self, stmt: &ast::Stmt) {
+        self.pair(stmt.id, SyntaxKind::from(&stmt.kind), &stmt.span);
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &ast::Expr) {
+        self.pair(expr.id, SyntaxKind::from(&expr.kind), &expr.span);
+        walk_expr(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &ast::Pattern) {
+        self.pair(pattern.id, SyntaxKind::Pattern, &pattern.span);
+        walk_pattern(self, pattern);
+    }
+}
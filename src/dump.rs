@@ -0,0 +1,76 @@
+//! JSON dumps of the token stream and the AST, for tools that can't link this
+//! crate. Only built with the `serde` feature.
+//!
+//! Every dump is an object with a `format` (`"exx-tokens"` or `"exx-ast"`) and
+//! a `version`, which goes up whenever the shape of the output changes:
+//!
+//! ```json
+//! { "format": "exx-tokens", "version": 6, "tokens": [Token, ...] }
+//! { "format": "exx-ast", "version": 6, "program": [Stmt, ...] }
+//! { "format": "exx-ast", "version": 6, "error": { "message": "...", "span": Span } }
+//! ```
+//!
+//! Structs become objects keyed by their Rust field names. Enums use serde's
+//! default tagging: unit variants are plain strings (`"Semicolon"`), and every
+//! other variant is an object with the variant name as its only key
+//! (`{ "Identifier": "x" }`). A `Span` is `{ "start": Position, "end": Position }`,
+//! where a `Position` has a 1-based `line` and `column` and a 0-based byte
+//! offset in `absolute`. Statements, expressions and patterns also carry an
+//! `id` that is unique within the dump.
+//!
+//! Version 2 added node ids, version 3 type parameters on functions and
+//! interface methods, version 4 `match` expressions and union, literal and
+//! variant types, version 5 `bounds` on type parameters, and version 6 `[T]`
+//! array types.
+
+use crate::ast::Stmt;
+use crate::parser::ParseError;
+use crate::token::Token;
+use serde::Serialize;
+
+/// The schema version written to every dump.
+pub const VERSION: u32 = 6;
+
+#[derive(Serialize)]
+struct Dump<T> {
+    format: &'static str,
+    version: u32,
+
+    #[serde(flatten)]
+    body: T,
+}
+
+#[derive(Serialize)]
+struct Tokens<'a> {
+    tokens: &'a [Token],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Ast<'a> {
+    Program(&'a [Stmt]),
+    Error(&'a ParseError),
+}
+
+/// Dumps a token stream, including any lexer errors attached to each token.
+pub fn tokens(tokens: &[Token]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Dump {
+        format: "exx-tokens",
+        version: VERSION,
+        body: Tokens { tokens },
+    })
+}
+
+/// Dumps a parsed program, or the error that stopped the parser.
+pub fn ast(program: Result<&[Stmt], &ParseError>) -> serde_json::Result<String> {
+    let body = match program {
+        Ok(program) => Ast::Program(program),
+        Err(error) => Ast::Error(error),
+    };
+
+    serde_json::to_string_pretty(&Dump {
+        format: "exx-ast",
+        version: VERSION,
+        body,
+    })
+}
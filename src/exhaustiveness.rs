@@ -0,0 +1,411 @@
+use crate::ast::Literal;
+use crate::checker::Ty;
+use std::fmt;
+
+// stops a match over many nested types from listing every combination
+const WITNESS_LIMIT: usize = 8;
+
+/// A pattern reduced to what matters for exhaustiveness: which constructor it
+/// requires, if any, and the patterns for that constructor's fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pat {
+    Wild, // `_`, or a binding
+    Ctor(Ctor, Vec<Pat>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ctor {
+    Variant(String),
+    Literal(Literal),
+    Tuple(usize),
+    Record(Vec<String>), // the fields a pattern names, in its own order
+}
+
+/// Where algebraic data types come from; implemented by the checker.
+pub trait Variants {
+    /// The constructors of `adt` and the types of their fields, with the
+    /// type parameters bound to `args` (or `Unknown`, past the end of them).
+    fn variants(&self, adt: &str, args: &[Ty]) -> Vec<(String, Vec<Ty>)>;
+
+    /// The algebraic data type `constructor` belongs to.
+    fn adt(&self, constructor: &str) -> Option<&str>;
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Example values no arm matches, at most `WITNESS_LIMIT` of them.
+    pub missing: Vec<Pat>,
+    /// The indices of arms every value they match is matched by an earlier arm.
+    pub unreachable: Vec<usize>,
+}
+
+/// Checks the arms of a `match` on a value of type `ty`, using the usefulness
+/// algorithm from Maranget's "Warnings for pattern matching": an arm is
+/// reachable if it's useful after the arms above it, and the match is
+/// exhaustive if a wildcard isn't useful after all of them.
+pub fn check(variants: &impl Variants, arms: &[Pat], ty: &Ty) -> Report {
+    let checker = Checker { variants };
+    let mut rows: Vec<Vec<Pat>> = Vec::new();
+    let mut report = Report::default();
+
+    for (index, arm) in arms.iter().enumerate() {
+        let row = vec![arm.clone()];
+
+        if checker
+            .useful(&rows, &row, std::slice::from_ref(ty))
+            .is_empty()
+        {
+            report.unreachable.push(index);
+        }
+
+        rows.push(row);
+    }
+
+    report.missing = checker
+        .useful(&rows, &[Pat::Wild], std::slice::from_ref(ty))
+        .into_iter()
+        .map(|mut witness| witness.remove(0))
+        .collect();
+
+    report
+}
+
+struct Checker<'v, V> {
+    variants: &'v V,
+}
+
+impl<V: Variants> Checker<'_, V> {
+    /// The values matched by `row` that no row in `rows` matches, as example
+    /// rows of patterns; empty when `row` is redundant.
+    fn useful(&self, rows: &[Vec<Pat>], row: &[Pat], types: &[Ty]) -> Vec<Vec<Pat>> {
+        let Some((head, rest)) = row.split_first() else {
+            return if rows.is_empty() {
+                vec![vec![]]
+            } else {
+                vec![]
+            };
+        };
+
+        let column: Vec<&Pat> = rows.iter().map(|row| &row[0]).chain([head]).collect();
+        let signature = self.signature(&types[0], &column);
+
+        if let Pat::Ctor(ctor, args) = head {
+            let (ctor, fields) = signature
+                .and_then(|signature| signature.into_iter().find(|(known, _)| same(known, ctor)))
+                .unwrap_or_else(|| (ctor.clone(), vec![Ty::Unknown; args.len()]));
+
+            let mut row = specialise(head, &ctor, fields.len()).unwrap_or_default();
+            row.extend_from_slice(rest);
+
+            return self
+                .specialised(rows, &row, &ctor, &fields, &types[1..])
+                .into_iter()
+                .map(|witness| rebuild(&ctor, fields.len(), witness))
+                .collect();
+        }
+
+        let used: Vec<&Ctor> = column
+            .iter()
+            .filter_map(|pat| match pat {
+                Pat::Ctor(ctor, _) => Some(ctor),
+                Pat::Wild => None,
+            })
+            .collect();
+
+        let signature = signature.filter(|_| !used.is_empty());
+
+        // when the arms use every constructor, each has to be checked on its own
+        if let Some(signature) = &signature
+            && signature
+                .iter()
+                .all(|(ctor, _)| used.iter().any(|used| same(ctor, used)))
+        {
+            let mut witnesses = Vec::new();
+
+            for (ctor, fields) in signature {
+                let mut wild = vec![Pat::Wild; fields.len()];
+                wild.extend_from_slice(rest);
+
+                let found = self.specialised(rows, &wild, ctor, fields, &types[1..]);
+                witnesses.extend(
+                    found
+                        .into_iter()
+                        .map(|witness| rebuild(ctor, fields.len(), witness)),
+                );
+
+                if witnesses.len() >= WITNESS_LIMIT {
+                    break;
+                }
+            }
+
+            witnesses.truncate(WITNESS_LIMIT);
+
+            return witnesses;
+        }
+
+        // otherwise a wildcard gets past the constructors nobody matched
+        let defaults: Vec<Vec<Pat>> = rows
+            .iter()
+            .filter(|row| row[0] == Pat::Wild)
+            .map(|row| row[1..].to_vec())
+            .collect();
+
+        let found = self.useful(&defaults, rest, &types[1..]);
+
+        if found.is_empty() {
+            return found;
+        }
+
+        let heads: Vec<Pat> = match &signature {
+            Some(signature) => signature
+                .iter()
+                .filter(|(ctor, _)| !used.iter().any(|used| same(ctor, used)))
+                .map(|(ctor, fields)| Pat::Ctor(ctor.clone(), vec![Pat::Wild; fields.len()]))
+                .collect(),
+
+            None => vec![Pat::Wild],
+        };
+
+        let mut witnesses = Vec::new();
+
+        for witness in found {
+            for head in &heads {
+                let mut row = vec![head.clone()];
+                row.extend(witness.iter().cloned());
+
+                witnesses.push(row);
+            }
+        }
+
+        witnesses.truncate(WITNESS_LIMIT);
+
+        witnesses
+    }
+
+    // `useful` on the rows that start with `ctor`, with its fields spread out;
+    // `row` has had that done already
+    fn specialised(
+        &self,
+        rows: &[Vec<Pat>],
+        row: &[Pat],
+        ctor: &Ctor,
+        fields: &[Ty],
+        types: &[Ty],
+    ) -> Vec<Vec<Pat>> {
+        let rows: Vec<Vec<Pat>> = rows
+            .iter()
+            .filter_map(|row| {
+                let mut specialised = specialise(&row[0], ctor, fields.len())?;
+                specialised.extend_from_slice(&row[1..]);
+
+                Some(specialised)
+            })
+            .collect();
+
+        let mut all_types = fields.to_vec();
+        all_types.extend_from_slice(types);
+
+        self.useful(&rows, row, &all_types)
+    }
+
+    /// Every constructor of `ty`, with the types of their fields, or `None`
+    /// for types with too many values to list, like `number`. Types the
+    /// checker couldn't work out are guessed from the patterns in `column`.
+    fn signature(&self, ty: &Ty, column: &[&Pat]) -> Option<Vec<(Ctor, Vec<Ty>)>> {
+        let booleans = || {
+            [true, false]
+                .map(|value| (Ctor::Literal(Literal::Boolean(value)), vec![]))
+                .to_vec()
+        };
+
+        // a record is a single constructor with every field any pattern names
+        let record = |fields: &dyn Fn(&str) -> Ty| {
+            let mut names: Vec<String> = Vec::new();
+
+            for pat in column {
+                if let Pat::Ctor(Ctor::Record(named), _) = pat {
+                    for name in named {
+                        if !names.contains(name) {
+                            names.push(name.clone());
+                        }
+                    }
+                }
+            }
+
+            let types = names.iter().map(|name| fields(name)).collect();
+
+            vec![(Ctor::Record(names), types)]
+        };
+
+        match ty {
+            Ty::Boolean => Some(booleans()),
+            Ty::Adt { name, args } => Some(self.adt_signature(name, args)),
+            Ty::Tuple(items) => Some(vec![(Ctor::Tuple(items.len()), items.clone())]),
+
+            Ty::Record { fields, .. } => Some(record(&|name| {
+                fields
+                    .iter()
+                    .find(|field| field.name == name)
+                    .map_or(Ty::Unknown, |field| field.ty.clone())
+            })),
+
+            Ty::Class(_) | Ty::Interface(_) => Some(record(&|_| Ty::Unknown)),
+
+            Ty::Union(members) => {
+                let mut signature = Vec::new();
+
+                for member in members {
+                    match member {
+                        Ty::Literal(value) => {
+                            signature.push((Ctor::Literal(value.clone()), vec![]))
+                        }
+                        Ty::Nil => signature.push((Ctor::Literal(Literal::Nil), vec![])),
+                        Ty::Boolean => signature.extend(booleans()),
+
+                        _ => return None,
+                    }
+                }
+
+                Some(signature)
+            }
+
+            Ty::Optional(inner) => {
+                let mut signature = self.signature(inner, column)?;
+                signature.push((Ctor::Literal(Literal::Nil), vec![]));
+
+                Some(signature)
+            }
+
+            Ty::Unknown | Ty::Var(_) | Ty::Param(_) => {
+                let first = column.iter().find_map(|pat| match pat {
+                    Pat::Ctor(ctor, args) => Some((ctor, args.len())),
+                    Pat::Wild => None,
+                })?;
+
+                match first {
+                    (Ctor::Variant(name), _) => {
+                        let adt = self.variants.adt(name)?;
+
+                        Some(self.adt_signature(adt, &[]))
+                    }
+
+                    (Ctor::Literal(Literal::Boolean(_)), _) => Some(booleans()),
+                    (Ctor::Tuple(len), _) => {
+                        Some(vec![(Ctor::Tuple(*len), vec![Ty::Unknown; *len])])
+                    }
+
+                    (Ctor::Record(_), _) => Some(record(&|_| Ty::Unknown)),
+                    (Ctor::Literal(_), _) => None,
+                }
+            }
+
+            _ => None,
+        }
+    }
+
+    fn adt_signature(&self, adt: &str, args: &[Ty]) -> Vec<(Ctor, Vec<Ty>)> {
+        self.variants
+            .variants(adt, args)
+            .into_iter()
+            .map(|(name, fields)| (Ctor::Variant(name), fields))
+            .collect()
+    }
+}
+
+// records match whichever fields they name, so any two are the same constructor
+fn same(a: &Ctor, b: &Ctor) -> bool {
+    match (a, b) {
+        (Ctor::Record(_), Ctor::Record(_)) => true,
+
+        _ => a == b,
+    }
+}
+
+/// The patterns for the fields of `ctor` that `pat` requires, or `None` if
+/// `pat` can't match a value built with `ctor`.
+fn specialise(pat: &Pat, ctor: &Ctor, arity: usize) -> Option<Vec<Pat>> {
+    match pat {
+        Pat::Wild => Some(vec![Pat::Wild; arity]),
+
+        // lined up with the fields of `ctor`, which names every field in the column
+        Pat::Ctor(Ctor::Record(named), args) => {
+            let Ctor::Record(fields) = ctor else {
+                return None;
+            };
+
+            Some(
+                fields
+                    .iter()
+                    .map(|field| {
+                        named
+                            .iter()
+                            .position(|name| name == field)
+                            .map_or(Pat::Wild, |index| args[index].clone())
+                    })
+                    .collect(),
+            )
+        }
+
+        Pat::Ctor(other, args) if other == ctor && args.len() == arity => Some(args.clone()),
+
+        // a constructor pattern with the wrong number of fields has already been reported
+        Pat::Ctor(other, _) if other == ctor => Some(vec![Pat::Wild; arity]),
+
+        Pat::Ctor(..) => None,
+    }
+}
+
+// folds the first `arity` patterns of a witness back into a `ctor` pattern
+fn rebuild(ctor: &Ctor, arity: usize, mut witness: Vec<Pat>) -> Vec<Pat> {
+    let rest = witness.split_off(arity);
+    let mut row = vec![Pat::Ctor(ctor.clone(), witness)];
+    row.extend(rest);
+
+    row
+}
+
+impl fmt::Display for Pat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter<'_>, pats: &[Pat]) -> fmt::Result {
+            for (index, pat) in pats.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+
+                write!(f, "{}", pat)?;
+            }
+
+            Ok(())
+        }
+
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Ctor(Ctor::Variant(name), args) if args.is_empty() => write!(f, "{}", name),
+            Pat::Ctor(Ctor::Variant(name), args) => {
+                write!(f, "{}(", name)?;
+                list(f, args)?;
+                write!(f, ")")
+            }
+
+            Pat::Ctor(Ctor::Literal(value), _) => write!(f, "{}", value),
+            Pat::Ctor(Ctor::Tuple(_), args) => {
+                write!(f, "(")?;
+                list(f, args)?;
+                write!(f, ")")
+            }
+
+            // only the fields that narrow things down are worth showing
+            Pat::Ctor(Ctor::Record(names), args) => {
+                write!(f, "{{ ")?;
+
+                for (name, arg) in names.iter().zip(args) {
+                    if *arg != Pat::Wild {
+                        write!(f, "{}: {}, ", name, arg)?;
+                    }
+                }
+
+                write!(f, ".. }}")
+            }
+        }
+    }
+}
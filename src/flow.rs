@@ -0,0 +1,649 @@
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::node::{DefId, Tables};
+use crate::span::Span;
+use crate::visit::{Visitor, walk_expr};
+use std::collections::HashMap;
+
+/// Builds a control-flow graph of every function body, and reports functions
+/// that can end without returning a value, statements that can never run, and
+/// variables that are read before they are definitely assigned. Needs the
+/// `defs` the resolver records.
+pub fn check(program: &[Stmt], tables: &Tables) -> Vec<Diagnostic> {
+    let (mut diagnostics, _) = Builder::body(tables, |builder| builder.stmts(program));
+
+    // nested functions are analysed first, so put everything back in source order
+    diagnostics.sort_by_key(|diagnostic| {
+        diagnostic
+            .labels
+            .first()
+            .map(|label| label.span.start.absolute)
+    });
+
+    diagnostics
+}
+
+type BlockId = usize;
+
+const ENTRY: BlockId = 0;
+
+/// Straight-line code: control enters at the top, runs every event in order,
+/// then moves on to one of the successors.
+#[derive(Default)]
+struct Block<'a> {
+    events: Vec<Event<'a>>,
+    successors: Vec<BlockId>,
+    first: Option<&'a Span>, // the first statement lowered into the block
+    after: Option<&'a Span>, // the `return`, `break` or `continue` the block follows
+}
+
+// what happens to the locals being tracked, which are indexes into `locals`
+#[derive(Clone, Copy)]
+enum Event<'a> {
+    Declare(usize),
+    Assign(usize, &'a Span),
+    Read(usize, &'a Span),
+}
+
+/// A `let` or `var` declared without a value, which has to be assigned before
+/// it can be read.
+struct Local<'a> {
+    name: &'a str,
+    span: &'a Span,
+    is_mutable: bool,
+}
+
+struct Builder<'a> {
+    tables: &'a Tables,
+    blocks: Vec<Block<'a>>,
+    current: BlockId,
+    loops: Vec<(BlockId, BlockId)>, // where `continue` and `break` go
+    locals: Vec<Local<'a>>,
+    indexes: HashMap<DefId, usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Builder<'a> {
+    /// Analyses the body `lower` builds, returning what it reports and
+    /// whether control can fall off its end.
+    fn body(tables: &'a Tables, lower: impl FnOnce(&mut Self)) -> (Vec<Diagnostic>, bool) {
+        let mut builder = Builder {
+            tables,
+            blocks: vec![Block::default()],
+            current: ENTRY,
+            loops: vec![],
+            locals: vec![],
+            indexes: HashMap::new(),
+            diagnostics: vec![],
+        };
+
+        lower(&mut builder);
+
+        let reachable = builder.reachable();
+        let falls_through = reachable[builder.current];
+
+        builder.unreachable_code(&reachable);
+        builder.assignments(&reachable);
+
+        (builder.diagnostics, falls_through)
+    }
+
+    fn block(&mut self) -> BlockId {
+        self.blocks.push(Block::default());
+        self.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: BlockId, to: BlockId) {
+        self.blocks[from].successors.push(to);
+    }
+
+    fn event(&mut self, event: Event<'a>) {
+        self.blocks[self.current].events.push(event);
+    }
+
+    // whatever follows a `return`, `break` or `continue` starts a block nothing jumps to
+    fn terminate(&mut self, span: &'a Span) {
+        self.current = self.block();
+        self.blocks[self.current].after = Some(span);
+    }
+
+    // a separate body, like a nested function, gets a graph of its own
+    fn nested(&mut self, lower: impl FnOnce(&mut Builder<'a>)) -> bool {
+        let (diagnostics, falls_through) = Builder::body(self.tables, lower);
+        self.diagnostics.extend(diagnostics);
+
+        falls_through
+    }
+
+    fn function(&mut self, stmt: &'a Stmt) {
+        let StmtKind::FunctionDecl {
+            name,
+            return_type,
+            body,
+            ..
+        } = &stmt.kind
+        else {
+            return;
+        };
+
+        let falls_through = self.nested(|builder| builder.stmts(body));
+
+        // a function declared to return nil can just run off its end
+        let Some(return_type) = return_type else {
+            return;
+        };
+
+        if !falls_through || matches!(return_type, Type::Literal(Literal::Nil)) {
+            return;
+        }
+
+        let span = body.last().map_or(&stmt.span, |last| &last.span);
+
+        self.diagnostics.push(
+            Diagnostic::error("E400", "Not all code paths return a value")
+                .with_label(
+                    span.clone(),
+                    format!(
+                        "`{}` can reach its end here without returning a `{}`",
+                        name, return_type
+                    ),
+                )
+                .with_note(format!(
+                    "help: return a value on every path through `{}`",
+                    name
+                )),
+        );
+    }
+
+    // tracks the names a declaration without a value introduces
+    fn declare(&mut self, pattern: &'a Pattern, is_mutable: bool) {
+        match &pattern.kind {
+            PatternKind::Identifier(name) => {
+                let Some(&def) = self.tables.defs.get(pattern.id) else {
+                    return;
+                };
+
+                let index = self.locals.len();
+
+                self.locals.push(Local {
+                    name,
+                    span: &pattern.span,
+                    is_mutable,
+                });
+                self.indexes.insert(def, index);
+                self.event(Event::Declare(index));
+            }
+
+            PatternKind::Tuple(items) | PatternKind::Constructor { args: items, .. } => {
+                for item in items {
+                    self.declare(item, is_mutable);
+                }
+            }
+
+            PatternKind::Record { fields, .. } => {
+                for field in fields {
+                    self.declare(&field.pattern, is_mutable);
+                }
+            }
+
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+    }
+
+    fn local(&self, expr: &Expr) -> Option<usize> {
+        let ExprKind::Identifier(_) = &expr.kind else {
+            return None;
+        };
+
+        let def = self.tables.defs.get(expr.id)?;
+
+        self.indexes.get(def).copied()
+    }
+
+    fn stmts(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            self.blocks[self.current].first.get_or_insert(&stmt.span);
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match &stmt.kind {
+            StmtKind::VariableDecl {
+                is_mutable,
+                pattern,
+                initializer,
+                ..
+            } => match initializer {
+                Some(initializer) => self.visit_expr(initializer),
+                None => self.declare(pattern, *is_mutable),
+            },
+
+            StmtKind::FunctionDecl { .. } => self.function(stmt),
+
+            // fields are part of each instance, not locals, so only their values are walked
+            StmtKind::ClassDecl {
+                fields,
+                methods,
+                static_fields,
+                static_methods,
+                ..
+            } => {
+                for member in [fields, static_fields, methods, static_methods]
+                    .into_iter()
+                    .flatten()
+                {
+                    self.member(member);
+                }
+            }
+
+            StmtKind::MixinDecl { methods, .. } => {
+                for method in methods {
+                    self.member(method);
+                }
+            }
+
+            StmtKind::ModuleDecl { declarations, .. } => self.stmts(declarations),
+
+            StmtKind::InterfaceDecl { .. } | StmtKind::TypeAlias { .. } | StmtKind::Import(_) => {}
+
+            StmtKind::Assign { target, op, value } => {
+                self.visit_expr(value);
+
+                let Some(local) = self.local(target) else {
+                    return self.visit_expr(target);
+                };
+
+                // `x += 1` reads `x` before it writes it
+                if op.is_some() {
+                    self.event(Event::Read(local, &target.span));
+                }
+
+                self.event(Event::Assign(local, &target.span));
+            }
+
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.visit_expr(condition);
+
+                let start = self.current;
+                let after = self.block();
+
+                self.current = self.block();
+                self.edge(start, self.current);
+                self.stmts(then_branch);
+                self.edge(self.current, after);
+
+                match else_branch {
+                    Some(else_branch) => {
+                        self.current = self.block();
+                        self.edge(start, self.current);
+                        self.stmts(else_branch);
+                        self.edge(self.current, after);
+                    }
+
+                    None => self.edge(start, after),
+                }
+
+                self.current = after;
+            }
+
+            StmtKind::While { condition, body } => {
+                let head = self.block();
+                self.edge(self.current, head);
+                self.current = head;
+
+                self.visit_expr(condition);
+
+                let exit = self.block();
+
+                // `while true` only ends by breaking out of it
+                if !matches!(condition.kind, ExprKind::Literal(Literal::Boolean(true))) {
+                    self.edge(self.current, exit);
+                }
+
+                self.looped(head, exit, body);
+            }
+
+            StmtKind::For { iterable, body, .. } => {
+                self.visit_expr(iterable);
+
+                let head = self.block();
+                self.edge(self.current, head);
+
+                let exit = self.block();
+                self.edge(head, exit);
+
+                self.current = head;
+                self.looped(head, exit, body);
+            }
+
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+
+                self.terminate(&stmt.span);
+            }
+
+            StmtKind::Break | StmtKind::Continue => {
+                if let Some(&(head, exit)) = self.loops.last() {
+                    let target = match stmt.kind {
+                        StmtKind::Break => exit,
+                        _ => head,
+                    };
+
+                    self.edge(self.current, target);
+                }
+
+                self.terminate(&stmt.span);
+            }
+
+            StmtKind::Expression(expr) => self.visit_expr(expr),
+        }
+    }
+
+    // runs `body` from the end of a loop's `head`, going back to it afterwards
+    fn looped(&mut self, head: BlockId, exit: BlockId, body: &'a [Stmt]) {
+        let start = self.block();
+        self.edge(self.current, start);
+        self.current = start;
+
+        self.loops.push((head, exit));
+        self.stmts(body);
+        self.loops.pop();
+
+        self.edge(self.current, head);
+        self.current = exit;
+    }
+
+    fn member(&mut self, member: &'a Stmt) {
+        match &member.kind {
+            StmtKind::VariableDecl {
+                initializer: Some(initializer),
+                ..
+            } => {
+                self.nested(|builder| builder.visit_expr(initializer));
+            }
+
+            StmtKind::FunctionDecl { .. } => self.function(member),
+            _ => {}
+        }
+    }
+
+    // `a && b`, `a || b` and `a ?? b` only sometimes evaluate `b`
+    fn short_circuit(&mut self, left: &'a Expr, right: &'a Expr) {
+        self.visit_expr(left);
+
+        let start = self.current;
+        let after = self.block();
+        self.edge(start, after);
+
+        self.current = self.block();
+        self.edge(start, self.current);
+        self.visit_expr(right);
+        self.edge(self.current, after);
+
+        self.current = after;
+    }
+
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut stack = vec![ENTRY];
+
+        while let Some(block) = stack.pop() {
+            if std::mem::replace(&mut reachable[block], true) {
+                continue;
+            }
+
+            stack.extend(&self.blocks[block].successors);
+        }
+
+        reachable
+    }
+
+    /// Warns once for each stretch of statements that can't run: the first
+    /// statement of an unreachable block, unless control could only get there
+    /// through other unreachable statements.
+    fn unreachable_code(&mut self, reachable: &[bool]) {
+        let dead = |block: BlockId| !reachable[block] && self.blocks[block].first.is_some();
+
+        let mut covered = vec![false; self.blocks.len()];
+
+        for block in (0..self.blocks.len()).filter(|&block| dead(block)) {
+            let mut stack = self.blocks[block].successors.clone();
+
+            while let Some(next) = stack.pop() {
+                if !std::mem::replace(&mut covered[next], true) {
+                    stack.extend(&self.blocks[next].successors);
+                }
+            }
+        }
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            let Some(first) = block.first.filter(|_| dead(index) && !covered[index]) else {
+                continue;
+            };
+
+            let mut diagnostic = Diagnostic::warning("W003", "Unreachable code")
+                .with_label(first.clone(), "this statement is never run");
+
+            diagnostic = match block.after {
+                Some(after) => {
+                    diagnostic.with_label(after.clone(), "any code after this is skipped")
+                }
+                None => diagnostic
+                    .with_note("every path leading here returns, breaks or continues first"),
+            };
+
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Finds which locals are definitely assigned (on every path) and maybe
+    /// assigned (on some path) where each block starts, then replays the
+    /// events to report reads of unassigned locals and second assignments to
+    /// a `let`.
+    fn assignments(&mut self, reachable: &[bool]) {
+        if self.locals.is_empty() {
+            return;
+        }
+
+        let count = self.locals.len();
+        let mut predecessors = vec![vec![]; self.blocks.len()];
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            for &successor in &block.successors {
+                if reachable[index] {
+                    predecessors[successor].push(index);
+                }
+            }
+        }
+
+        // definitely assigned starts out at "everything" so loops can only take away from it
+        let mut outputs = vec![(vec![true; count], vec![false; count]); self.blocks.len()];
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for block in (0..self.blocks.len()).filter(|&block| reachable[block]) {
+                let state = self.transfer(block, self.input(block, &predecessors[block], &outputs));
+
+                if state != outputs[block] {
+                    outputs[block] = state;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut reported = vec![false; count];
+
+        for block in (0..self.blocks.len()).filter(|&block| reachable[block]) {
+            let (mut definitely, mut maybe) = self.input(block, &predecessors[block], &outputs);
+
+            for event in self.blocks[block].events.clone() {
+                match event {
+                    Event::Declare(local) => {
+                        definitely[local] = false;
+                        maybe[local] = false;
+                    }
+
+                    Event::Read(local, span) if !definitely[local] && !reported[local] => {
+                        reported[local] = true;
+                        self.unassigned_read(local, span);
+                    }
+
+                    Event::Read(..) => {}
+
+                    Event::Assign(local, span) => {
+                        if maybe[local] && !self.locals[local].is_mutable {
+                            self.second_assignment(local, span);
+                        }
+
+                        definitely[local] = true;
+                        maybe[local] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // a block starts with what holds at the end of every (or any) block before it
+    fn input(
+        &self,
+        block: BlockId,
+        predecessors: &[BlockId],
+        outputs: &[(Vec<bool>, Vec<bool>)],
+    ) -> (Vec<bool>, Vec<bool>) {
+        let count = self.locals.len();
+
+        if block == ENTRY {
+            return (vec![false; count], vec![false; count]);
+        }
+
+        let mut definitely = vec![true; count];
+        let mut maybe = vec![false; count];
+
+        for &predecessor in predecessors {
+            let (predecessor_definitely, predecessor_maybe) = &outputs[predecessor];
+
+            for local in 0..count {
+                definitely[local] &= predecessor_definitely[local];
+                maybe[local] |= predecessor_maybe[local];
+            }
+        }
+
+        (definitely, maybe)
+    }
+
+    fn transfer(&self, block: BlockId, state: (Vec<bool>, Vec<bool>)) -> (Vec<bool>, Vec<bool>) {
+        let (mut definitely, mut maybe) = state;
+
+        for event in &self.blocks[block].events {
+            match event {
+                Event::Declare(local) => {
+                    definitely[*local] = false;
+                    maybe[*local] = false;
+                }
+
+                Event::Assign(local, _) => {
+                    definitely[*local] = true;
+                    maybe[*local] = true;
+                }
+
+                Event::Read(..) => {}
+            }
+        }
+
+        (definitely, maybe)
+    }
+
+    fn unassigned_read(&mut self, local: usize, read: &Span) {
+        let Local { name, span, .. } = self.locals[local];
+
+        self.diagnostics.push(
+            Diagnostic::error(
+                "E401",
+                format!("`{}` is read before it is definitely assigned", name),
+            )
+            .with_label(read.clone(), "read here")
+            .with_label(span.clone(), "declared without a value here")
+            .with_note(format!(
+                "help: assign `{}` on every path before this, or give it an initial value",
+                name
+            )),
+        );
+    }
+
+    fn second_assignment(&mut self, local: usize, assignment: &Span) {
+        let Local { name, span, .. } = self.locals[local];
+
+        self.diagnostics.push(
+            Diagnostic::error(
+                "E100",
+                format!("Cannot assign twice to immutable binding `{}`", name),
+            )
+            .with_label(assignment.clone(), "assigned here")
+            .with_label(span.clone(), "declared with `let` here")
+            .with_note(format!(
+                "help: declare `{}` with `var` to make it mutable",
+                name
+            )),
+        );
+    }
+}
+
+impl<'a> Visitor<'a> for Builder<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        self.stmt(stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(_) => {
+                if let Some(local) = self.local(expr) {
+                    self.event(Event::Read(local, &expr.span));
+                }
+            }
+
+            ExprKind::Binary {
+                left,
+                op: BinaryOp::And | BinaryOp::Or | BinaryOp::Coalesce,
+                right,
+            } => self.short_circuit(left, right),
+
+            // a block expression runs in place, so it leaves loops and functions like any block
+            ExprKind::Block(body) => self.stmts(body),
+
+            ExprKind::Lambda { body, .. } => {
+                self.nested(|builder| match &body.kind {
+                    ExprKind::Block(body) => builder.stmts(body),
+                    _ => builder.visit_expr(body),
+                });
+            }
+
+            // exactly one arm runs
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+
+                let start = self.current;
+                let after = self.block();
+
+                for arm in arms {
+                    self.current = self.block();
+                    self.edge(start, self.current);
+                    self.visit_expr(&arm.body);
+                    self.edge(self.current, after);
+                }
+
+                self.current = after;
+            }
+
+            _ => walk_expr(self, expr),
+        }
+    }
+}
@@ -5,11 +5,15 @@ use crate::tokentype::TokenType;
 use std::iter::Peekable;
 use std::str::Chars;
 
+const OPERATOR_CHARS: [char; 12] = ['+', '-', '*', '/', '=', '<', '>', '!', '&', '^', '%', '|'];
+
 #[derive(Debug)]
 pub struct Lexer<'src> {
     input: Peekable<Chars<'src>>,
     current: Position,
     eof_emitted: bool,
+    luau_header: Option<usize>, // bracket depth between `luau` and its raw body
+    trivia: bool,               // emit whitespace and comments instead of skipping them
 }
 
 impl<'src> Lexer<'src> {
@@ -22,6 +26,17 @@ impl<'src> Lexer<'src> {
                 absolute: 0,
             },
             eof_emitted: false,
+            luau_header: None,
+            trivia: false,
+        }
+    }
+
+    /// A lexer that also yields whitespace and comment tokens, so that the
+    /// tokens cover the source without gaps.
+    pub fn with_trivia(source: &'src str) -> Self {
+        Self {
+            trivia: true,
+            ..Self::new(source)
         }
     }
 
@@ -46,6 +61,13 @@ impl<'src> Lexer<'src> {
         self.input.peek().copied()
     }
 
+    fn peek_next(&self) -> Option<char> {
+        let mut lookahead = self.input.clone();
+        lookahead.next();
+
+        lookahead.next()
+    }
+
     fn match_char(&mut self, expected: char) -> bool {
         if self.peek() == Some(expected) {
             self.advance();
@@ -77,14 +99,70 @@ impl<'src> Lexer<'src> {
         }
     }
 
-    fn skip_whitespace(&mut self) {
-        while let Some(c) = self.peek() {
-            if c.is_whitespace() {
-                self.advance();
+    fn starts_comment(&self) -> bool {
+        let mut lookahead = self.input.clone();
+
+        lookahead.next() == Some('/') && matches!(lookahead.next(), Some('/' | '*'))
+    }
+
+    // whitespace, a `// ...` line comment or a `/* ... */` block comment, if one starts here
+    fn read_trivia(&mut self) -> Option<Token> {
+        let start_pos = self.current;
+
+        let token_type = if self.peek().is_some_and(char::is_whitespace) {
+            self.read_sequence(char::is_whitespace);
+
+            TokenType::Whitespace
+        } else if self.starts_comment() {
+            let _ = self.advance();
+
+            if self.match_char('/') {
+                let comment = format!("//{}", self.read_sequence(|c| c != '\n'));
+
+                match comment.strip_prefix("///") {
+                    Some(text) if !text.starts_with('/') => {
+                        TokenType::DocComment(text.strip_prefix(' ').unwrap_or(text).to_string())
+                    }
+
+                    _ => TokenType::Comment(comment),
+                }
             } else {
-                break;
+                let _ = self.advance();
+                let mut comment = String::from("/*");
+
+                loop {
+                    let Some((c, _)) = self.advance() else {
+                        return Some(self.error_token(
+                            start_pos,
+                            ErrorKind::UnterminatedBlockComment,
+                            "Unterminated block comment",
+                        ));
+                    };
+
+                    comment.push(c);
+
+                    if comment.len() > 3 && comment.ends_with("*/") {
+                        break;
+                    }
+                }
+
+                match comment.strip_prefix("/**") {
+                    Some(body) if !body.starts_with(['*', '/']) => {
+                        TokenType::DocComment(block_doc(&body[..body.len() - 2]))
+                    }
+
+                    _ => TokenType::Comment(comment),
+                }
             }
-        }
+        } else {
+            return None;
+        };
+
+        Some(Token {
+            token_type,
+            span: Span::new(start_pos, self.current),
+            errors: vec![],
+        })
     }
 
     fn read_sequence<F>(&mut self, mut predicate: F) -> String
@@ -109,7 +187,9 @@ impl<'src> Lexer<'src> {
         let mut number = String::from(first_char);
         number.push_str(&self.read_sequence(|c| c.is_ascii_digit()));
 
-        if self.match_char('.') {
+        // `1..5` is a range, not a malformed decimal
+        if self.peek() == Some('.') && self.peek_next() != Some('.') {
+            self.advance();
             number.push('.');
 
             let fractional_part = self.read_sequence(|c| c.is_ascii_digit());
@@ -137,9 +217,11 @@ impl<'src> Lexer<'src> {
         let token_type = match identifier.as_str() {
             "true" => TokenType::Boolean(true),
             "false" => TokenType::Boolean(false),
+            "nil" => TokenType::Nil,
 
             "let" | "var" | "fn" | "rec" | "type" | "if" | "else" | "return" | "class"
-            | "static" | "interface" | "mixin" | "implements" | "includes" | "luau" => {
+            | "static" | "interface" | "mixin" | "implements" | "includes" | "luau" | "while"
+            | "for" | "in" | "break" | "continue" | "match" | "where" => {
                 TokenType::Keyword(identifier)
             }
 
@@ -157,7 +239,7 @@ impl<'src> Lexer<'src> {
         let mut operator = String::new();
 
         while let Some(next_char) = self.peek() {
-            if next_char.is_ascii_punctuation() {
+            if OPERATOR_CHARS.contains(&next_char) && !self.starts_comment() {
                 let (ch, _) = self.advance().unwrap();
                 operator.push(ch);
             } else {
@@ -166,8 +248,8 @@ impl<'src> Lexer<'src> {
         }
 
         let valid_operators = [
-            "==", "!=", "<=", ">=", "+=", "-=", "->", "&&", "||", "!", "<", ">", "+", "-", "*",
-            "/", "%", "&", "|", "^", "=",
+            "==", "!=", "<=", ">=", "+=", "-=", "->", "=>", "&&", "||", "!", "<", ">", "+", "-",
+            "*", "/", "%", "&", "|", "^", "=",
         ];
 
         if valid_operators.contains(&operator.as_str()) {
@@ -179,6 +261,7 @@ impl<'src> Lexer<'src> {
                 "+=" => TokenType::PlusEqual,
                 "-=" => TokenType::MinusEqual,
                 "->" => TokenType::Arrow,
+                "=>" => TokenType::FatArrow,
                 "&&" => TokenType::And,
                 "||" => TokenType::Or,
                 "!" => TokenType::Bang,
@@ -211,6 +294,153 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    fn read_dots(&mut self, start_pos: Position) -> Token {
+        let _ = self.advance();
+
+        let token_type = if self.match_char('.') {
+            if self.match_char('=') {
+                TokenType::DotDotEqual
+            } else {
+                TokenType::DotDot
+            }
+        } else {
+            TokenType::Dot
+        };
+
+        Token {
+            token_type,
+            span: Span::new(start_pos, self.current),
+            errors: vec![],
+        }
+    }
+
+    fn read_question(&mut self, start_pos: Position) -> Token {
+        let _ = self.advance();
+
+        let token_type = if self.match_char('.') {
+            TokenType::QuestionDot
+        } else if self.match_char('?') {
+            TokenType::QuestionQuestion
+        } else {
+            TokenType::Question
+        };
+
+        Token {
+            token_type,
+            span: Span::new(start_pos, self.current),
+            errors: vec![],
+        }
+    }
+
+    /// Captures the body of a `luau { ... }` block verbatim. Braces inside Luau
+    /// strings and comments don't count towards the closing `}`.
+    fn read_luau_block(&mut self, start_pos: Position) -> Token {
+        let _ = self.advance();
+
+        let mut body = String::new();
+        let mut depth = 1;
+
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        let _ = self.advance();
+
+                        return Token {
+                            token_type: TokenType::LuauBlock(body),
+                            span: Span::new(start_pos, self.current),
+                            errors: vec![],
+                        };
+                    }
+                }
+
+                '"' | '\'' | '`' => {
+                    self.copy_luau_string(c, &mut body);
+                    continue;
+                }
+
+                '[' if self.copy_long_bracket(&mut body) => continue,
+
+                '-' if self.peek_next() == Some('-') => {
+                    body.push_str(&self.read_sequence(|c| c == '-'));
+
+                    if !self.copy_long_bracket(&mut body) {
+                        body.push_str(&self.read_sequence(|c| c != '\n'));
+                    }
+
+                    continue;
+                }
+
+                _ => {}
+            }
+
+            let (c, _) = self.advance().unwrap();
+            body.push(c);
+        }
+
+        self.error_token(
+            start_pos,
+            ErrorKind::UnterminatedLuauBlock,
+            "Unterminated luau block",
+        )
+    }
+
+    fn copy_luau_string(&mut self, quote: char, body: &mut String) {
+        let (c, _) = self.advance().unwrap();
+        body.push(c);
+
+        while let Some((c, _)) = self.advance() {
+            body.push(c);
+
+            if c == '\\' {
+                if let Some((escaped, _)) = self.advance() {
+                    body.push(escaped);
+                }
+            } else if c == quote || c == '\n' {
+                break;
+            }
+        }
+    }
+
+    // copies a `[[ ... ]]` or `[==[ ... ]==]` long string or comment, if one starts here
+    fn copy_long_bracket(&mut self, body: &mut String) -> bool {
+        let mut lookahead = self.input.clone();
+
+        if lookahead.next() != Some('[') {
+            return false;
+        }
+
+        let mut level = 0;
+        while lookahead.peek() == Some(&'=') {
+            lookahead.next();
+            level += 1;
+        }
+
+        if lookahead.next() != Some('[') {
+            return false;
+        }
+
+        let close = format!("]{}]", "=".repeat(level));
+
+        for _ in 0..level + 2 {
+            let (c, _) = self.advance().unwrap();
+            body.push(c);
+        }
+
+        while let Some((c, _)) = self.advance() {
+            body.push(c);
+
+            if body.ends_with(&close) {
+                break;
+            }
+        }
+
+        true
+    }
+
     fn read_string(&mut self, start_pos: Position) -> Token {
         let mut string = String::new();
         let mut errors = Vec::new();
@@ -272,15 +502,61 @@ impl<'src> Lexer<'src> {
     }
 }
 
+impl Lexer<'_> {
+    // a `{` ends the header of a `luau<T>(a, b)` block unless it's nested inside the type or bindings
+    fn track_luau_header(&mut self, token_type: &TokenType) {
+        self.luau_header = match (self.luau_header, token_type) {
+            (_, TokenType::Keyword(keyword)) if keyword == "luau" => Some(0),
+            (None, _) => None,
+
+            (Some(depth), TokenType::Less | TokenType::LeftParen) => Some(depth + 1),
+            (Some(depth), TokenType::Greater | TokenType::RightParen) => {
+                Some(depth.saturating_sub(1))
+            }
+
+            (Some(_), TokenType::LuauBlock(_) | TokenType::Semicolon | TokenType::Error(_)) => None,
+
+            (header, _) => header,
+        };
+    }
+}
+
+// the lines of a `/** */` body, without their leading `*` or the blank lines around them
+fn block_doc(body: &str) -> String {
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => String::new(),
+    }
+}
+
 impl Iterator for Lexer<'_> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(_) = self.peek() {
-            self.skip_whitespace();
+        while let Some(trivia) = self.read_trivia() {
+            if self.trivia
+                || !trivia.errors.is_empty()
+                || matches!(trivia.token_type, TokenType::DocComment(_))
+            {
+                return Some(trivia);
+            }
+        }
 
+        if let Some(first_char) = self.peek() {
             let start_pos = self.current;
-            let first_char = self.peek()?;
 
             let token = match first_char {
                 '0'..='9' => {
@@ -304,12 +580,13 @@ impl Iterator for Lexer<'_> {
                 ')' => self.single_char_token(start_pos, TokenType::RightParen),
                 '[' => self.single_char_token(start_pos, TokenType::LeftBracket),
                 ']' => self.single_char_token(start_pos, TokenType::RightBracket),
+                '{' if self.luau_header == Some(0) => self.read_luau_block(start_pos),
                 '{' => self.single_char_token(start_pos, TokenType::LeftBrace),
                 '}' => self.single_char_token(start_pos, TokenType::RightBrace),
                 ',' => self.single_char_token(start_pos, TokenType::Comma),
-                '.' => self.single_char_token(start_pos, TokenType::Dot),
-
-                c if c.is_whitespace() => return self.next(),
+                '@' => self.single_char_token(start_pos, TokenType::At),
+                '.' => self.read_dots(start_pos),
+                '?' => self.read_question(start_pos),
 
                 _ => {
                     let _ = self.advance()?;
@@ -327,6 +604,8 @@ impl Iterator for Lexer<'_> {
                 }
             };
 
+            self.track_luau_header(&token.token_type);
+
             Some(token)
         } else if !self.eof_emitted {
             self.eof_emitted = true;
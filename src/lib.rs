@@ -1,5 +1,21 @@
+pub mod ast;
+pub mod checker;
+pub mod codegen;
+pub mod cst;
+pub mod diagnostic;
+#[cfg(feature = "serde")]
+pub mod dump;
+pub mod exhaustiveness;
+pub mod flow;
 pub mod lexer;
+pub mod mutability;
+pub mod node;
+pub mod parser;
 pub mod position;
+pub mod printer;
+pub mod resolver;
 pub mod span;
+pub mod testing;
 pub mod token;
 pub mod tokentype;
+pub mod visit;
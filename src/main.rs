@@ -1,16 +1,93 @@
-use crate::lexer::Lexer;
 use ariadne::{Color, Label, Report, ReportKind, Source};
-use parser::{Parser, ParserToken};
+use exx::codegen::Codegen;
+use exx::diagnostic::{Diagnostic, Severity};
+use exx::lexer::Lexer;
+use exx::node::Tables;
+use exx::parser::{Parser, ParserToken};
+use exx::{checker, flow, mutability, resolver};
 
-mod ast;
-mod lexer;
-mod parser;
-mod position;
-mod span;
-mod token;
-mod tokentype;
+fn report(diagnostic: &Diagnostic, source: &str) {
+    let kind = match diagnostic.severity {
+        Severity::Error => ReportKind::Error,
+        Severity::Warning => ReportKind::Warning,
+    };
+
+    let color = match diagnostic.severity {
+        Severity::Error => Color::Fixed(81),
+        Severity::Warning => Color::Yellow,
+    };
+
+    let primary = diagnostic
+        .labels
+        .first()
+        .map(|label| label.span.start.absolute..label.span.end.absolute)
+        .unwrap_or(0..0);
+
+    let mut report = Report::build(kind, ("anonymous", primary))
+        .with_code(&diagnostic.code)
+        .with_message(&diagnostic.message);
+
+    for label in &diagnostic.labels {
+        report = report.with_label(
+            Label::new((
+                "anonymous",
+                label.span.start.absolute..label.span.end.absolute,
+            ))
+            .with_message(&label.message)
+            .with_color(color),
+        );
+    }
+
+    for note in &diagnostic.notes {
+        report = report.with_note(note);
+    }
+
+    report
+        .finish()
+        .print(("anonymous", Source::from(source)))
+        .unwrap();
+}
+
+// `exx --dump-tokens <file>` and `exx --dump-ast <file>` print JSON instead of compiling
+#[cfg(feature = "serde")]
+fn dump(args: &[String]) -> bool {
+    use exx::dump;
+
+    let [mode, path] = args else {
+        return false;
+    };
+
+    if mode != "--dump-tokens" && mode != "--dump-ast" {
+        return false;
+    }
+
+    let source = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("error: cannot read `{}`: {}", path, err);
+        std::process::exit(1);
+    });
+
+    let tokens: Vec<_> = Lexer::new(&source).collect();
+
+    let json = if mode == "--dump-tokens" {
+        dump::tokens(&tokens)
+    } else {
+        let parser_tokens: Vec<ParserToken> = tokens.into_iter().map(|t| t.into()).collect();
+        let program = Parser::new(parser_tokens).parse_program();
+
+        dump::ast(program.as_deref())
+    };
+
+    println!("{}", json.expect("tokens and ASTs always serialize"));
+
+    true
+}
 
 fn main() {
+    #[cfg(feature = "serde")]
+    if dump(&std::env::args().skip(1).collect::<Vec<_>>()) {
+        return;
+    }
+
     let source = r#"let apply: ((number) -> number, number) -> number =
     (f, x) -> f(x);"#;
     let lexer = Lexer::new(source);
@@ -51,12 +128,36 @@ fn main() {
 
     let mut parser = Parser::new(parser_tokens);
     match parser.parse_program() {
-        Ok(expr) => {
-            dbg!(expr);
+        Ok(program) => {
+            let mut tables = Tables::new(&program);
+            let mut diagnostics = resolver::resolve(&program, &mut tables);
+            diagnostics.extend(mutability::check(&program));
+            diagnostics.extend(flow::check(&program, &tables));
+            diagnostics.extend(checker::check_with(&program, &mut tables));
+
+            for diagnostic in &diagnostics {
+                report(diagnostic, source);
+            }
+
+            if !diagnostics.iter().any(Diagnostic::is_error) {
+                let codegen = Codegen::new().with_types(&tables.types);
+                println!("{}", codegen.generate(&program));
+            }
         }
 
         Err(err) => {
-            println!("{}", err.message);
+            let range = err.span.start.absolute..err.span.end.absolute;
+
+            Report::build(ReportKind::Error, ("anonymous", range.clone()))
+                .with_message(&err.message)
+                .with_label(
+                    Label::new(("anonymous", range))
+                        .with_message(&err.message)
+                        .with_color(error_color),
+                )
+                .finish()
+                .print(("anonymous", Source::from(source)))
+                .unwrap();
         }
     }
 }
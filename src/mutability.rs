@@ -0,0 +1,312 @@
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::span::Span;
+use crate::visit::{Visitor, walk_expr};
+use std::collections::HashMap;
+
+/// Rejects assignments to `let` bindings, parameters and loop variables, and
+/// mutation of fields reached through a `let` binding.
+pub fn check(program: &[Stmt]) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        scopes: vec![HashMap::new()],
+        loop_depth: 0,
+        diagnostics: vec![],
+    };
+
+    checker.stmts(program);
+
+    checker.diagnostics
+}
+
+struct Binding {
+    kind: BindingKind,
+    span: Span,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BindingKind {
+    Let,
+    DeferredLet, // a `let` without a value, which `flow` checks is assigned only once
+    Var,
+    Parameter,
+    LoopVariable,
+    Item, // a function, class, module or other declaration
+}
+
+struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    loop_depth: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    // functions start with a fresh loop depth, since loops never reach into their bodies
+    fn function(&mut self, params: &[Parameter], f: impl FnOnce(&mut Self)) {
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+
+        self.scoped(|this| {
+            for param in params {
+                this.declare(&param.pattern, BindingKind::Parameter);
+            }
+
+            f(this);
+        });
+
+        self.loop_depth = loop_depth;
+    }
+
+    fn declare(&mut self, pattern: &Pattern, kind: BindingKind) {
+        let scope = self.scopes.last_mut().unwrap();
+
+        for (name, span) in pattern.identifiers() {
+            scope.insert(
+                name.to_string(),
+                Binding {
+                    kind,
+                    span: span.clone(),
+                },
+            );
+        }
+    }
+
+    fn declare_name(&mut self, name: &str, span: &Span) {
+        self.scopes.last_mut().unwrap().insert(
+            name.to_string(),
+            Binding {
+                kind: BindingKind::Item,
+                span: span.clone(),
+            },
+        );
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::VariableDecl {
+                is_mutable,
+                pattern,
+                initializer,
+                ..
+            } => {
+                if let Some(initializer) = initializer {
+                    self.visit_expr(initializer);
+                }
+
+                let kind = if *is_mutable {
+                    BindingKind::Var
+                } else if initializer.is_none() {
+                    BindingKind::DeferredLet
+                } else {
+                    BindingKind::Let
+                };
+
+                self.declare(pattern, kind);
+            }
+
+            StmtKind::FunctionDecl {
+                name, params, body, ..
+            } => {
+                self.declare_name(name, &stmt.span);
+                self.function(params, |this| this.stmts(body));
+            }
+
+            StmtKind::ClassDecl { name, methods, .. }
+            | StmtKind::MixinDecl { name, methods, .. } => {
+                self.declare_name(name, &stmt.span);
+                self.scoped(|this| this.stmts(methods));
+            }
+
+            StmtKind::InterfaceDecl { name, .. } => self.declare_name(name, &stmt.span),
+
+            StmtKind::ModuleDecl {
+                name, declarations, ..
+            } => {
+                self.declare_name(name, &stmt.span);
+                self.scoped(|this| this.stmts(declarations));
+            }
+
+            StmtKind::Import(name) => self.declare_name(name, &stmt.span),
+            StmtKind::TypeAlias { .. } => {}
+
+            StmtKind::Assign { target, value, .. } => {
+                self.visit_expr(value);
+                self.assignment(target);
+            }
+
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.visit_expr(condition);
+                self.scoped(|this| this.stmts(then_branch));
+
+                if let Some(else_branch) = else_branch {
+                    self.scoped(|this| this.stmts(else_branch));
+                }
+            }
+
+            StmtKind::While { condition, body } => {
+                self.visit_expr(condition);
+
+                self.loop_depth += 1;
+                self.scoped(|this| this.stmts(body));
+                self.loop_depth -= 1;
+            }
+
+            StmtKind::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                self.visit_expr(iterable);
+
+                self.loop_depth += 1;
+                self.scoped(|this| {
+                    this.declare(pattern, BindingKind::LoopVariable);
+                    this.stmts(body);
+                });
+                self.loop_depth -= 1;
+            }
+
+            StmtKind::Expression(expr) | StmtKind::Return(Some(expr)) => self.visit_expr(expr),
+            StmtKind::Return(None) | StmtKind::Break | StmtKind::Continue => {}
+        }
+    }
+
+    fn assignment(&mut self, target: &Expr) {
+        let ExprKind::Identifier(name) = &target.kind else {
+            self.visit_expr(target);
+            self.field_mutation(target);
+            return;
+        };
+
+        let Some(binding) = self.lookup(name) else {
+            return;
+        };
+
+        let diagnostic = match binding.kind {
+            BindingKind::Var | BindingKind::DeferredLet => return,
+
+            BindingKind::LoopVariable => {
+                Diagnostic::error("E101", format!("Cannot reassign loop variable `{}`", name))
+                    .with_label(target.span.clone(), "assigned here")
+                    .with_label(binding.span.clone(), "bound by the loop here")
+            }
+
+            BindingKind::Let if self.loop_depth > 0 => Diagnostic::error(
+                "E100",
+                format!("Cannot mutate immutable binding `{}` inside a loop", name),
+            )
+            .with_label(target.span.clone(), "assigned here")
+            .with_label(binding.span.clone(), "declared with `let` here")
+            .with_note(format!(
+                "help: declare `{}` with `var` to make it mutable",
+                name
+            )),
+
+            BindingKind::Let => Diagnostic::error(
+                "E100",
+                format!("Cannot assign twice to immutable binding `{}`", name),
+            )
+            .with_label(target.span.clone(), "assigned here")
+            .with_label(binding.span.clone(), "declared with `let` here")
+            .with_note(format!(
+                "help: declare `{}` with `var` to make it mutable",
+                name
+            )),
+
+            BindingKind::Parameter => {
+                Diagnostic::error("E100", format!("Cannot assign to parameter `{}`", name))
+                    .with_label(target.span.clone(), "assigned here")
+                    .with_label(binding.span.clone(), "bound as a parameter here")
+                    .with_note(format!(
+                        "help: copy it into a mutable binding first, as in `var {0} = {0};`",
+                        name
+                    ))
+            }
+
+            BindingKind::Item => {
+                Diagnostic::error("E100", format!("Cannot assign to declaration `{}`", name))
+                    .with_label(target.span.clone(), "assigned here")
+                    .with_label(binding.span.clone(), "declared here")
+            }
+        };
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    // `a.b.c = value` mutates whatever `a` is bound to, so `a` can't be a `let`
+    fn field_mutation(&mut self, target: &Expr) {
+        let mut root = target;
+
+        while let ExprKind::PropertyAccess { object, .. } = &root.kind {
+            root = object;
+        }
+
+        let ExprKind::Identifier(name) = &root.kind else {
+            return;
+        };
+
+        let ExprKind::PropertyAccess { name: field, .. } = &target.kind else {
+            return;
+        };
+
+        let Some(binding) = self.lookup(name) else {
+            return;
+        };
+
+        if !matches!(binding.kind, BindingKind::Let | BindingKind::DeferredLet) {
+            return;
+        }
+
+        self.diagnostics.push(
+            Diagnostic::error(
+                "E102",
+                format!(
+                    "Cannot mutate field `{}` through immutable binding `{}`",
+                    field, name
+                ),
+            )
+            .with_label(target.span.clone(), "mutated here")
+            .with_label(binding.span.clone(), "declared with `let` here")
+            .with_note(format!(
+                "help: declare `{}` with `var` to make it mutable",
+                name
+            )),
+        );
+    }
+}
+
+impl Visitor<'_> for Checker {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.stmt(stmt);
+    }
+
+    // only blocks and lambdas need handling; everything else just walks its children
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Block(body) => self.scoped(|this| this.stmts(body)),
+            ExprKind::Lambda { params, body } => {
+                self.function(params, |this| this.visit_expr(body))
+            }
+
+            _ => walk_expr(self, expr),
+        }
+    }
+}
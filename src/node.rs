@@ -0,0 +1,425 @@
+//! Node identity, and the side tables the passes over a program share.
+//!
+//! Nodes stay in the owned tree from `ast`, with `Box`ed children, and a
+//! `NodeId` on each `Stmt`, `Expr` and `Pattern` is what tables key on. They
+//! aren't moved into an arena: the parser, every pass, the printer and the
+//! folds in `visit` all walk that tree directly, so an arena would mean
+//! rewriting all of them at once for no change in behaviour. Names in the
+//! tree are interned `Ident`s, which copy and compare by pointer. The
+//! `Interner` is used by the resolver, whose scopes are keyed by `Symbol`.
+
+use crate::ast::*;
+use crate::checker::Ty;
+use crate::span::Span;
+use crate::visit::{
+    Visitor, VisitorMut, walk_expr, walk_expr_mut, walk_pattern, walk_pattern_mut, walk_stmt,
+    walk_stmt_mut,
+};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::{Mutex, OnceLock};
+
+/// Identifies one `Stmt`, `Expr` or `Pattern` within a parsed program.
+///
+/// Ids are handed out in pre-order from zero by `number`, so they double as
+/// indexes into a `NodeMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    /// The id of a node that hasn't been numbered yet, such as one built by a `Fold`.
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Identifies a definition (a binding, function, class, ...) that resolved
+/// names point at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DefId(pub u32);
+
+/// What a `DefId` points at.
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+    Interface,
+    Mixin,
+    Module,
+    Import,
+    TypeAlias,
+    Variant, // a constructor of an algebraic data type
+    Member,  // a field or method, visible by name inside its class or mixin
+}
+
+impl DefKind {
+    /// Whether the name can be used as a value, as opposed to only as a type.
+    pub fn is_value(self) -> bool {
+        !matches!(self, DefKind::Interface | DefKind::TypeAlias)
+    }
+
+    /// Whether the name can be used as a type.
+    pub fn is_type(self) -> bool {
+        matches!(
+            self,
+            DefKind::Class | DefKind::Interface | DefKind::TypeAlias
+        )
+    }
+}
+
+/// A side table from `NodeId`s to facts about those nodes. Ids are dense, so
+/// this is a `Vec` rather than a `HashMap`.
+#[derive(Debug, Clone)]
+pub struct NodeMap<T> {
+    entries: Vec<Option<T>>,
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `value` for `id`. Dummy ids are never stored.
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        if id == NodeId::DUMMY {
+            return None;
+        }
+
+        let index = id.index();
+
+        if index >= self.entries.len() {
+            self.entries.resize_with(index + 1, || None);
+        }
+
+        self.entries[index].replace(value)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.entries.get(id.index())?.as_ref()
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((NodeId(index as u32), value.as_ref()?)))
+    }
+}
+
+/// An interned identifier. Two symbols are equal exactly when their names are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+#[derive(Debug, Default)]
+pub struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    names: Vec<Rc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+
+        let symbol = Symbol(self.names.len() as u32);
+        let name: Rc<str> = name.into();
+
+        self.names.push(name.clone());
+        self.symbols.insert(name, symbol);
+
+        symbol
+    }
+
+    /// The symbol for `name`, if it has been interned.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.0 as usize]
+    }
+}
+
+/// A name in the AST. Names are interned for the whole process, so an `Ident`
+/// is a copyable pointer and two of them compare without looking at their
+/// text. It otherwise behaves like the `str` it holds.
+#[derive(Clone, Copy)]
+pub struct Ident(&'static str);
+
+impl Ident {
+    pub fn new(name: &str) -> Self {
+        static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+        let mut names = NAMES.get_or_init(Mutex::default).lock().unwrap();
+
+        match names.get(name) {
+            Some(&interned) => Ident(interned),
+
+            None => {
+                let interned: &'static str = Box::leak(name.into());
+                names.insert(interned);
+
+                Ident(interned)
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Ident {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Ident {}
+
+impl PartialEq<str> for Ident {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Ident {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Ident {
+    fn eq(&self, other: &String) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<Ident> for str {
+    fn eq(&self, other: &Ident) -> bool {
+        self == other.0
+    }
+}
+
+impl PartialEq<Ident> for &str {
+    fn eq(&self, other: &Ident) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialEq<Ident> for String {
+    fn eq(&self, other: &Ident) -> bool {
+        self == other.0
+    }
+}
+
+// by name, so that maps keyed by `Ident` can be looked up by `&str` too
+impl Hash for Ident {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialOrd for Ident {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ident {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(other.0)
+    }
+}
+
+impl Deref for Ident {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl Borrow<str> for Ident {
+    fn borrow(&self) -> &str {
+        self.0
+    }
+}
+
+impl AsRef<str> for Ident {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl From<&str> for Ident {
+    fn from(name: &str) -> Self {
+        Ident::new(name)
+    }
+}
+
+impl From<String> for Ident {
+    fn from(name: String) -> Self {
+        Ident::new(&name)
+    }
+}
+
+impl From<Ident> for String {
+    fn from(ident: Ident) -> Self {
+        ident.0.to_string()
+    }
+}
+
+impl fmt::Debug for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0, f)
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ident {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ident {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Ident::from)
+    }
+}
+
+/// Everything the passes over a program learn about its nodes, shared between
+/// them: the resolver fills in `defs`, the checker `types`, and tools like the
+/// language server read all of them back by `NodeId`.
+#[derive(Debug, Default)]
+pub struct Tables {
+    pub spans: NodeMap<Span>,
+    pub types: NodeMap<Ty>,
+    pub defs: NodeMap<DefId>,
+    pub definitions: Vec<Definition>, // indexed by `DefId`
+    pub symbols: Interner,            // the names the resolver has seen
+}
+
+impl Tables {
+    /// Fresh tables for a numbered program, with every node's span filled in.
+    pub fn new(program: &[Stmt]) -> Self {
+        let mut spans = Spans(NodeMap::new());
+
+        for stmt in program {
+            spans.visit_stmt(stmt);
+        }
+
+        Self {
+            spans: spans.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn definition(&self, def: DefId) -> &Definition {
+        &self.definitions[def.0 as usize]
+    }
+
+    /// The definition an identifier (or binding pattern) resolved to.
+    pub fn resolution(&self, id: NodeId) -> Option<&Definition> {
+        self.defs.get(id).map(|&def| self.definition(def))
+    }
+}
+
+/// Gives every node in `program` a fresh id, in pre-order, and returns how
+/// many were handed out. The parser numbers everything it produces, so this
+/// is only needed after a pass builds new nodes.
+pub fn number(program: &mut [Stmt]) -> u32 {
+    let mut numbering = Numbering(0);
+
+    for stmt in program {
+        numbering.visit_stmt(stmt);
+    }
+
+    numbering.0
+}
+
+struct Numbering(u32);
+
+impl Numbering {
+    fn next(&mut self) -> NodeId {
+        self.0 += 1;
+
+        NodeId(self.0 - 1)
+    }
+}
+
+impl VisitorMut for Numbering {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.id = self.next();
+        walk_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        expr.id = self.next();
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        pattern.id = self.next();
+        walk_pattern_mut(self, pattern);
+    }
+}
+
+struct Spans(NodeMap<Span>);
+
+impl Visitor<'_> for Spans {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.0.insert(stmt.id, stmt.span.clone());
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.0.insert(expr.id, expr.span.clone());
+        walk_expr(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        self.0.insert(pattern.id, pattern.span.clone());
+        walk_pattern(self, pattern);
+    }
+}
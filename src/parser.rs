@@ -1,4 +1,8 @@
 use crate::ast::*;
+use crate::cst::{Sink, SyntaxKind};
+use crate::node::{self, Ident};
+use crate::position::Position;
+use crate::span::Span;
 use std::fmt;
 
 #[allow(dead_code)]
@@ -9,22 +13,44 @@ pub enum TokenKind {
     Fn,
     Rec,
     Class,
+    Interface,
+    Implements,
+    Mixin,
+    Includes,
+    Static,
+    Luau,
     Type,
     Module,
     Import,
     Return,
     If,
     Else,
+    While,
+    For,
+    In,
+    Break,
+    Continue,
+    Match,
+    Where,
 
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
+    At,
     Colon,
     Semicolon,
-    Arrow, // ->
+    Arrow,    // ->
+    FatArrow, // =>
     Dot,
+    DotDot,      // ..
+    DotDotEqual, // ..=
+    Question,
+    QuestionDot,      // ?.
+    QuestionQuestion, // ??
 
     Plus,
     Minus,
@@ -39,13 +65,19 @@ pub enum TokenKind {
     GreaterEqual,
     And,
     Or,
+    Pipe,
     Not,
     Equal,
+    PlusEqual,
+    MinusEqual,
 
     Identifier(String),
     Number(String),
     String(String),
     Boolean(bool),
+    Nil,
+    LuauBlock(String),
+    DocComment(String),
 
     Eof,
 }
@@ -53,6 +85,7 @@ pub enum TokenKind {
 #[derive(Debug, Clone)]
 pub struct ParserToken {
     pub kind: TokenKind,
+    pub span: Span,
 }
 
 impl fmt::Display for ParserToken {
@@ -69,29 +102,160 @@ impl fmt::Display for ParserToken {
 pub struct Parser {
     tokens: Vec<ParserToken>,
     current: usize,
+    loop_depth: usize,
+    // set while parsing `if`/`while`/`for` headers, where `{` starts the body
+    no_struct_literal: bool,
+    sink: Option<Sink>, // builds the syntax tree, when one was asked for
+    errors: Vec<ParseError>,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParseError {
     pub message: String,
+    pub span: Span,
+}
+
+// a function's name, type parameters, parameters and return type
+type Signature = (
+    Ident,
+    Vec<Ident>,
+    Vec<TypeBound>,
+    Vec<Parameter>,
+    Option<Type>,
+);
+
+// decides whether a function takes a `self` receiver
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionKind {
+    Function,
+    Instance,
+    Static,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<ParserToken>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            loop_depth: 0,
+            no_struct_literal: false,
+            sink: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Also builds a syntax tree while parsing, out of the tokens `sink` was
+    /// made from. Those must be `tokens` with their trivia put back.
+    pub(crate) fn with_sink(mut self, sink: Sink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub(crate) fn into_sink(self) -> Option<Sink> {
+        self.sink
     }
 
     pub fn parse_program(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let (program, mut errors) = self.parse_recovering();
+
+        if errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(errors.swap_remove(0))
+        }
+    }
+
+    /// Parses every declaration it can. One that fails is skipped, and parsing
+    /// carries on after it, so this returns what did parse along with every
+    /// error, in the order they were found.
+    pub fn parse_recovering(&mut self) -> (Vec<Stmt>, Vec<ParseError>) {
         let mut declarations = Vec::new();
 
         while !self.is_at_end() {
-            declarations.push(self.declaration()?);
+            let start = self.current;
+
+            match self.declaration() {
+                Ok(declaration) => declarations.push(declaration),
+                Err(error) => self.recover(error, start, false),
+            }
         }
 
-        Ok(declarations)
+        node::number(&mut declarations);
+
+        (declarations, std::mem::take(&mut self.errors))
+    }
+
+    /// Records `error` and skips ahead to where the declaration that began at
+    /// token `start` can be taken to end: past a `;`, past the `}` closing a
+    /// block it opened, or before a keyword that starts a new declaration.
+    /// Inside a block, a `}` closing the block also stops it. What the failed
+    /// declaration and the skipped tokens leave behind becomes an `Error` node.
+    fn recover(&mut self, error: ParseError, start: usize, nested: bool) {
+        self.errors.push(error);
+
+        let mut depth = self.tokens[start..self.current]
+            .iter()
+            .fold(0usize, |depth, token| match token.kind {
+                TokenKind::LeftBrace => depth + 1,
+                TokenKind::RightBrace => depth.saturating_sub(1),
+
+                _ => depth,
+            });
+
+        while !self.is_at_end() {
+            match self.peek().kind {
+                TokenKind::Semicolon if depth == 0 => {
+                    self.advance();
+                    break;
+                }
+
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace if depth == 0 && nested => break,
+                TokenKind::RightBrace if depth == 1 => {
+                    self.advance();
+                    break;
+                }
+
+                TokenKind::RightBrace => depth = depth.saturating_sub(1),
+
+                ref kind if depth == 0 && self.current > start && starts_declaration(kind) => {
+                    break;
+                }
+
+                _ => {}
+            }
+
+            self.advance();
+        }
+
+        if self.current > start {
+            self.node(SyntaxKind::Error, self.tokens[start].span.start);
+        }
     }
 
     fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        if self.check(&TokenKind::DocComment(String::new())) {
+            let start = self.peek().span.start;
+            let docs = self.doc_comments();
+            let docs_span = self.span_from(start);
+            let declaration = self.declaration()?;
+
+            return document(docs, docs_span, declaration);
+        }
+
+        if self.check(&TokenKind::At) {
+            let attributes = self.attributes()?;
+
+            if self.check(&TokenKind::DocComment(String::new())) {
+                return Err(self.error("Doc comments must come before attributes"));
+            }
+
+            let declaration = self.declaration()?;
+
+            return attach(attributes, declaration);
+        }
+
         if self.match_token(&[TokenKind::Import]) {
             return self.import_declaration();
         }
@@ -104,11 +268,24 @@ impl Parser {
             return self.class_declaration();
         }
 
+        if self.match_token(&[TokenKind::Interface]) {
+            return self.interface_declaration();
+        }
+
+        if self.match_token(&[TokenKind::Mixin]) {
+            return self.mixin_declaration();
+        }
+
+        if self.match_token(&[TokenKind::Type]) {
+            return self.type_alias();
+        }
+
         if self.match_token(&[TokenKind::Fn]) {
-            return self.function_declaration();
+            return self.function_declaration(FunctionKind::Function);
         }
 
         if self.match_token(&[TokenKind::Return]) {
+            let start = self.previous().span.start;
             let expr = if !self.check(&TokenKind::Semicolon) {
                 Some(self.expression()?)
             } else {
@@ -117,17 +294,34 @@ impl Parser {
 
             self.consume(TokenKind::Semicolon, "Expected ';' after return statement")?;
 
-            return Ok(Stmt::Return(expr));
+            return Ok(self.stmt(StmtKind::Return(expr), self.span_from(start)));
         }
 
         if self.match_token(&[TokenKind::Let, TokenKind::Var]) {
             return self.variable_declaration();
         }
 
+        if self.match_token(&[TokenKind::If]) {
+            return self.if_statement();
+        }
+
+        if self.match_token(&[TokenKind::While]) {
+            return self.while_statement();
+        }
+
+        if self.match_token(&[TokenKind::For]) {
+            return self.for_statement();
+        }
+
+        if self.match_token(&[TokenKind::Break, TokenKind::Continue]) {
+            return self.loop_control();
+        }
+
         self.statement()
     }
 
     fn import_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
         let module_name = self.consume_identifier("Expected module name after 'import'")?;
 
         self.consume(
@@ -135,78 +329,387 @@ impl Parser {
             "Expected ';' after import declaration",
         )?;
 
-        Ok(Stmt::Import(module_name))
+        Ok(self.stmt(StmtKind::Import(module_name), self.span_from(start)))
     }
 
     fn module_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
         let name = self.consume_identifier("Expected module name")?;
         self.consume(TokenKind::LeftBrace, "Expected '{' after module name")?;
 
-        let mut declarations = Vec::new();
-        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-            declarations.push(self.declaration()?);
-        }
+        let declarations = self.block_body("Expected '}' after module body")?;
 
-        self.consume(TokenKind::RightBrace, "Expected '}' after module body")?;
-
-        Ok(Stmt::ModuleDecl { name, declarations })
+        Ok(self.stmt(
+            StmtKind::ModuleDecl {
+                docs: None,
+                name,
+                declarations,
+            },
+            self.span_from(start),
+        ))
     }
 
     fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
         let name = self.consume_identifier("Expected class name")?;
+
+        let mut implements = Vec::new();
+        let mut includes = Vec::new();
+
+        // `implements` and `includes` may each appear once, in either order
+        while self.match_token(&[TokenKind::Implements, TokenKind::Includes]) {
+            let (names, what) = match self.previous().kind {
+                TokenKind::Implements => (&mut implements, "implements"),
+                _ => (&mut includes, "includes"),
+            };
+
+            if !names.is_empty() {
+                return Err(self.error(format!("Duplicate '{}' clause", what)));
+            }
+
+            *names = self.name_list(what)?;
+        }
+
         self.consume(TokenKind::LeftBrace, "Expected '{' after class name")?;
 
         let mut fields = Vec::new();
         let mut methods = Vec::new();
+        let mut static_fields = Vec::new();
+        let mut static_methods = Vec::new();
 
         while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let docs = self.doc_comments();
+            let attributes = self.attributes()?;
+            let is_static = self.match_token(&[TokenKind::Static]);
+
             if self.match_token(&[TokenKind::Fn]) {
-                methods.push(self.function_declaration()?);
+                if is_static {
+                    let method = self.function_declaration(FunctionKind::Static)?;
+                    static_methods.push(with_docs(docs, attach(attributes, method)?));
+                } else {
+                    let method = self.function_declaration(FunctionKind::Instance)?;
+                    methods.push(with_docs(docs, attach(attributes, method)?));
+                }
             } else if self.match_token(&[TokenKind::Let, TokenKind::Var]) {
-                fields.push(self.variable_declaration()?);
+                let field = with_docs(docs, attach(attributes, self.variable_declaration()?)?);
+
+                if is_static {
+                    static_fields.push(field);
+                } else {
+                    fields.push(field);
+                }
             } else {
+                return Err(self.error("Expected field declaration starting with 'let' or 'var', or a method declaration starting with 'fn'."));
+            }
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after class body")?;
+
+        Ok(self.stmt(
+            StmtKind::ClassDecl {
+                docs: None,
+                attributes: Vec::new(),
+                name,
+                implements,
+                includes,
+                fields,
+                methods,
+                static_fields,
+                static_methods,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn mixin_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let name = self.consume_identifier("Expected mixin name")?;
+        self.consume(TokenKind::LeftBrace, "Expected '{' after mixin name")?;
+
+        let mut methods = Vec::new();
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let docs = self.doc_comments();
+            let attributes = self.attributes()?;
+
+            self.consume(
+                TokenKind::Fn,
+                "Expected method declaration starting with 'fn' in mixin body",
+            )?;
+
+            let method = self.function_declaration(FunctionKind::Instance)?;
+            methods.push(with_docs(docs, attach(attributes, method)?));
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after mixin body")?;
+
+        Ok(self.stmt(
+            StmtKind::MixinDecl {
+                docs: None,
+                name,
+                methods,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    /// Joins consecutive doc comments into one Markdown string.
+    fn doc_comments(&mut self) -> Option<String> {
+        let mut lines = Vec::new();
+
+        while self.match_token(&[TokenKind::DocComment(String::new())]) {
+            if let TokenKind::DocComment(text) = &self.previous().kind {
+                lines.push(text.clone());
+            }
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Parses any `@name` or `@name(args)` attributes before a declaration.
+    fn attributes(&mut self) -> Result<Vec<Attribute>, ParseError> {
+        let mut attributes: Vec<Attribute> = Vec::new();
+
+        while self.match_token(&[TokenKind::At]) {
+            let start = self.previous().span.start;
+            let name = self.consume_identifier("Expected attribute name after '@'")?;
+
+            let mut note = None;
+            if self.match_token(&[TokenKind::LeftParen]) {
+                if name != "deprecated" {
+                    return Err(self.error(format!("`@{}` takes no arguments", name)));
+                }
+
+                if self.match_token(&[TokenKind::String(String::new())])
+                    && let TokenKind::String(text) = &self.previous().kind
+                {
+                    note = Some(text.clone());
+                }
+
+                self.consume(
+                    TokenKind::RightParen,
+                    "Expected ')' after attribute arguments",
+                )?;
+            }
+
+            let kind = match name.as_str() {
+                "inline" => AttributeKind::Inline,
+                "deprecated" => AttributeKind::Deprecated(note),
+                "native" => AttributeKind::Native,
+                "test" => AttributeKind::Test,
+
+                _ => {
+                    return Err(ParseError {
+                        message: format!("Unknown attribute `@{}`", name),
+                        span: self.span_from(start),
+                    });
+                }
+            };
+
+            let span = self.span_from(start);
+
+            if attributes
+                .iter()
+                .any(|attribute| attribute.kind.name() == kind.name())
+            {
                 return Err(ParseError {
-                    message: "Expected field declaration starting with 'let' or 'var', or a method declaration starting with 'fn'.".into(),
+                    message: format!("Duplicate attribute `@{}`", name),
+                    span,
                 });
             }
+
+            self.node(SyntaxKind::Attribute, start);
+            attributes.push(Attribute { kind, span });
         }
 
-        self.consume(TokenKind::RightBrace, "Expected '}' after class body")?;
+        Ok(attributes)
+    }
 
-        Ok(Stmt::ClassDecl {
-            name,
-            fields,
-            methods,
-        })
+    fn name_list(&mut self, clause: &str) -> Result<Vec<Ident>, ParseError> {
+        let mut names = Vec::new();
+
+        loop {
+            names.push(self.consume_identifier(&format!("Expected name after '{}'", clause))?);
+
+            if !self.match_token(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+
+        Ok(names)
     }
 
-    fn function_declaration(&mut self) -> Result<Stmt, ParseError> {
-        let name = self.consume_identifier("Expected function name")?;
-        self.consume(TokenKind::LeftParen, "Expected '(' after function name")?;
+    fn interface_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let name = self.consume_identifier("Expected interface name")?;
+        self.consume(TokenKind::LeftBrace, "Expected '{' after interface name")?;
+
+        let mut methods = Vec::new();
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let docs = self.doc_comments();
+
+            self.consume(
+                TokenKind::Fn,
+                "Expected method signature starting with 'fn' in interface body",
+            )?;
+
+            let method_start = self.previous().span.start;
+            let name_span = self.peek().span.clone();
+            let (name, type_params, bounds, params, return_type) = self.function_signature()?;
+            check_receiver(&name, name_span, &params, FunctionKind::Instance)?;
+
+            self.consume(
+                TokenKind::Semicolon,
+                "Expected ';' after interface method signature",
+            )?;
+
+            self.node(SyntaxKind::MethodSignature, method_start);
+            methods.push(MethodSignature {
+                docs,
+                name,
+                type_params,
+                bounds,
+                params,
+                return_type,
+                span: self.span_from(method_start),
+            });
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after interface body")?;
+
+        Ok(self.stmt(
+            StmtKind::InterfaceDecl {
+                docs: None,
+                name,
+                methods,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn type_alias(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let name = self.consume_identifier("Expected type name after 'type'")?;
+        let params = self.type_parameters()?;
+
+        self.consume(TokenKind::Equal, "Expected '=' after type name")?;
+        let ty = self.parse_type()?;
+        self.consume(TokenKind::Semicolon, "Expected ';' after type alias")?;
+
+        Ok(self.stmt(
+            StmtKind::TypeAlias {
+                docs: None,
+                name,
+                params,
+                ty,
+            },
+            self.span_from(start),
+        ))
+    }
 
+    // an optional `<T, U>` after a type or function name
+    fn type_parameters(&mut self) -> Result<Vec<Ident>, ParseError> {
         let mut params = Vec::new();
 
-        if !self.check(&TokenKind::RightParen) {
+        if self.match_token(&[TokenKind::Less]) {
             loop {
-                let param_name = self.consume_identifier("Expected parameter name")?;
-                let type_annotation = if self.match_token(&[TokenKind::Colon]) {
-                    Some(self.parse_type()?)
-                } else {
-                    None
-                };
+                params.push(self.consume_identifier("Expected type parameter name")?);
 
-                params.push(Parameter {
-                    name: param_name,
-                    type_annotation,
-                });
+                if !self.match_token(&[TokenKind::Comma]) {
+                    break;
+                }
+            }
+
+            self.consume(TokenKind::Greater, "Expected '>' after type parameters")?;
+        }
+
+        Ok(params)
+    }
+
+    // `<T: Show + Eq, U>`, with the bounds split out from the names
+    fn bounded_type_parameters(&mut self) -> Result<(Vec<Ident>, Vec<TypeBound>), ParseError> {
+        let mut params = Vec::new();
+        let mut bounds = Vec::new();
+
+        if self.match_token(&[TokenKind::Less]) {
+            loop {
+                let start = self.peek().span.start;
+                let param = self.consume_identifier("Expected type parameter name")?;
+
+                if self.match_token(&[TokenKind::Colon]) {
+                    bounds.push(self.type_bound(param, start)?);
+                }
+
+                params.push(param);
 
                 if !self.match_token(&[TokenKind::Comma]) {
                     break;
                 }
             }
+
+            self.consume(TokenKind::Greater, "Expected '>' after type parameters")?;
         }
 
-        self.consume(TokenKind::RightParen, "Expected ')' after parameters")?;
+        Ok((params, bounds))
+    }
+
+    // the `Show + Eq` after `T:`
+    fn type_bound(&mut self, param: Ident, start: Position) -> Result<TypeBound, ParseError> {
+        let mut constraints = Vec::new();
+
+        loop {
+            constraints.push(self.consume_identifier("Expected constraint name")?);
+
+            if !self.match_token(&[TokenKind::Plus]) {
+                break;
+            }
+        }
+
+        Ok(TypeBound {
+            param,
+            constraints,
+            span: self.span_from(start),
+        })
+    }
+
+    // `where T: Show, U: Eq + Ord`, after a function's return type
+    fn where_clause(&mut self, type_params: &[Ident]) -> Result<Vec<TypeBound>, ParseError> {
+        let mut bounds = Vec::new();
+
+        if !self.match_token(&[TokenKind::Where]) {
+            return Ok(bounds);
+        }
+
+        loop {
+            let start = self.peek().span.start;
+            let param_span = self.peek().span.clone();
+            let param = self.consume_identifier("Expected type parameter name after 'where'")?;
+
+            if !type_params.contains(&param) {
+                return Err(ParseError {
+                    message: format!("Cannot find type parameter `{}`", param),
+                    span: param_span,
+                });
+            }
+
+            self.consume(TokenKind::Colon, "Expected ':' after type parameter")?;
+            bounds.push(self.type_bound(param, start)?);
+
+            if !self.match_token(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+
+        Ok(bounds)
+    }
+
+    fn function_signature(&mut self) -> Result<Signature, ParseError> {
+        let name = self.consume_identifier("Expected function name")?;
+        let (type_params, mut bounds) = self.bounded_type_parameters()?;
+        self.consume(TokenKind::LeftParen, "Expected '(' after function name")?;
+
+        let params = self.parse_parameters()?;
 
         let return_type = if self.match_token(&[TokenKind::Arrow]) {
             Some(self.parse_type()?)
@@ -214,24 +717,41 @@ impl Parser {
             None
         };
 
-        self.consume(TokenKind::LeftBrace, "Expected '{' before function body")?;
-        let mut body = Vec::new();
+        bounds.extend(self.where_clause(&type_params)?);
 
-        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-            body.push(self.declaration()?);
-        }
+        Ok((name, type_params, bounds, params, return_type))
+    }
 
-        self.consume(TokenKind::RightBrace, "Expected '}' after function body")?;
+    fn function_declaration(&mut self, kind: FunctionKind) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let name_span = self.peek().span.clone();
+        let (name, type_params, bounds, params, return_type) = self.function_signature()?;
+        check_receiver(&name, name_span, &params, kind)?;
 
-        Ok(Stmt::FunctionDecl {
-            name,
-            params,
-            return_type,
-            body,
-        })
+        self.consume(TokenKind::LeftBrace, "Expected '{' before function body")?;
+
+        // `break` and `continue` never cross a function boundary
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let body = self.block_body("Expected '}' after function body");
+        self.loop_depth = loop_depth;
+
+        Ok(self.stmt(
+            StmtKind::FunctionDecl {
+                docs: None,
+                attributes: Vec::new(),
+                name,
+                type_params,
+                bounds,
+                params,
+                return_type,
+                body: body?,
+            },
+            self.span_from(start),
+        ))
     }
 
     fn variable_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
         let keyword = self.previous_token_kind();
         let is_mutable = match keyword {
             TokenKind::Let => false,
@@ -240,7 +760,7 @@ impl Parser {
             _ => false,
         };
 
-        let name = self.consume_identifier("Expected variable name")?;
+        let pattern = self.parse_binding_pattern("variable")?;
         let type_annotation = if self.match_token(&[TokenKind::Colon]) {
             Some(self.parse_type()?)
         } else {
@@ -253,45 +773,305 @@ impl Parser {
             None
         };
 
+        if initializer.is_none() && !matches!(pattern.kind, PatternKind::Identifier(_)) {
+            return Err(ParseError {
+                message: "Destructuring declarations require an initializer".into(),
+                span: pattern.span.clone(),
+            });
+        }
+
         self.consume(
             TokenKind::Semicolon,
             "Expected ';' after variable declaration",
         )?;
 
-        Ok(Stmt::VariableDecl {
-            is_mutable,
-            name,
-            type_annotation,
-            initializer,
-        })
+        Ok(self.stmt(
+            StmtKind::VariableDecl {
+                docs: None,
+                attributes: Vec::new(),
+                is_mutable,
+                pattern,
+                type_annotation,
+                initializer,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let condition = self.condition()?;
+
+        self.consume(TokenKind::LeftBrace, "Expected '{' after if condition")?;
+        let then_branch = self.block_body("Expected '}' after if body")?;
+
+        let else_branch = if self.match_token(&[TokenKind::Else]) {
+            if self.match_token(&[TokenKind::If]) {
+                Some(vec![self.if_statement()?])
+            } else {
+                self.consume(TokenKind::LeftBrace, "Expected '{' after 'else'")?;
+
+                Some(self.block_body("Expected '}' after else body")?)
+            }
+        } else {
+            None
+        };
+
+        Ok(self.stmt(
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let condition = self.condition()?;
+
+        self.consume(TokenKind::LeftBrace, "Expected '{' after while condition")?;
+        let body = self.loop_body("Expected '}' after while body")?;
+
+        Ok(self.stmt(StmtKind::While { condition, body }, self.span_from(start)))
+    }
+
+    fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span.start;
+        let pattern = self.parse_binding_pattern("loop variable")?;
+
+        self.consume(TokenKind::In, "Expected 'in' after loop variable")?;
+        let iterable = self.iterable()?;
+
+        if matches!(iterable.kind, ExprKind::Range { .. })
+            && !matches!(
+                pattern.kind,
+                PatternKind::Identifier(_) | PatternKind::Wildcard
+            )
+        {
+            return Err(ParseError {
+                message: "Range loops bind a single number and cannot be destructured".into(),
+                span: pattern.span.clone(),
+            });
+        }
+
+        self.consume(TokenKind::LeftBrace, "Expected '{' after for loop header")?;
+        let body = self.loop_body("Expected '}' after for body")?;
+
+        Ok(self.stmt(
+            StmtKind::For {
+                pattern,
+                iterable,
+                body,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn loop_control(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous().clone();
+        let (kind, name) = match keyword.kind {
+            TokenKind::Break => (StmtKind::Break, "break"),
+            TokenKind::Continue => (StmtKind::Continue, "continue"),
+
+            _ => unreachable!(),
+        };
+
+        if self.loop_depth == 0 {
+            return Err(ParseError {
+                message: format!("`{}` outside of a loop", name),
+                span: keyword.span,
+            });
+        }
+
+        self.consume(
+            TokenKind::Semicolon,
+            &format!("Expected ';' after '{}'", name),
+        )?;
+
+        Ok(self.stmt(kind, self.span_from(keyword.span.start)))
+    }
+
+    fn block_body(&mut self, message: &str) -> Result<Vec<Stmt>, ParseError> {
+        let mut body = Vec::new();
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let start = self.current;
+
+            match self.declaration() {
+                Ok(stmt) => body.push(stmt),
+                Err(error) => self.recover(error, start, true),
+            }
+        }
+
+        self.consume(TokenKind::RightBrace, message)?;
+
+        Ok(body)
+    }
+
+    fn loop_body(&mut self, message: &str) -> Result<Vec<Stmt>, ParseError> {
+        self.loop_depth += 1;
+        let body = self.block_body(message);
+        self.loop_depth -= 1;
+
+        body
+    }
+
+    // an expression directly followed by a `{ ... }` body
+    fn condition(&mut self) -> Result<Expr, ParseError> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, true);
+        let condition = self.expression();
+        self.no_struct_literal = no_struct_literal;
+
+        condition
     }
 
     fn statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.peek().span.start;
         let expr = self.expression()?;
 
-        self.consume(TokenKind::Semicolon, "Expected ';' after expression")?;
+        if self.match_token(&[
+            TokenKind::Equal,
+            TokenKind::PlusEqual,
+            TokenKind::MinusEqual,
+        ]) {
+            let op = match self.previous_token_kind() {
+                TokenKind::Equal => None,
+                TokenKind::PlusEqual => Some(BinaryOp::Add),
+                TokenKind::MinusEqual => Some(BinaryOp::Sub),
+
+                _ => unreachable!(),
+            };
+
+            if !matches!(
+                expr.kind,
+                ExprKind::Identifier(_) | ExprKind::PropertyAccess { .. }
+            ) {
+                return Err(ParseError {
+                    message: "Invalid assignment target".into(),
+                    span: expr.span,
+                });
+            }
+
+            let value = self.expression()?;
+            self.consume(TokenKind::Semicolon, "Expected ';' after assignment")?;
+
+            return Ok(self.stmt(
+                StmtKind::Assign {
+                    target: expr,
+                    op,
+                    value,
+                },
+                self.span_from(start),
+            ));
+        }
+
+        // a statement-level `luau { ... }` or `match` reads like a block, so its `;` is optional
+        if matches!(expr.kind, ExprKind::Luau { .. } | ExprKind::Match { .. }) {
+            self.match_token(&[TokenKind::Semicolon]);
+        } else {
+            self.consume(TokenKind::Semicolon, "Expected ';' after expression")?;
+        }
 
-        Ok(Stmt::Expression(expr))
+        Ok(self.stmt(StmtKind::Expression(expr), self.span_from(start)))
     }
 
+    // `?` binds tighter than `|`, so `A | B?` is `A | (B?)`
     fn parse_type(&mut self) -> Result<Type, ParseError> {
+        let start = self.peek().span.start;
+        let ty = self.optional_type()?;
+
+        if !self.check(&TokenKind::Pipe) {
+            return Ok(ty);
+        }
+
+        let mut members = vec![ty];
+
+        while self.match_token(&[TokenKind::Pipe]) {
+            members.push(self.optional_type()?);
+        }
+
+        Ok(self.finish_type(start, Type::Union(members)))
+    }
+
+    fn optional_type(&mut self) -> Result<Type, ParseError> {
+        let start = self.peek().span.start;
+        let ty = self.type_atom()?;
+
+        if self.match_token(&[TokenKind::Question]) {
+            return Ok(self.finish_type(start, Type::Optional(Box::new(ty))));
+        }
+
+        Ok(ty)
+    }
+
+    fn type_atom(&mut self) -> Result<Type, ParseError> {
+        let start = self.peek().span.start;
+        let ty = self.type_atom_kind()?;
+
+        Ok(self.finish_type(start, ty))
+    }
+
+    fn finish_type(&mut self, start: Position, ty: Type) -> Type {
+        self.node(SyntaxKind::Type, start);
+
+        ty
+    }
+
+    fn type_atom_kind(&mut self) -> Result<Type, ParseError> {
+        if self.match_token(&[TokenKind::LeftBrace]) {
+            return self.record_type();
+        }
+
+        if self.match_token(&[TokenKind::LeftBracket]) {
+            let element = self.parse_type()?;
+            self.consume(
+                TokenKind::RightBracket,
+                "Expected ']' after array element type",
+            )?;
+
+            return Ok(Type::Array(Box::new(element)));
+        }
+
+        if matches!(
+            self.peek().kind,
+            TokenKind::String(_)
+                | TokenKind::Number(_)
+                | TokenKind::Boolean(_)
+                | TokenKind::Nil
+                | TokenKind::Minus
+        ) {
+            return Ok(Type::Literal(self.literal("Expected type")?));
+        }
+
         if self.match_token(&[TokenKind::LeftParen]) {
             let mut params = Vec::new();
 
-            loop {
-                params.push(self.parse_type()?);
+            if !self.check(&TokenKind::RightParen) {
+                loop {
+                    params.push(self.parse_type()?);
 
-                if !self.match_token(&[TokenKind::Comma]) {
-                    break;
+                    if !self.match_token(&[TokenKind::Comma]) {
+                        break;
+                    }
                 }
             }
 
-            self.consume(TokenKind::RightParen, "Expected ')' in function type")?;
-            self.consume(TokenKind::Arrow, "Expected '->' in function type")?;
+            self.consume(TokenKind::RightParen, "Expected ')' in type")?;
 
-            let return_type = Box::new(self.parse_type()?);
+            if self.match_token(&[TokenKind::Arrow]) {
+                let return_type = Box::new(self.parse_type()?);
 
-            Ok(Type::Function(params, return_type))
+                return Ok(Type::Function(params, return_type));
+            }
+
+            match params.len() {
+                0 => Err(self.error("Expected '->' after '()' in function type")),
+                1 => Ok(params.pop().unwrap()),
+
+                _ => Ok(Type::Tuple(params)),
+            }
         } else {
             let name = self.consume_identifier("Expected type name")?;
 
@@ -309,26 +1089,175 @@ impl Parser {
                 self.consume(TokenKind::Greater, "Expected '>' in generic type")?;
 
                 Ok(Type::Generic { name, params })
+            } else if self.match_token(&[TokenKind::LeftParen]) {
+                let mut fields = Vec::new();
+
+                if !self.check(&TokenKind::RightParen) {
+                    loop {
+                        fields.push(self.parse_type()?);
+
+                        if !self.match_token(&[TokenKind::Comma]) {
+                            break;
+                        }
+                    }
+                }
+
+                self.consume(TokenKind::RightParen, "Expected ')' after variant fields")?;
+
+                Ok(Type::Variant { name, fields })
             } else {
                 Ok(Type::Simple(name))
             }
         }
     }
 
-    fn parse_lambda(&mut self) -> Result<Expr, ParseError> {
+    /// Parses the rest of a `{ readonly name: T, age?: number, [K]: V }` table
+    /// type. Entries are separated by `,` or `;`, as in Luau.
+    fn record_type(&mut self) -> Result<Type, ParseError> {
+        let mut fields: Vec<FieldType> = Vec::new();
+        let mut indexer = None;
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let entry_start = self.peek().span.start;
+
+            if self.match_token(&[TokenKind::LeftBracket]) {
+                let key = self.parse_type()?;
+                self.consume(TokenKind::RightBracket, "Expected ']' after index type")?;
+                self.consume(TokenKind::Colon, "Expected ':' after index signature")?;
+                let value = self.parse_type()?;
+
+                if indexer.is_some() {
+                    return Err(ParseError {
+                        message: "Record type can only have one index signature".into(),
+                        span: self.span_from(entry_start),
+                    });
+                }
+
+                indexer = Some(Box::new((key, value)));
+            } else {
+                // `readonly` is only a modifier when a field name follows it
+                let readonly = matches!(&self.peek().kind, TokenKind::Identifier(name) if name == "readonly")
+                    && matches!(
+                        self.tokens.get(self.current + 1).map(|token| &token.kind),
+                        Some(TokenKind::Identifier(_))
+                    );
+
+                if readonly {
+                    self.advance();
+                }
+
+                let name = self.consume_identifier("Expected field name in record type")?;
+                let optional = self.match_token(&[TokenKind::Question]);
+                self.consume(
+                    TokenKind::Colon,
+                    "Expected ':' after field name in record type",
+                )?;
+                let ty = self.parse_type()?;
+
+                if fields.iter().any(|field| field.name == name) {
+                    return Err(ParseError {
+                        message: format!("Duplicate field `{}` in record type", name),
+                        span: self.span_from(entry_start),
+                    });
+                }
+
+                fields.push(FieldType {
+                    name,
+                    ty,
+                    optional,
+                    readonly,
+                });
+            }
+
+            if !self.match_token(&[TokenKind::Comma, TokenKind::Semicolon]) {
+                break;
+            }
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after record type")?;
+
+        Ok(Type::Record { fields, indexer })
+    }
+
+    /// Parses the rest of `luau<T>(a, b) { ... }`; the type and bindings are optional.
+    fn luau_block(&mut self) -> Result<ExprKind, ParseError> {
+        let return_type = if self.match_token(&[TokenKind::Less]) {
+            let ty = self.parse_type()?;
+            self.consume(TokenKind::Greater, "Expected '>' after luau block type")?;
+
+            Some(ty)
+        } else {
+            None
+        };
+
+        let mut bindings = Vec::new();
+
+        if self.match_token(&[TokenKind::LeftParen]) {
+            while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+                let span = self.peek().span.clone();
+                let name =
+                    self.consume_identifier("Expected name of a local to pass into luau block")?;
+
+                bindings.push((name, span));
+
+                if !self.match_token(&[TokenKind::Comma]) {
+                    break;
+                }
+            }
+
+            self.consume(
+                TokenKind::RightParen,
+                "Expected ')' after luau block bindings",
+            )?;
+        }
+
+        if !self.check(&TokenKind::LuauBlock(String::new())) {
+            return Err(self.error("Expected '{' to start luau block"));
+        }
+
+        let token = self.advance().clone();
+        let TokenKind::LuauBlock(body) = token.kind else {
+            unreachable!()
+        };
+
+        Ok(ExprKind::Luau {
+            return_type,
+            bindings,
+            body,
+            body_span: token.span,
+        })
+    }
+
+    fn parse_lambda(&mut self) -> Result<ExprKind, ParseError> {
         let params = self.parse_parameters()?;
 
         self.consume(TokenKind::Arrow, "Expected '->' after lambda parameters")?;
 
-        let body = self.expression()?;
-        Ok(Expr::Lambda {
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let body = self.expression();
+        self.loop_depth = loop_depth;
+
+        Ok(ExprKind::Lambda {
             params,
-            body: Box::new(body),
+            body: Box::new(body?),
         })
     }
 
     fn expression(&mut self) -> Result<Expr, ParseError> {
-        self.logical_or()
+        self.coalesce()
+    }
+
+    // `a ?? b ?? c` groups to the right, so the first non-nil operand wins
+    fn coalesce(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.logical_or()?;
+
+        if self.match_token(&[TokenKind::QuestionQuestion]) {
+            let right = self.coalesce()?;
+
+            return Ok(self.binary(expr, BinaryOp::Coalesce, right));
+        }
+
+        Ok(expr)
     }
 
     fn logical_or(&mut self) -> Result<Expr, ParseError> {
@@ -338,11 +1267,7 @@ impl Parser {
             let op = BinaryOp::Or;
             let right = self.logical_and()?;
 
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                op,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -354,11 +1279,7 @@ impl Parser {
         while self.match_token(&[TokenKind::And]) {
             let op = BinaryOp::And;
             let right = self.equality()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                op,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -375,11 +1296,7 @@ impl Parser {
             };
 
             let right = self.comparison()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                op,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -404,16 +1321,34 @@ impl Parser {
             };
 
             let right = self.addition()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                op,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
     }
 
+    // ranges only make sense as the iterable of a `for` loop
+    fn iterable(&mut self) -> Result<Expr, ParseError> {
+        let start = self.condition()?;
+
+        if self.match_token(&[TokenKind::DotDot, TokenKind::DotDotEqual]) {
+            let inclusive = self.previous_token_kind() == TokenKind::DotDotEqual;
+            let end = self.condition()?;
+            let span = Span::new(start.span.start, end.span.end);
+
+            return Ok(self.expr(
+                ExprKind::Range {
+                    start: Box::new(start),
+                    end: Box::new(end),
+                    inclusive,
+                },
+                span,
+            ));
+        }
+
+        Ok(start)
+    }
+
     fn addition(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.multiplication()?;
 
@@ -426,11 +1361,7 @@ impl Parser {
             };
 
             let right = self.multiplication()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                op,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -448,17 +1379,15 @@ impl Parser {
             };
 
             let right = self.unary()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                op,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
     }
 
     fn unary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.peek().span.start;
+
         if self.match_token(&[TokenKind::Not, TokenKind::Minus]) {
             let op = match self.previous_token_kind() {
                 TokenKind::Not => UnaryOp::Not,
@@ -469,100 +1398,91 @@ impl Parser {
 
             let expr = self.unary()?;
 
-            return Ok(Expr::Unary {
-                op,
-                expr: Box::new(expr),
-            });
+            return Ok(self.expr(
+                ExprKind::Unary {
+                    op,
+                    expr: Box::new(expr),
+                },
+                self.span_from(start),
+            ));
         }
 
         self.primary()
     }
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = if self.match_token(&[TokenKind::Number(String::new())]) {
+        let start = self.peek().span.start;
+
+        let kind = if self.match_token(&[TokenKind::Luau]) {
+            self.luau_block()?
+        } else if self.match_token(&[TokenKind::Number(String::new())]) {
             let token = self.previous().clone();
             if let TokenKind::Number(n) = token.kind {
-                let num = n.parse::<f64>().map_err(|_| ParseError {
-                    message: format!("Invalid number literal: {}", n),
-                })?;
-                Expr::Literal(Literal::Number(num))
+                let num = n
+                    .parse::<f64>()
+                    .map_err(|_| self.error(format!("Invalid number literal: {}", n)))?;
+                ExprKind::Literal(Literal::Number(num))
             } else {
-                return Err(ParseError {
-                    message: "Expected number literal".into(),
-                });
+                return Err(self.error("Expected number literal"));
             }
         } else if self.match_token(&[TokenKind::String(String::new())]) {
             let token = self.previous().clone();
             if let TokenKind::String(s) = token.kind {
-                Expr::Literal(Literal::String(s))
+                ExprKind::Literal(Literal::String(s))
             } else {
-                return Err(ParseError {
-                    message: "Expected string literal".into(),
-                });
+                return Err(self.error("Expected string literal"));
             }
         } else if self.match_token(&[TokenKind::Boolean(true)]) {
-            Expr::Literal(Literal::Boolean(true))
+            ExprKind::Literal(Literal::Boolean(true))
         } else if self.match_token(&[TokenKind::Boolean(false)]) {
-            Expr::Literal(Literal::Boolean(false))
+            ExprKind::Literal(Literal::Boolean(false))
+        } else if self.match_token(&[TokenKind::Nil]) {
+            ExprKind::Literal(Literal::Nil)
         } else if self.match_token(&[TokenKind::Identifier(String::new())]) {
             let token = self.previous().clone();
 
             if let TokenKind::Identifier(name) = token.kind {
-                if self.check(&TokenKind::LeftBrace) {
-                    self.advance();
-
-                    let mut fields = Vec::new();
+                let name = Ident::from(name);
 
-                    while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-                        let field_name =
-                            self.consume_identifier("Expected field name in struct literal")?;
+                if !self.no_struct_literal && self.check(&TokenKind::LeftBrace) {
+                    self.advance();
 
-                        fields.push((field_name.clone(), Expr::Identifier(field_name)));
+                    let fields = self.record_fields("struct literal")?;
 
-                        if !self.match_token(&[TokenKind::Comma]) {
-                            break;
-                        }
-                    }
-                    self.consume(TokenKind::RightBrace, "Expected '}' after struct literal")?;
-                    return Ok(Expr::StructLiteral { name, fields });
+                    ExprKind::StructLiteral { name, fields }
                 } else {
-                    return Ok(Expr::Identifier(name));
+                    ExprKind::Identifier(name)
                 }
             } else {
-                return Err(ParseError {
-                    message: "Expected identifier".into(),
-                });
+                return Err(self.error("Expected identifier"));
             }
         } else if self.match_token(&[TokenKind::LeftParen]) {
-            if self.lambda_check() {
-                self.parse_lambda()?
-            } else {
-                let expr = self.expression()?;
-                self.consume(TokenKind::RightParen, "Expected ')' after expression")?;
+            let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+            let kind = self.parenthesized();
+            self.no_struct_literal = no_struct_literal;
 
-                Expr::Grouping(Box::new(expr))
-            }
+            kind?
+        } else if self.match_token(&[TokenKind::Match]) {
+            self.match_expression()?
         } else if self.match_token(&[TokenKind::LeftBrace]) {
-            let mut body = Vec::new();
-
-            while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-                body.push(self.declaration()?);
+            if self.record_check() {
+                ExprKind::Record(self.record_fields("record")?)
+            } else {
+                ExprKind::Block(self.block_body("Expected '}' after block")?)
             }
-
-            self.consume(TokenKind::RightBrace, "Expected '}' after block")?;
-
-            Expr::Block(body)
         } else {
-            return Err(ParseError {
-                message: format!("Unexpected token: {:?}", self.peek().kind),
-            });
+            return Err(self.error(format!("Unexpected token: {:?}", self.peek().kind)));
         };
 
+        let mut expr = self.expr(kind, self.span_from(start));
+
         loop {
             if self.match_token(&[TokenKind::LeftParen]) {
                 expr = self.finish_call(expr)?;
             } else if self.match_token(&[TokenKind::Dot]) {
                 expr = self.finish_property_access(expr)?;
+            } else if self.match_token(&[TokenKind::QuestionDot]) {
+                expr = self.finish_optional_access(expr)?;
             } else {
                 break;
             }
@@ -571,11 +1491,99 @@ impl Parser {
         Ok(expr)
     }
 
+    // `match value { pattern => result, ... }` after its `match`
+    fn match_expression(&mut self) -> Result<ExprKind, ParseError> {
+        let scrutinee = self.condition()?;
+        self.consume(TokenKind::LeftBrace, "Expected '{' after match value")?;
+
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let arms = self.match_arms();
+        self.no_struct_literal = no_struct_literal;
+
+        Ok(ExprKind::Match {
+            scrutinee: Box::new(scrutinee),
+            arms: arms?,
+        })
+    }
+
+    fn match_arms(&mut self) -> Result<Vec<MatchArm>, ParseError> {
+        let mut arms = Vec::new();
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let start = self.peek().span.start;
+            let pattern = self.parse_pattern()?;
+            self.consume(TokenKind::FatArrow, "Expected '=>' after match pattern")?;
+            let body = self.expression()?;
+
+            // like a block statement, an arm with a block body needs no `,`
+            let block = matches!(body.kind, ExprKind::Block(_));
+
+            self.node(SyntaxKind::MatchArm, start);
+            arms.push(MatchArm {
+                pattern,
+                body,
+                span: self.span_from(start),
+            });
+
+            if !self.match_token(&[TokenKind::Comma]) && !block {
+                break;
+            }
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after match arms")?;
+
+        Ok(arms)
+    }
+
+    // a lambda, tuple or grouping after its opening '('
+    fn parenthesized(&mut self) -> Result<ExprKind, ParseError> {
+        if self.lambda_check() {
+            return self.parse_lambda();
+        }
+
+        let expr = self.expression()?;
+
+        if self.match_token(&[TokenKind::Comma]) {
+            let mut items = vec![expr];
+
+            loop {
+                items.push(self.expression()?);
+
+                if !self.match_token(&[TokenKind::Comma]) {
+                    break;
+                }
+            }
+
+            self.consume(TokenKind::RightParen, "Expected ')' after tuple")?;
+
+            Ok(ExprKind::Tuple(items))
+        } else {
+            self.consume(TokenKind::RightParen, "Expected ')' after expression")?;
+
+            Ok(ExprKind::Grouping(Box::new(expr)))
+        }
+    }
+
     fn peek(&self) -> &ParserToken {
         &self.tokens[self.current]
     }
 
     fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let arguments = self.arguments();
+        self.no_struct_literal = no_struct_literal;
+
+        let span = self.span_from(callee.span.start);
+        Ok(self.expr(
+            ExprKind::Call {
+                callee: Box::new(callee),
+                arguments: arguments?,
+            },
+            span,
+        ))
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>, ParseError> {
         let mut arguments = Vec::new();
         if !self.check(&TokenKind::RightParen) {
             loop {
@@ -586,33 +1594,58 @@ impl Parser {
             }
         }
         self.consume(TokenKind::RightParen, "Expected ')' after arguments")?;
-        Ok(Expr::Call {
-            callee: Box::new(callee),
-            arguments,
-        })
+
+        Ok(arguments)
     }
 
     fn finish_property_access(&mut self, object: Expr) -> Result<Expr, ParseError> {
         let name = self.consume_identifier("Expected property name after '.'")?;
-        Ok(Expr::PropertyAccess {
-            object: Box::new(object),
-            name,
-        })
+        let span = self.span_from(object.span.start);
+
+        Ok(self.expr(
+            ExprKind::PropertyAccess {
+                object: Box::new(object),
+                name,
+            },
+            span,
+        ))
+    }
+
+    fn finish_optional_access(&mut self, object: Expr) -> Result<Expr, ParseError> {
+        let name = self.consume_identifier("Expected property name after '?.'")?;
+        let span = self.span_from(object.span.start);
+
+        Ok(self.expr(
+            ExprKind::OptionalPropertyAccess {
+                object: Box::new(object),
+                name,
+            },
+            span,
+        ))
     }
 
-    #[allow(dead_code)]
     fn parse_parameters(&mut self) -> Result<Vec<Parameter>, ParseError> {
         let mut params = Vec::new();
         if !self.check(&TokenKind::RightParen) {
             loop {
-                let name = self.consume_identifier("Expected parameter name")?;
+                let pattern = self.parse_binding_pattern("parameter")?;
+
+                if !params.is_empty()
+                    && matches!(&pattern.kind, PatternKind::Identifier(name) if name == "self")
+                {
+                    return Err(ParseError {
+                        message: "`self` must be the first parameter".into(),
+                        span: pattern.span,
+                    });
+                }
+
                 let type_annotation = if self.match_token(&[TokenKind::Colon]) {
                     Some(self.parse_type()?)
                 } else {
                     None
                 };
                 params.push(Parameter {
-                    name,
+                    pattern,
                     type_annotation,
                 });
 
@@ -627,6 +1660,169 @@ impl Parser {
         Ok(params)
     }
 
+    /// Parses a pattern in a `let`/`var` or parameter position, where the
+    /// pattern has to match every value of its type.
+    fn parse_binding_pattern(&mut self, position: &str) -> Result<Pattern, ParseError> {
+        let pattern = self.parse_pattern()?;
+
+        if let Some(refutable) = pattern.refutable_part() {
+            return Err(ParseError {
+                message: format!(
+                    "Refutable pattern in {} binding; only identifiers, `_`, tuples and records are allowed here",
+                    position
+                ),
+                span: refutable.span.clone(),
+            });
+        }
+
+        let bindings = pattern.bindings();
+        for (index, name) in bindings.iter().enumerate() {
+            if bindings[..index].contains(name) {
+                return Err(ParseError {
+                    message: format!(
+                        "Identifier `{}` is bound more than once in the same pattern",
+                        name
+                    ),
+                    span: pattern.span.clone(),
+                });
+            }
+        }
+
+        Ok(pattern)
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let start = self.peek().span.start;
+
+        let kind = if self.match_token(&[TokenKind::LeftParen]) {
+            let mut items = Vec::new();
+
+            if !self.check(&TokenKind::RightParen) {
+                loop {
+                    items.push(self.parse_pattern()?);
+
+                    if !self.match_token(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(TokenKind::RightParen, "Expected ')' after tuple pattern")?;
+
+            if items.len() == 1 {
+                return Ok(items.pop().unwrap());
+            }
+
+            PatternKind::Tuple(items)
+        } else if self.match_token(&[TokenKind::LeftBrace]) {
+            PatternKind::Record {
+                name: None,
+                fields: self.finish_record_pattern()?,
+            }
+        } else if self.match_token(&[TokenKind::Identifier(String::new())]) {
+            let name = match self.previous_token_kind() {
+                TokenKind::Identifier(name) => Ident::from(name),
+                _ => unreachable!(),
+            };
+
+            if self.match_token(&[TokenKind::LeftBrace]) {
+                PatternKind::Record {
+                    name: Some(name),
+                    fields: self.finish_record_pattern()?,
+                }
+            } else if self.match_token(&[TokenKind::LeftParen]) {
+                let mut args = Vec::new();
+
+                if !self.check(&TokenKind::RightParen) {
+                    loop {
+                        args.push(self.parse_pattern()?);
+
+                        if !self.match_token(&[TokenKind::Comma]) {
+                            break;
+                        }
+                    }
+                }
+
+                self.consume(
+                    TokenKind::RightParen,
+                    "Expected ')' after constructor pattern",
+                )?;
+
+                PatternKind::Constructor { name, args }
+            } else if name == "_" {
+                PatternKind::Wildcard
+            } else {
+                PatternKind::Identifier(name)
+            }
+        } else {
+            PatternKind::Literal(self.literal("Expected pattern")?)
+        };
+
+        Ok(self.pattern(kind, self.span_from(start)))
+    }
+
+    fn finish_record_pattern(&mut self) -> Result<Vec<FieldPattern>, ParseError> {
+        let mut fields = Vec::new();
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let start = self.peek().span.start;
+            let name = self.consume_identifier("Expected field name in record pattern")?;
+
+            let pattern = if self.match_token(&[TokenKind::Colon]) {
+                self.parse_pattern()?
+            } else {
+                self.pattern(PatternKind::Identifier(name), self.span_from(start))
+            };
+
+            fields.push(FieldPattern { name, pattern });
+
+            if !self.match_token(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenKind::RightBrace, "Expected '}' after record pattern")?;
+
+        Ok(fields)
+    }
+
+    // a literal in a pattern or type, which unlike an expression can be a negative number
+    fn literal(&mut self, message: &str) -> Result<Literal, ParseError> {
+        let negative = self.match_token(&[TokenKind::Minus]);
+
+        match self.peek().kind.clone() {
+            TokenKind::Number(n) => {
+                self.advance();
+
+                let num = n
+                    .parse::<f64>()
+                    .map_err(|_| self.error(format!("Invalid number literal: {}", n)))?;
+
+                Ok(Literal::Number(if negative { -num } else { num }))
+            }
+
+            TokenKind::String(s) if !negative => {
+                self.advance();
+
+                Ok(Literal::String(s))
+            }
+
+            TokenKind::Boolean(b) if !negative => {
+                self.advance();
+
+                Ok(Literal::Boolean(b))
+            }
+
+            TokenKind::Nil if !negative => {
+                self.advance();
+
+                Ok(Literal::Nil)
+            }
+
+            _ => Err(self.error(message)),
+        }
+    }
+
     fn match_token(&mut self, kinds: &[TokenKind]) -> bool {
         for kind in kinds {
             if self.check(kind) {
@@ -648,10 +1844,62 @@ impl Parser {
             (TokenKind::Identifier(_), TokenKind::Identifier(_)) => true,
             (TokenKind::Number(_), TokenKind::Number(_)) => true,
             (TokenKind::String(_), TokenKind::String(_)) => true,
+            (TokenKind::LuauBlock(_), TokenKind::LuauBlock(_)) => true,
+            (TokenKind::DocComment(_), TokenKind::DocComment(_)) => true,
             (expected, actual) => expected == actual,
         }
     }
 
+    // `{}`, `{ name }`, `{ name, ...` and `{ name: ...` are records, anything else is a block
+    fn record_check(&self) -> bool {
+        match &self.peek().kind {
+            TokenKind::RightBrace => true,
+            TokenKind::Identifier(_) => matches!(
+                self.tokens.get(self.current + 1).map(|token| &token.kind),
+                Some(TokenKind::Colon | TokenKind::Comma | TokenKind::RightBrace)
+            ),
+
+            _ => false,
+        }
+    }
+
+    fn record_fields(&mut self, context: &str) -> Result<Vec<(Ident, Expr)>, ParseError> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let fields = self.record_entries(context);
+        self.no_struct_literal = no_struct_literal;
+
+        let fields = fields?;
+        self.consume(
+            TokenKind::RightBrace,
+            &format!("Expected '}}' after {}", context),
+        )?;
+
+        Ok(fields)
+    }
+
+    fn record_entries(&mut self, context: &str) -> Result<Vec<(Ident, Expr)>, ParseError> {
+        let mut fields = Vec::new();
+
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let field_span = self.peek().span.clone();
+            let name = self.consume_identifier(&format!("Expected field name in {}", context))?;
+
+            let value = if self.match_token(&[TokenKind::Colon]) {
+                self.expression()?
+            } else {
+                self.expr(ExprKind::Identifier(name), field_span)
+            };
+
+            fields.push((name, value));
+
+            if !self.match_token(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+
+        Ok(fields)
+    }
+
     fn lambda_check(&self) -> bool {
         let mut index = self.current;
         let mut paren_count = 1;
@@ -672,18 +1920,18 @@ impl Parser {
             index += 1;
         }
 
-        if index < self.tokens.len() && self.tokens[index].kind == TokenKind::RightParen {
-            if index + 1 < self.tokens.len() && self.tokens[index + 1].kind == TokenKind::Arrow {
-                return true;
-            }
-        }
-
-        false
+        index + 1 < self.tokens.len()
+            && self.tokens[index].kind == TokenKind::RightParen
+            && self.tokens[index + 1].kind == TokenKind::Arrow
     }
 
     fn advance(&mut self) -> &ParserToken {
         if !self.is_at_end() {
             self.current += 1;
+
+            if let Some(sink) = &mut self.sink {
+                sink.token();
+            }
         }
 
         &self.tokens[self.current - 1]
@@ -697,17 +1945,13 @@ impl Parser {
         if self.check(&kind) {
             Ok(self.advance())
         } else {
-            Err(ParseError {
-                message: message.into(),
-            })
+            Err(self.error(message))
         }
     }
 
-    fn consume_identifier(&mut self, message: &str) -> Result<String, ParseError> {
+    fn consume_identifier(&mut self, message: &str) -> Result<Ident, ParseError> {
         if self.is_at_end() {
-            return Err(ParseError {
-                message: message.into(),
-            });
+            return Err(self.error(message));
         }
 
         let token = self.tokens[self.current].clone();
@@ -715,11 +1959,9 @@ impl Parser {
         if let TokenKind::Identifier(name) = token.kind {
             self.advance();
 
-            Ok(name)
+            Ok(Ident::from(name))
         } else {
-            Err(ParseError {
-                message: message.into(),
-            })
+            Err(self.error(message))
         }
     }
 
@@ -730,6 +1972,190 @@ impl Parser {
     fn previous(&self) -> &ParserToken {
         &self.tokens[self.current - 1]
     }
+
+    fn binary(&mut self, left: Expr, op: BinaryOp, right: Expr) -> Expr {
+        let span = Span::new(left.span.start, right.span.end);
+
+        self.expr(
+            ExprKind::Binary {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            },
+            span,
+        )
+    }
+
+    fn stmt(&mut self, kind: StmtKind, span: Span) -> Stmt {
+        self.node(SyntaxKind::from(&kind), span.start);
+
+        Stmt::new(kind, span)
+    }
+
+    fn expr(&mut self, kind: ExprKind, span: Span) -> Expr {
+        self.node(SyntaxKind::from(&kind), span.start);
+
+        Expr::new(kind, span)
+    }
+
+    fn pattern(&mut self, kind: PatternKind, span: Span) -> Pattern {
+        self.node(SyntaxKind::Pattern, span.start);
+
+        Pattern::new(kind, span)
+    }
+
+    // gathers everything read since `start` into one node of the syntax tree
+    fn node(&mut self, kind: SyntaxKind, start: Position) {
+        if let Some(sink) = &mut self.sink {
+            sink.node(kind, start.absolute);
+        }
+    }
+
+    fn span_from(&self, start: Position) -> Span {
+        Span::new(start, self.previous().span.end)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+            span: self.peek().span.clone(),
+        }
+    }
+}
+
+/// Moves parsed attributes onto the declaration they precede, checking that
+/// each one makes sense there.
+fn attach(attributes: Vec<Attribute>, mut stmt: Stmt) -> Result<Stmt, ParseError> {
+    if attributes.is_empty() {
+        return Ok(stmt);
+    }
+
+    let (target, slot) = match &mut stmt.kind {
+        StmtKind::FunctionDecl { attributes, .. } => ("function", attributes),
+        StmtKind::ClassDecl { attributes, .. } => ("class", attributes),
+        StmtKind::VariableDecl { attributes, .. } => ("variable", attributes),
+
+        _ => {
+            return Err(ParseError {
+                message: "Attributes can only be applied to functions, classes and variables"
+                    .into(),
+                span: attributes[0].span.clone(),
+            });
+        }
+    };
+
+    for attribute in &attributes {
+        let allowed = match attribute.kind {
+            AttributeKind::Deprecated(_) => true,
+            AttributeKind::Native => target != "variable",
+            AttributeKind::Inline | AttributeKind::Test => target == "function",
+        };
+
+        if !allowed {
+            return Err(ParseError {
+                message: format!(
+                    "`@{}` cannot be applied to a {}",
+                    attribute.kind.name(),
+                    target
+                ),
+                span: attribute.span.clone(),
+            });
+        }
+    }
+
+    *slot = attributes;
+
+    Ok(stmt)
+}
+
+/// Attaches doc comments to the declaration they precede.
+fn document(docs: Option<String>, span: Span, stmt: Stmt) -> Result<Stmt, ParseError> {
+    match &stmt.kind {
+        StmtKind::VariableDecl { .. }
+        | StmtKind::FunctionDecl { .. }
+        | StmtKind::ClassDecl { .. }
+        | StmtKind::InterfaceDecl { .. }
+        | StmtKind::MixinDecl { .. }
+        | StmtKind::ModuleDecl { .. }
+        | StmtKind::TypeAlias { .. } => Ok(with_docs(docs, stmt)),
+
+        _ => Err(ParseError {
+            message: "Doc comments can only be applied to declarations".into(),
+            span,
+        }),
+    }
+}
+
+fn with_docs(docs: Option<String>, mut stmt: Stmt) -> Stmt {
+    match &mut stmt.kind {
+        StmtKind::VariableDecl { docs: slot, .. }
+        | StmtKind::FunctionDecl { docs: slot, .. }
+        | StmtKind::ClassDecl { docs: slot, .. }
+        | StmtKind::InterfaceDecl { docs: slot, .. }
+        | StmtKind::MixinDecl { docs: slot, .. }
+        | StmtKind::ModuleDecl { docs: slot, .. }
+        | StmtKind::TypeAlias { docs: slot, .. } => *slot = docs,
+
+        _ => {}
+    }
+
+    stmt
+}
+
+// where `recover` stops skipping, as long as it has skipped something
+fn starts_declaration(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Let
+            | TokenKind::Var
+            | TokenKind::Fn
+            | TokenKind::Class
+            | TokenKind::Interface
+            | TokenKind::Mixin
+            | TokenKind::Module
+            | TokenKind::Import
+            | TokenKind::Type
+            | TokenKind::Return
+            | TokenKind::If
+            | TokenKind::While
+            | TokenKind::For
+            | TokenKind::At
+            | TokenKind::DocComment(_)
+    )
+}
+
+/// Instance methods take `self` first; nothing else takes `self` at all.
+fn check_receiver(
+    name: &str,
+    name_span: Span,
+    params: &[Parameter],
+    kind: FunctionKind,
+) -> Result<(), ParseError> {
+    let receiver = params.first().filter(
+        |param| matches!(&param.pattern.kind, PatternKind::Identifier(name) if name == "self"),
+    );
+
+    match (kind, receiver) {
+        (FunctionKind::Instance, None) => Err(ParseError {
+            message: format!(
+                "Instance method `{}` must take `self` as its first parameter; declare it `static` if it doesn't need an instance",
+                name
+            ),
+            span: name_span,
+        }),
+
+        (FunctionKind::Static, Some(receiver)) => Err(ParseError {
+            message: format!("Static method `{}` cannot take `self`", name),
+            span: receiver.pattern.span.clone(),
+        }),
+
+        (FunctionKind::Function, Some(receiver)) => Err(ParseError {
+            message: format!("Function `{}` cannot take `self` outside of a class", name),
+            span: receiver.pattern.span.clone(),
+        }),
+
+        _ => Ok(()),
+    }
 }
 
 impl From<crate::token::Token> for ParserToken {
@@ -741,19 +2167,41 @@ impl From<crate::token::Token> for ParserToken {
             TokenType::Number(n) => Number(n),
             TokenType::String(s) => String(s),
             TokenType::Identifier(s) => Identifier(s),
+            TokenType::LuauBlock(body) => LuauBlock(body),
+            TokenType::DocComment(text) => DocComment(text),
             TokenType::Boolean(b) => Boolean(b),
+            TokenType::Nil => Nil,
             TokenType::Keyword(ref kw) if kw == "let" => Let,
             TokenType::Keyword(ref kw) if kw == "var" => Var,
             TokenType::Keyword(ref kw) if kw == "fn" => Fn,
             TokenType::Keyword(ref kw) if kw == "class" => Class,
+            TokenType::Keyword(ref kw) if kw == "type" => Type,
+            TokenType::Keyword(ref kw) if kw == "interface" => Interface,
+            TokenType::Keyword(ref kw) if kw == "implements" => Implements,
+            TokenType::Keyword(ref kw) if kw == "mixin" => Mixin,
+            TokenType::Keyword(ref kw) if kw == "includes" => Includes,
+            TokenType::Keyword(ref kw) if kw == "static" => Static,
+            TokenType::Keyword(ref kw) if kw == "luau" => Luau,
             TokenType::Keyword(ref kw) if kw == "return" => Return,
+            TokenType::Keyword(ref kw) if kw == "if" => If,
+            TokenType::Keyword(ref kw) if kw == "else" => Else,
+            TokenType::Keyword(ref kw) if kw == "while" => While,
+            TokenType::Keyword(ref kw) if kw == "for" => For,
+            TokenType::Keyword(ref kw) if kw == "in" => In,
+            TokenType::Keyword(ref kw) if kw == "break" => Break,
+            TokenType::Keyword(ref kw) if kw == "continue" => Continue,
+            TokenType::Keyword(ref kw) if kw == "match" => Match,
+            TokenType::Keyword(ref kw) if kw == "where" => Where,
             TokenType::Semicolon => Semicolon,
             TokenType::Colon => Colon,
             TokenType::Arrow => Arrow,
+            TokenType::FatArrow => FatArrow,
             TokenType::LeftParen => LeftParen,
             TokenType::RightParen => RightParen,
             TokenType::LeftBrace => LeftBrace,
             TokenType::RightBrace => RightBrace,
+            TokenType::LeftBracket => LeftBracket,
+            TokenType::RightBracket => RightBracket,
             TokenType::Plus => Plus,
             TokenType::Minus => Minus,
             TokenType::Star => Star,
@@ -767,15 +2215,27 @@ impl From<crate::token::Token> for ParserToken {
             TokenType::GreaterEqual => GreaterEqual,
             TokenType::And => And,
             TokenType::Or => Or,
+            TokenType::Pipe => Pipe,
             TokenType::Bang => Not,
             TokenType::Equal => Equal,
+            TokenType::PlusEqual => PlusEqual,
+            TokenType::MinusEqual => MinusEqual,
             TokenType::Dot => Dot,
+            TokenType::DotDot => DotDot,
+            TokenType::DotDotEqual => DotDotEqual,
             TokenType::Comma => Comma,
+            TokenType::At => At,
+            TokenType::Question => Question,
+            TokenType::QuestionDot => QuestionDot,
+            TokenType::QuestionQuestion => QuestionQuestion,
             TokenType::Eof => Eof,
 
             _ => Identifier("unknown".into()),
         };
 
-        ParserToken { kind }
+        ParserToken {
+            kind,
+            span: token.span,
+        }
     }
 }
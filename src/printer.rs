@@ -0,0 +1,741 @@
+use crate::ast::*;
+use crate::node::Ident;
+use std::fmt;
+
+// binding power of each expression form, loosest first; mirrors the parser's descent
+const LAMBDA_PRECEDENCE: u8 = 0;
+const UNARY_PRECEDENCE: u8 = 8;
+const POSTFIX_PRECEDENCE: u8 = 9;
+
+/// Prints a whole program back to exx source, one declaration per line and
+/// with blank lines around functions, classes and the like.
+pub fn print(program: &[Stmt]) -> String {
+    let mut printer = Printer::new();
+    printer.stmts(program);
+
+    printer.output
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut printer = Printer::new();
+        printer.stmt(self, "");
+
+        write!(f, "{}", printer.output.trim_end())
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Printer::new().expr(self))
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", pattern(self))
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", literal(self))
+    }
+}
+
+struct Printer {
+    output: String,
+    indent: usize,
+    no_struct_literal: bool, // inside an `if`/`while`/`for` header, as in the parser
+}
+
+impl Printer {
+    fn new() -> Self {
+        Self {
+            output: String::new(),
+            indent: 0,
+            no_struct_literal: false,
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.output.push_str("    ");
+        }
+
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    // runs `f` one level deeper and returns what it printed instead of appending it
+    fn capture(&mut self, f: impl FnOnce(&mut Self)) -> String {
+        let saved = std::mem::take(&mut self.output);
+
+        self.indent += 1;
+        f(self);
+        self.indent -= 1;
+
+        std::mem::replace(&mut self.output, saved)
+    }
+
+    // brackets of any kind lift the struct literal restriction, as they do in the parser
+    fn nested(&mut self, f: impl FnOnce(&mut Self) -> String) -> String {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let text = f(self);
+        self.no_struct_literal = no_struct_literal;
+
+        text
+    }
+
+    fn condition(&mut self, expr: &Expr) -> String {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, true);
+        let text = self.expr(expr);
+        self.no_struct_literal = no_struct_literal;
+
+        text
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        self.members(stmts.iter().map(|stmt| (stmt, "")));
+    }
+
+    // functions, classes and the like get a blank line on either side
+    fn members<'a>(&mut self, members: impl Iterator<Item = (&'a Stmt, &'a str)>) {
+        let mut previous: Option<&Stmt> = None;
+
+        for (stmt, prefix) in members {
+            if previous.is_some_and(|previous| is_spaced(previous) || is_spaced(stmt)) {
+                self.output.push('\n');
+            }
+
+            self.stmt(stmt, prefix);
+            previous = Some(stmt);
+        }
+    }
+
+    fn block(&mut self, body: &[Stmt]) -> String {
+        if body.is_empty() {
+            return "{}".into();
+        }
+
+        let inner = self.capture(|this| this.stmts(body));
+
+        format!("{{\n{}{}}}", inner, "    ".repeat(self.indent))
+    }
+
+    fn docs(&mut self, docs: Option<&str>) {
+        for line in docs.into_iter().flat_map(str::lines) {
+            if line.is_empty() {
+                self.line("///");
+            } else {
+                self.line(&format!("/// {}", line));
+            }
+        }
+    }
+
+    // `prefix` is `static ` for static class members
+    fn stmt(&mut self, stmt: &Stmt, prefix: &str) {
+        self.docs(stmt.docs());
+
+        for attribute in stmt.attributes() {
+            self.line(&self::attribute(attribute));
+        }
+
+        match &stmt.kind {
+            StmtKind::VariableDecl {
+                is_mutable,
+                pattern,
+                type_annotation,
+                initializer,
+                ..
+            } => {
+                let mut text = format!(
+                    "{}{} {}",
+                    prefix,
+                    if *is_mutable { "var" } else { "let" },
+                    self::pattern(pattern)
+                );
+
+                if let Some(ty) = type_annotation {
+                    text.push_str(&format!(": {}", ty));
+                }
+
+                if let Some(initializer) = initializer {
+                    text.push_str(&format!(" = {}", self.expr(initializer)));
+                }
+
+                self.line(&format!("{};", text));
+            }
+
+            StmtKind::FunctionDecl {
+                name,
+                type_params,
+                bounds,
+                params,
+                return_type,
+                body,
+                ..
+            } => {
+                let body = self.block(body);
+
+                self.line(&format!(
+                    "{}fn {}{}({}){} {}",
+                    prefix,
+                    name,
+                    type_parameters(type_params, bounds),
+                    parameters(params),
+                    return_type_suffix(return_type.as_ref()),
+                    body
+                ));
+            }
+
+            StmtKind::ClassDecl {
+                name,
+                implements,
+                includes,
+                fields,
+                methods,
+                static_fields,
+                static_methods,
+                ..
+            } => {
+                let mut header = format!("class {}", name);
+
+                if !implements.is_empty() {
+                    header.push_str(&format!(" implements {}", implements.join(", ")));
+                }
+
+                if !includes.is_empty() {
+                    header.push_str(&format!(" includes {}", includes.join(", ")));
+                }
+
+                self.line(&format!("{} {{", header));
+                self.indent += 1;
+
+                let members = fields
+                    .iter()
+                    .map(|field| (field, ""))
+                    .chain(static_fields.iter().map(|field| (field, "static ")))
+                    .chain(static_methods.iter().map(|method| (method, "static ")))
+                    .chain(methods.iter().map(|method| (method, "")));
+
+                self.members(members);
+                self.indent -= 1;
+                self.line("}");
+            }
+
+            StmtKind::InterfaceDecl { name, methods, .. } => {
+                self.line(&format!("interface {} {{", name));
+                self.indent += 1;
+
+                for method in methods {
+                    self.docs(method.docs.as_deref());
+
+                    let signature = format!(
+                        "fn {}{}({}){};",
+                        method.name,
+                        type_parameters(&method.type_params, &method.bounds),
+                        parameters(&method.params),
+                        return_type_suffix(method.return_type.as_ref())
+                    );
+
+                    self.line(&signature);
+                }
+
+                self.indent -= 1;
+                self.line("}");
+            }
+
+            StmtKind::MixinDecl {
+                name,
+                methods: body,
+                ..
+            } => {
+                self.line(&format!("mixin {} {{", name));
+                self.indent += 1;
+                self.stmts(body);
+                self.indent -= 1;
+                self.line("}");
+            }
+
+            StmtKind::ModuleDecl {
+                name, declarations, ..
+            } => {
+                self.line(&format!("module {} {{", name));
+                self.indent += 1;
+                self.stmts(declarations);
+                self.indent -= 1;
+                self.line("}");
+            }
+
+            StmtKind::TypeAlias {
+                name, params, ty, ..
+            } => {
+                self.line(&format!(
+                    "type {}{} = {};",
+                    name,
+                    type_parameters(params, &[]),
+                    ty
+                ));
+            }
+
+            StmtKind::Import(name) => self.line(&format!("import {};", name)),
+
+            StmtKind::Assign { target, op, value } => {
+                let operator = match op {
+                    Some(op) => format!("{}=", binary_operator(op)),
+                    None => "=".into(),
+                };
+
+                let target = self.expr(target);
+                let value = self.expr(value);
+
+                self.line(&format!("{} {} {};", target, operator, value));
+            }
+
+            StmtKind::If { .. } => {
+                let text = self.if_chain(stmt);
+                self.line(&text);
+            }
+
+            StmtKind::While { condition, body } => {
+                let condition = self.condition(condition);
+                let body = self.block(body);
+
+                self.line(&format!("while {} {}", condition, body));
+            }
+
+            StmtKind::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                let iterable = self.condition(iterable);
+                let body = self.block(body);
+
+                self.line(&format!(
+                    "for {} in {} {}",
+                    self::pattern(pattern),
+                    iterable,
+                    body
+                ));
+            }
+
+            // a statement-level `luau { ... }` needs no `;`
+            StmtKind::Expression(
+                expr @ Expr {
+                    kind:
+                        ExprKind::Luau {
+                            return_type: None, ..
+                        },
+                    ..
+                },
+            ) => {
+                let text = self.expr(expr);
+                self.line(&text);
+            }
+
+            StmtKind::Expression(expr) => {
+                let text = self.expr(expr);
+                self.line(&format!("{};", text));
+            }
+
+            StmtKind::Return(Some(expr)) => {
+                let text = self.expr(expr);
+                self.line(&format!("return {};", text));
+            }
+
+            StmtKind::Return(None) => self.line("return;"),
+            StmtKind::Break => self.line("break;"),
+            StmtKind::Continue => self.line("continue;"),
+        }
+    }
+
+    // `if a { } else if b { } else { }`, on a single logical line
+    fn if_chain(&mut self, stmt: &Stmt) -> String {
+        let StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } = &stmt.kind
+        else {
+            unreachable!()
+        };
+
+        let condition = self.condition(condition);
+        let mut text = format!("if {} {}", condition, self.block(then_branch));
+
+        match else_branch.as_deref() {
+            Some(
+                [
+                    nested @ Stmt {
+                        kind: StmtKind::If { .. },
+                        ..
+                    },
+                ],
+            ) => {
+                text.push_str(&format!(" else {}", self.if_chain(nested)));
+            }
+
+            Some(else_branch) => {
+                text.push_str(&format!(" else {}", self.block(else_branch)));
+            }
+
+            None => {}
+        }
+
+        text
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) -> String {
+        self.nested(|this| {
+            exprs
+                .iter()
+                .map(|expr| this.expr(expr))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    }
+
+    fn fields(&mut self, fields: &[(Ident, Expr)]) -> String {
+        if fields.is_empty() {
+            return "{}".into();
+        }
+
+        let fields = self.nested(|this| {
+            fields
+                .iter()
+                .map(|(name, value)| match &value.kind {
+                    ExprKind::Identifier(value) if value == name => name.to_string(),
+
+                    _ => format!("{}: {}", name, this.expr(value)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+
+        format!("{{ {} }}", fields)
+    }
+
+    // `expr`, parenthesized if it binds looser than `min` allows
+    fn operand(&mut self, expr: &Expr, min: u8) -> String {
+        // `1.x` would lex as a malformed number
+        let number = matches!(expr.kind, ExprKind::Literal(Literal::Number(_)));
+
+        if precedence(expr) < min || (number && min == POSTFIX_PRECEDENCE) {
+            let text = self.nested(|this| this.expr(expr));
+
+            format!("({})", text)
+        } else {
+            self.expr(expr)
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Binary { left, op, right } => {
+                let precedence = binary_precedence(op);
+
+                // `??` groups to the right, everything else to the left
+                let (left_min, right_min) = match op {
+                    BinaryOp::Coalesce => (precedence + 1, precedence),
+
+                    _ => (precedence, precedence + 1),
+                };
+
+                let left = self.operand(left, left_min);
+                let right = self.operand(right, right_min);
+
+                format!("{} {} {}", left, binary_operator(op), right)
+            }
+
+            ExprKind::Unary { op, expr } => {
+                let operator = match op {
+                    UnaryOp::Negate => "-",
+                    UnaryOp::Not => "!",
+                };
+
+                let operand = self.operand(expr, UNARY_PRECEDENCE);
+
+                // `--x` or `!-x` would lex as a single operator
+                if operand.starts_with(['-', '!']) {
+                    format!("{} {}", operator, operand)
+                } else {
+                    format!("{}{}", operator, operand)
+                }
+            }
+
+            ExprKind::Literal(value) => literal(value),
+            ExprKind::Identifier(name) => name.to_string(),
+
+            ExprKind::Grouping(inner) => {
+                let inner = self.nested(|this| this.expr(inner));
+
+                format!("({})", inner)
+            }
+
+            ExprKind::Tuple(items) => format!("({})", self.exprs(items)),
+
+            ExprKind::StructLiteral { name, fields } => {
+                let text = format!("{} {}", name, self.fields(fields));
+
+                if self.no_struct_literal {
+                    format!("({})", text)
+                } else {
+                    text
+                }
+            }
+
+            ExprKind::Record(fields) => self.fields(fields),
+            ExprKind::Block(body) => self.block(body),
+
+            ExprKind::PropertyAccess { object, name } => {
+                format!("{}.{}", self.operand(object, POSTFIX_PRECEDENCE), name)
+            }
+
+            ExprKind::OptionalPropertyAccess { object, name } => {
+                format!("{}?.{}", self.operand(object, POSTFIX_PRECEDENCE), name)
+            }
+
+            ExprKind::Call { callee, arguments } => {
+                let callee = self.operand(callee, POSTFIX_PRECEDENCE);
+
+                format!("{}({})", callee, self.exprs(arguments))
+            }
+
+            ExprKind::Lambda { params, body } => {
+                let params = parameters(params);
+                let body = self.nested(|this| this.expr(body));
+
+                format!("({}) -> {}", params, body)
+            }
+
+            ExprKind::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let start = self.operand(start, LAMBDA_PRECEDENCE + 1);
+                let end = self.operand(end, LAMBDA_PRECEDENCE + 1);
+
+                format!("{}{}{}", start, if *inclusive { "..=" } else { ".." }, end)
+            }
+
+            ExprKind::Luau {
+                return_type,
+                bindings,
+                body,
+                ..
+            } => {
+                let mut text = String::from("luau");
+
+                if let Some(ty) = return_type {
+                    text.push_str(&format!("<{}>", ty));
+                }
+
+                if !bindings.is_empty() {
+                    let names: Vec<_> = bindings.iter().map(|(name, _)| name.as_str()).collect();
+                    text.push_str(&format!("({})", names.join(", ")));
+                }
+
+                format!("{} {{{}}}", text, body)
+            }
+
+            ExprKind::Match { scrutinee, arms } => {
+                let scrutinee = self.condition(scrutinee);
+                let arms = self.capture(|this| {
+                    for arm in arms {
+                        let body = this.nested(|this| this.expr(&arm.body));
+                        this.line(&format!("{} => {},", pattern(&arm.pattern), body));
+                    }
+                });
+
+                format!(
+                    "match {} {{\n{}{}}}",
+                    scrutinee,
+                    arms,
+                    "    ".repeat(self.indent)
+                )
+            }
+        }
+    }
+}
+
+fn is_spaced(stmt: &Stmt) -> bool {
+    matches!(
+        stmt.kind,
+        StmtKind::FunctionDecl { .. }
+            | StmtKind::ClassDecl { .. }
+            | StmtKind::InterfaceDecl { .. }
+            | StmtKind::MixinDecl { .. }
+            | StmtKind::ModuleDecl { .. }
+    )
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::Lambda { .. } | ExprKind::Range { .. } => LAMBDA_PRECEDENCE,
+        ExprKind::Binary { op, .. } => binary_precedence(op),
+        ExprKind::Unary { .. } => UNARY_PRECEDENCE,
+
+        // a negative literal only comes out of a rewrite, but prints like `-x`
+        ExprKind::Literal(Literal::Number(n)) if n.is_sign_negative() => UNARY_PRECEDENCE,
+
+        _ => POSTFIX_PRECEDENCE,
+    }
+}
+
+fn binary_precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Coalesce => 1,
+        BinaryOp::Or => 2,
+        BinaryOp::And => 3,
+        BinaryOp::EqualEqual | BinaryOp::NotEqual => 4,
+        BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => 5,
+        BinaryOp::Add | BinaryOp::Sub => 6,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 7,
+    }
+}
+
+fn binary_operator(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::EqualEqual => "==",
+        BinaryOp::NotEqual => "!=",
+        BinaryOp::Less => "<",
+        BinaryOp::LessEqual => "<=",
+        BinaryOp::Greater => ">",
+        BinaryOp::GreaterEqual => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+        BinaryOp::Coalesce => "??",
+    }
+}
+
+fn return_type_suffix(return_type: Option<&Type>) -> String {
+    match return_type {
+        Some(ty) => format!(" -> {}", ty),
+        None => String::new(),
+    }
+}
+
+// bounds from a `where` clause are printed with their parameter, as `<T: Show>`
+fn type_parameters(params: &[Ident], bounds: &[TypeBound]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| {
+            let constraints: Vec<&str> = bounds
+                .iter()
+                .filter(|bound| bound.param == *param)
+                .flat_map(|bound| bound.constraints.iter().map(Ident::as_str))
+                .collect();
+
+            if constraints.is_empty() {
+                param.to_string()
+            } else {
+                format!("{}: {}", param, constraints.join(" + "))
+            }
+        })
+        .collect();
+
+    format!("<{}>", params.join(", "))
+}
+
+fn parameters(params: &[Parameter]) -> String {
+    params
+        .iter()
+        .map(|param| match &param.type_annotation {
+            Some(ty) => format!("{}: {}", pattern(&param.pattern), ty),
+            None => pattern(&param.pattern),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn attribute(attribute: &Attribute) -> String {
+    match &attribute.kind {
+        AttributeKind::Deprecated(Some(note)) => format!("@deprecated({})", string(note)),
+
+        kind => format!("@{}", kind.name()),
+    }
+}
+
+fn pattern(pattern: &Pattern) -> String {
+    fn list(items: &[Pattern]) -> String {
+        items
+            .iter()
+            .map(self::pattern)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    match &pattern.kind {
+        PatternKind::Wildcard => "_".into(),
+        PatternKind::Identifier(name) => name.to_string(),
+        PatternKind::Tuple(items) => format!("({})", list(items)),
+
+        PatternKind::Record { name, fields } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|field| match &field.pattern.kind {
+                    PatternKind::Identifier(binding) if *binding == field.name => {
+                        field.name.to_string()
+                    }
+
+                    _ => format!("{}: {}", field.name, self::pattern(&field.pattern)),
+                })
+                .collect();
+
+            let body = if fields.is_empty() {
+                "{}".into()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            };
+
+            match name {
+                Some(name) => format!("{} {}", name, body),
+                None => body,
+            }
+        }
+
+        PatternKind::Constructor { name, args } => format!("{}({})", name, list(args)),
+        PatternKind::Literal(value) => literal(value),
+    }
+}
+
+fn literal(value: &Literal) -> String {
+    match value {
+        Literal::Number(n) => n.to_string(),
+        Literal::String(s) => string(s),
+        Literal::Boolean(b) => b.to_string(),
+        Literal::Nil => "nil".into(),
+    }
+}
+
+fn string(value: &str) -> String {
+    let mut text = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            '\t' => text.push_str("\\t"),
+            '\r' => text.push_str("\\r"),
+
+            _ => text.push(c),
+        }
+    }
+
+    text.push('"');
+
+    text
+}
@@ -0,0 +1,515 @@
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::node::{DefId, DefKind, Definition, NodeId, Symbol, Tables};
+use crate::span::Span;
+use crate::visit::{Visitor, walk_expr};
+use std::collections::HashMap;
+
+/// Globals every Luau script can use without declaring them.
+const PRELUDE: &[&str] = &[
+    "print",
+    "warn",
+    "error",
+    "assert",
+    "type",
+    "typeof",
+    "tostring",
+    "tonumber",
+    "pairs",
+    "ipairs",
+    "select",
+    "next",
+    "rawget",
+    "rawset",
+    "rawequal",
+    "rawlen",
+    "setmetatable",
+    "getmetatable",
+    "pcall",
+    "xpcall",
+    "unpack",
+    "require",
+    "math",
+    "string",
+    "table",
+    "coroutine",
+    "bit32",
+    "utf8",
+    "os",
+    "debug",
+    "buffer",
+    "game",
+    "workspace",
+    "script",
+    "Instance",
+    "Vector3",
+    "CFrame",
+    "Color3",
+    "UDim2",
+    "Enum",
+    "task",
+    "tick",
+    "time",
+    "wait",
+];
+
+/// Resolves every name in `program` to its definition, recording the links in
+/// `tables.defs` and the definitions themselves in `tables.definitions`.
+///
+/// Items (functions, classes, interfaces, mixins, modules, imports and type
+/// aliases) are visible throughout the block that declares them; `let` and
+/// `var` bindings only from the statement after theirs. An item used before
+/// its declaration has to be used from inside a function, which can't run
+/// until the item exists.
+pub fn resolve(program: &[Stmt], tables: &mut Tables) -> Vec<Diagnostic> {
+    let mut resolver = Resolver {
+        tables,
+        scopes: vec![Scope::default()],
+        pending: HashMap::new(),
+        deferred: 0,
+        diagnostics: vec![],
+    };
+
+    resolver.block(program);
+
+    resolver.diagnostics
+}
+
+// values and types live in separate namespaces, so `type Point = ...` and
+// `let Point = ...` don't collide; classes are both
+#[derive(Default)]
+struct Scope {
+    values: HashMap<Symbol, DefId>,
+    types: HashMap<Symbol, DefId>,
+}
+
+struct Resolver<'t> {
+    tables: &'t mut Tables,
+    scopes: Vec<Scope>,
+    pending: HashMap<DefId, NodeId>, // items whose declaration hasn't run yet, to that statement
+    deferred: usize,                 // how many function bodies deep the resolver is
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolver<'_> {
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(Scope::default());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn define(&mut self, name: &str, kind: DefKind, span: &Span) -> DefId {
+        let symbol = self.tables.symbols.intern(name);
+        let def = DefId(self.tables.definitions.len() as u32);
+        let scope = self.scopes.last_mut().unwrap();
+
+        let mut previous = None;
+
+        if kind.is_value() {
+            previous = previous.or(scope.values.insert(symbol, def));
+        }
+
+        if kind.is_type() {
+            previous = previous.or(scope.types.insert(symbol, def));
+        }
+
+        if let Some(previous) = previous {
+            let previous = self.tables.definition(previous);
+
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E301",
+                    format!("`{}` is defined more than once in this scope", name),
+                )
+                .with_label(span.clone(), "redefined here")
+                .with_label(previous.span.clone(), "first defined here"),
+            );
+        }
+
+        self.tables.definitions.push(Definition {
+            name: name.to_string(),
+            kind,
+            span: span.clone(),
+        });
+
+        def
+    }
+
+    // declares every identifier a binding pattern introduces
+    fn bind(&mut self, pattern: &Pattern, kind: DefKind) {
+        match &pattern.kind {
+            // a bare constructor like `None` is matched rather than bound
+            PatternKind::Identifier(name) if self.constructor(name).is_some() => {
+                self.use_name(pattern.id, name, &pattern.span);
+            }
+
+            PatternKind::Identifier(name) => {
+                let def = self.define(name, kind, &pattern.span);
+                self.tables.defs.insert(pattern.id, def);
+            }
+
+            PatternKind::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.bind(pattern, kind);
+                }
+            }
+
+            PatternKind::Record { fields, .. } => {
+                for field in fields {
+                    self.bind(&field.pattern, kind);
+                }
+            }
+
+            PatternKind::Constructor { name, args } => {
+                self.use_name(pattern.id, name, &pattern.span);
+
+                for arg in args {
+                    self.bind(arg, kind);
+                }
+            }
+
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<DefId> {
+        let symbol = self.tables.symbols.get(name)?;
+
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.values.get(&symbol).copied())
+    }
+
+    fn constructor(&self, name: &str) -> Option<DefId> {
+        self.lookup(name)
+            .filter(|&def| self.tables.definition(def).kind == DefKind::Variant)
+    }
+
+    fn use_name(&mut self, id: NodeId, name: &str, span: &Span) {
+        if let Some(def) = self.lookup(name) {
+            self.tables.defs.insert(id, def);
+
+            if self.deferred == 0 && self.pending.contains_key(&def) {
+                self.early_use(name, def, span);
+            }
+
+            return;
+        }
+
+        if PRELUDE.contains(&name) {
+            return;
+        }
+
+        let mut diagnostic =
+            Diagnostic::error("E300", format!("Cannot find `{}` in this scope", name))
+                .with_label(span.clone(), "not found in this scope");
+
+        if let Some((suggestion, def)) = self.suggestion(name) {
+            if let Some(def) = def {
+                let definition = self.tables.definition(def);
+
+                diagnostic = diagnostic.with_label(
+                    definition.span.clone(),
+                    format!("`{}` is defined here", suggestion),
+                );
+            }
+
+            diagnostic = diagnostic.with_note(format!("help: did you mean `{}`?", suggestion));
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn early_use(&mut self, name: &str, def: DefId, span: &Span) {
+        let definition = self.tables.definition(def);
+
+        self.diagnostics.push(
+            Diagnostic::error("E302", format!("`{}` is used before its declaration", name))
+                .with_label(span.clone(), "used here")
+                .with_label(definition.span.clone(), "declared later here")
+                .with_note("help: move the declaration up, or only use it inside a function"),
+        );
+    }
+
+    // the closest visible name, if it's close enough to plausibly be a typo
+    fn suggestion(&self, name: &str) -> Option<(String, Option<DefId>)> {
+        let limit = (name.chars().count() / 3).max(1);
+
+        let visible = self.scopes.iter().rev().flat_map(|scope| {
+            scope
+                .values
+                .iter()
+                .map(|(&symbol, &def)| (self.tables.symbols.resolve(symbol), Some(def)))
+        });
+        let builtins = PRELUDE.iter().map(|&name| (name, None));
+
+        let mut best: Option<(usize, &str, Option<DefId>)> = None;
+
+        for (candidate, def) in visible.chain(builtins) {
+            let distance = edit_distance(name, candidate);
+
+            // ties go to the alphabetically first name, so the output is stable
+            if distance > limit
+                || best.is_some_and(|(best, name, _)| (best, name) <= (distance, candidate))
+            {
+                continue;
+            }
+
+            best = Some((distance, candidate, def));
+        }
+
+        best.map(|(_, candidate, def)| (candidate.to_string(), def))
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.declare_item(stmt);
+        }
+
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn declare_item(&mut self, stmt: &Stmt) {
+        let (name, kind) = match &stmt.kind {
+            StmtKind::FunctionDecl { name, .. } => (name, DefKind::Function),
+            StmtKind::ClassDecl { name, .. } => (name, DefKind::Class),
+            StmtKind::InterfaceDecl { name, .. } => (name, DefKind::Interface),
+            StmtKind::MixinDecl { name, .. } => (name, DefKind::Mixin),
+            StmtKind::ModuleDecl { name, .. } => (name, DefKind::Module),
+            StmtKind::TypeAlias { name, .. } => (name, DefKind::TypeAlias),
+            StmtKind::Import(name) => (name, DefKind::Import),
+            _ => return,
+        };
+
+        let mut defs = vec![(self.define(name, kind, &stmt.span), kind)];
+
+        if let StmtKind::TypeAlias { ty, .. } = &stmt.kind {
+            for (variant, _) in ty.variants().unwrap_or_default() {
+                let def = self.define(&variant, DefKind::Variant, &stmt.span);
+                defs.push((def, DefKind::Variant));
+            }
+        }
+
+        for (def, kind) in defs {
+            if kind.is_value() {
+                self.pending.insert(def, stmt.id);
+            }
+        }
+    }
+
+    // methods and fields can be named bare inside their class; the checker
+    // then rejects calls that are missing `self.`
+    fn declare_members(&mut self, members: &[Stmt]) {
+        for member in members {
+            match &member.kind {
+                StmtKind::VariableDecl { pattern, .. } => self.bind(pattern, DefKind::Member),
+                StmtKind::FunctionDecl { name, .. } => {
+                    self.define(name, DefKind::Member, &member.span);
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn member(&mut self, member: &Stmt) {
+        match &member.kind {
+            StmtKind::VariableDecl {
+                initializer: Some(initializer),
+                ..
+            } => self.visit_expr(initializer),
+
+            StmtKind::FunctionDecl { params, body, .. } => self.function(params, body),
+            _ => {}
+        }
+    }
+
+    // parameters get a scope of their own, so the body can shadow them
+    fn function(&mut self, params: &[Parameter], body: &[Stmt]) {
+        self.deferring(|this| {
+            this.scoped(|this| {
+                for param in params {
+                    this.bind(&param.pattern, DefKind::Parameter);
+                }
+
+                this.scoped(|this| this.block(body));
+            })
+        });
+    }
+
+    // code that only runs once something calls it, by which time every item exists
+    fn deferring(&mut self, f: impl FnOnce(&mut Self)) {
+        self.deferred += 1;
+        f(self);
+        self.deferred -= 1;
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        if !self.pending.is_empty() {
+            self.pending.retain(|_, declared| *declared != stmt.id);
+        }
+
+        match &stmt.kind {
+            StmtKind::VariableDecl {
+                pattern,
+                initializer,
+                ..
+            } => {
+                if let Some(initializer) = initializer {
+                    self.visit_expr(initializer);
+                }
+
+                self.bind(pattern, DefKind::Variable);
+            }
+
+            StmtKind::FunctionDecl { params, body, .. } => self.function(params, body),
+
+            StmtKind::ClassDecl {
+                fields,
+                methods,
+                static_fields,
+                static_methods,
+                ..
+            } => self.scoped(|this| {
+                let members = [fields, static_fields, static_methods, methods];
+
+                for members in members {
+                    this.declare_members(members);
+                }
+
+                // instance field defaults are evaluated for each new instance
+                this.deferring(|this| fields.iter().for_each(|field| this.member(field)));
+
+                for member in [static_fields, static_methods, methods]
+                    .into_iter()
+                    .flatten()
+                {
+                    this.member(member);
+                }
+            }),
+
+            StmtKind::MixinDecl { methods, .. } => self.scoped(|this| {
+                this.declare_members(methods);
+
+                for method in methods {
+                    this.member(method);
+                }
+            }),
+
+            StmtKind::ModuleDecl { declarations, .. } => {
+                self.scoped(|this| this.block(declarations))
+            }
+
+            StmtKind::InterfaceDecl { .. } | StmtKind::TypeAlias { .. } | StmtKind::Import(_) => {}
+
+            StmtKind::Assign { target, value, .. } => {
+                self.visit_expr(value);
+                self.visit_expr(target);
+            }
+
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.visit_expr(condition);
+                self.scoped(|this| this.block(then_branch));
+
+                if let Some(else_branch) = else_branch {
+                    self.scoped(|this| this.block(else_branch));
+                }
+            }
+
+            StmtKind::While { condition, body } => {
+                self.visit_expr(condition);
+                self.scoped(|this| this.block(body));
+            }
+
+            StmtKind::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                self.visit_expr(iterable);
+                self.scoped(|this| {
+                    this.bind(pattern, DefKind::Variable);
+                    this.scoped(|this| this.block(body));
+                });
+            }
+
+            StmtKind::Expression(expr) | StmtKind::Return(Some(expr)) => self.visit_expr(expr),
+            StmtKind::Return(None) | StmtKind::Break | StmtKind::Continue => {}
+        }
+    }
+}
+
+impl Visitor<'_> for Resolver<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.stmt(stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.use_name(expr.id, name, &expr.span),
+
+            ExprKind::StructLiteral { name, .. } => {
+                self.use_name(expr.id, name, &expr.span);
+                walk_expr(self, expr);
+            }
+
+            ExprKind::Block(body) => self.scoped(|this| this.block(body)),
+
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+
+                for arm in arms {
+                    self.scoped(|this| {
+                        this.bind(&arm.pattern, DefKind::Variable);
+                        this.visit_expr(&arm.body);
+                    });
+                }
+            }
+
+            ExprKind::Lambda { params, body } => self.deferring(|this| {
+                this.scoped(|this| {
+                    for param in params {
+                        this.bind(&param.pattern, DefKind::Parameter);
+                    }
+
+                    this.visit_expr(body);
+                })
+            }),
+
+            // spliced Luau can only name locals, which have no node of their own
+            ExprKind::Luau { bindings, .. } => {
+                for (name, span) in bindings {
+                    self.use_name(NodeId::DUMMY, name, span);
+                }
+            }
+
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+/// The Levenshtein distance between two names, counted in characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
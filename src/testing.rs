@@ -0,0 +1,34 @@
+use crate::ast::*;
+use crate::span::Span;
+
+/// A function marked `@test`.
+#[derive(Debug, PartialEq)]
+pub struct TestCase<'a> {
+    pub name: &'a str,
+    pub span: &'a Span,
+}
+
+/// Finds every `@test` function, including those inside modules, in source order.
+pub fn discover(program: &[Stmt]) -> Vec<TestCase<'_>> {
+    let mut tests = Vec::new();
+    collect(program, &mut tests);
+
+    tests
+}
+
+fn collect<'a>(stmts: &'a [Stmt], tests: &mut Vec<TestCase<'a>>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StmtKind::FunctionDecl { name, .. } if stmt.has_attribute(&AttributeKind::Test) => {
+                tests.push(TestCase {
+                    name,
+                    span: &stmt.span,
+                });
+            }
+
+            StmtKind::ModuleDecl { declarations, .. } => collect(declarations, tests),
+
+            _ => {}
+        }
+    }
+}
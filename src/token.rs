@@ -2,6 +2,7 @@ use crate::span::Span;
 use crate::tokentype::TokenType;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ErrorKind {
     UnexpectedCharacter(char),
     InvalidDecimal,
@@ -9,7 +10,8 @@ pub enum ErrorKind {
     InvalidEscape(char),
     UnterminatedEscapeSequence,
     UnterminatedString,
-    // UnterminatedBlockComment,
+    UnterminatedBlockComment,
+    UnterminatedLuauBlock,
 }
 
 impl ErrorKind {
@@ -20,8 +22,9 @@ impl ErrorKind {
             Self::InvalidOperator(_) => "E002".into(),
             Self::InvalidEscape(_) => "E003".into(),
             Self::UnterminatedString => "E004".into(),
-            // Self::UnterminatedBlockComment => "E005".into(),
+            Self::UnterminatedBlockComment => "E005".into(),
             Self::UnterminatedEscapeSequence => "E006".into(),
+            Self::UnterminatedLuauBlock => "E007".into(),
         }
     }
 
@@ -32,8 +35,9 @@ impl ErrorKind {
             Self::InvalidOperator(op) => format!("Invalid operator: {}", op),
             Self::InvalidEscape(c) => format!("Invalid escape sequence: \\{}", c),
             Self::UnterminatedString => "Unterminated string literal".into(),
-            // Self::UnterminatedBlockComment => "Unterminated block comment".into(),
+            Self::UnterminatedBlockComment => "Unterminated block comment".into(),
             Self::UnterminatedEscapeSequence => "Unterminated escape sequence".into(),
+            Self::UnterminatedLuauBlock => "Unterminated luau block".into(),
         }
     }
 
@@ -44,13 +48,15 @@ impl ErrorKind {
             Self::InvalidOperator(_) => "Invalid operator".into(),
             Self::InvalidEscape(_) => "Invalid escape sequence".into(),
             Self::UnterminatedString => "Unterminated string".into(),
-            // Self::UnterminatedBlockComment => "Unterminated block comment".into(),
+            Self::UnterminatedBlockComment => "Expected a closing '*/'".into(),
             Self::UnterminatedEscapeSequence => "Unterminated escape sequence".into(),
+            Self::UnterminatedLuauBlock => "Expected a closing '}'".into(),
         }
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiagnosticError {
     pub kind: ErrorKind,
     pub span: Span,
@@ -58,6 +64,7 @@ pub struct DiagnosticError {
 
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub token_type: TokenType,
     pub span: Span,
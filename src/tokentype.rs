@@ -1,16 +1,27 @@
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType {
     Number(String),
     Keyword(String),
     Identifier(String),
     String(String),
     Boolean(bool),
+    Nil,
+    LuauBlock(String),  // the raw body of `luau { ... }`, without the braces
+    DocComment(String), // the Markdown text of a `///` or `/** */` comment
 
     Semicolon,
     Colon,
     Arrow,
+    FatArrow,
     Dot,
+    DotDot,
+    DotDotEqual,
     Comma,
+    At,
+    Question,
+    QuestionDot,
+    QuestionQuestion,
 
     LeftParen,
     RightParen,
@@ -41,6 +52,10 @@ pub enum TokenType {
     Pipe,
     Caret,
 
+    // only produced by `Lexer::with_trivia`
+    Whitespace,
+    Comment(String), // the full comment, including its `//` or `/* */`
+
     Error(String),
     Eof,
 }
@@ -0,0 +1,1016 @@
+use crate::ast::*;
+use crate::node::Ident;
+
+/// Walks a borrowed AST. Every method defaults to the matching `walk_*`
+/// function, so implementors only override the nodes they care about and call
+/// `walk_*` themselves to keep descending.
+pub trait Visitor<'ast> {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        walk_pattern(self, pattern);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        walk_type(self, ty);
+    }
+
+    fn visit_parameter(&mut self, param: &'ast Parameter) {
+        walk_parameter(self, param);
+    }
+
+    fn visit_method_signature(&mut self, method: &'ast MethodSignature) {
+        walk_method_signature(self, method);
+    }
+
+    fn visit_attribute(&mut self, _attribute: &'ast Attribute) {}
+}
+
+/// Like `Visitor`, but with mutable access so a pass can rewrite nodes in place.
+pub trait VisitorMut {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        walk_pattern_mut(self, pattern);
+    }
+
+    fn visit_type(&mut self, ty: &mut Type) {
+        walk_type_mut(self, ty);
+    }
+
+    fn visit_parameter(&mut self, param: &mut Parameter) {
+        walk_parameter_mut(self, param);
+    }
+
+    fn visit_method_signature(&mut self, method: &mut MethodSignature) {
+        walk_method_signature_mut(self, method);
+    }
+
+    fn visit_attribute(&mut self, _attribute: &mut Attribute) {}
+}
+
+/// Consumes an AST and rebuilds it, bottom-up by default. Overriding a `fold_*`
+/// method replaces that node with whatever it returns.
+pub trait Fold {
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        walk_stmt_fold(self, stmt)
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        walk_expr_fold(self, expr)
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        walk_pattern_fold(self, pattern)
+    }
+
+    fn fold_type(&mut self, ty: Type) -> Type {
+        walk_type_fold(self, ty)
+    }
+
+    fn fold_parameter(&mut self, param: Parameter) -> Parameter {
+        walk_parameter_fold(self, param)
+    }
+
+    fn fold_method_signature(&mut self, method: MethodSignature) -> MethodSignature {
+        walk_method_signature_fold(self, method)
+    }
+
+    fn fold_attribute(&mut self, attribute: Attribute) -> Attribute {
+        attribute
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Stmt) {
+    match &stmt.kind {
+        StmtKind::VariableDecl {
+            attributes,
+            pattern,
+            type_annotation,
+            initializer,
+            ..
+        } => {
+            for attribute in attributes {
+                visitor.visit_attribute(attribute);
+            }
+
+            visitor.visit_pattern(pattern);
+
+            if let Some(ty) = type_annotation {
+                visitor.visit_type(ty);
+            }
+
+            if let Some(initializer) = initializer {
+                visitor.visit_expr(initializer);
+            }
+        }
+
+        StmtKind::FunctionDecl {
+            attributes,
+            params,
+            return_type,
+            body,
+            ..
+        } => {
+            for attribute in attributes {
+                visitor.visit_attribute(attribute);
+            }
+
+            for param in params {
+                visitor.visit_parameter(param);
+            }
+
+            if let Some(ty) = return_type {
+                visitor.visit_type(ty);
+            }
+
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::ClassDecl {
+            attributes,
+            fields,
+            methods,
+            static_fields,
+            static_methods,
+            ..
+        } => {
+            for attribute in attributes {
+                visitor.visit_attribute(attribute);
+            }
+
+            for member in [fields, static_fields, static_methods, methods] {
+                for stmt in member {
+                    visitor.visit_stmt(stmt);
+                }
+            }
+        }
+
+        StmtKind::InterfaceDecl { methods, .. } => {
+            for method in methods {
+                visitor.visit_method_signature(method);
+            }
+        }
+
+        StmtKind::MixinDecl { methods: body, .. }
+        | StmtKind::ModuleDecl {
+            declarations: body, ..
+        } => {
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::TypeAlias { ty, .. } => visitor.visit_type(ty),
+
+        StmtKind::Assign { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(condition);
+
+            for stmt in then_branch.iter().chain(else_branch.iter().flatten()) {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::While { condition, body } => {
+            visitor.visit_expr(condition);
+
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::For {
+            pattern,
+            iterable,
+            body,
+        } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expr(iterable);
+
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::Expression(expr) | StmtKind::Return(Some(expr)) => visitor.visit_expr(expr),
+
+        StmtKind::Import(_) | StmtKind::Return(None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expr) {
+    match &expr.kind {
+        ExprKind::Binary { left, right, .. }
+        | ExprKind::Range {
+            start: left,
+            end: right,
+            ..
+        } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+
+        ExprKind::Unary { expr, .. }
+        | ExprKind::Grouping(expr)
+        | ExprKind::PropertyAccess { object: expr, .. }
+        | ExprKind::OptionalPropertyAccess { object: expr, .. } => visitor.visit_expr(expr),
+
+        ExprKind::Tuple(items) => {
+            for item in items {
+                visitor.visit_expr(item);
+            }
+        }
+
+        ExprKind::StructLiteral { fields, .. } | ExprKind::Record(fields) => {
+            for (_, value) in fields {
+                visitor.visit_expr(value);
+            }
+        }
+
+        ExprKind::Block(body) => {
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        ExprKind::Call { callee, arguments } => {
+            visitor.visit_expr(callee);
+
+            for argument in arguments {
+                visitor.visit_expr(argument);
+            }
+        }
+
+        ExprKind::Lambda { params, body } => {
+            for param in params {
+                visitor.visit_parameter(param);
+            }
+
+            visitor.visit_expr(body);
+        }
+
+        ExprKind::Luau { return_type, .. } => {
+            if let Some(ty) = return_type {
+                visitor.visit_type(ty);
+            }
+        }
+
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+
+            for arm in arms {
+                visitor.visit_pattern(&arm.pattern);
+                visitor.visit_expr(&arm.body);
+            }
+        }
+
+        ExprKind::Literal(_) | ExprKind::Identifier(_) => {}
+    }
+}
+
+pub fn walk_pattern<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, pattern: &'ast Pattern) {
+    match &pattern.kind {
+        PatternKind::Tuple(items) | PatternKind::Constructor { args: items, .. } => {
+            for item in items {
+                visitor.visit_pattern(item);
+            }
+        }
+
+        PatternKind::Record { fields, .. } => {
+            for field in fields {
+                visitor.visit_pattern(&field.pattern);
+            }
+        }
+
+        PatternKind::Wildcard | PatternKind::Identifier(_) | PatternKind::Literal(_) => {}
+    }
+}
+
+pub fn walk_type<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, ty: &'ast Type) {
+    match ty {
+        Type::Function(params, return_type) => {
+            for param in params {
+                visitor.visit_type(param);
+            }
+
+            visitor.visit_type(return_type);
+        }
+
+        Type::Tuple(items)
+        | Type::Generic { params: items, .. }
+        | Type::Union(items)
+        | Type::Variant { fields: items, .. } => {
+            for item in items {
+                visitor.visit_type(item);
+            }
+        }
+
+        Type::Optional(inner) | Type::Array(inner) => visitor.visit_type(inner),
+
+        Type::Record { fields, indexer } => {
+            for field in fields {
+                visitor.visit_type(&field.ty);
+            }
+
+            if let Some(indexer) = indexer {
+                visitor.visit_type(&indexer.0);
+                visitor.visit_type(&indexer.1);
+            }
+        }
+
+        Type::Simple(_) | Type::Literal(_) => {}
+    }
+}
+
+pub fn walk_parameter<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, param: &'ast Parameter) {
+    visitor.visit_pattern(&param.pattern);
+
+    if let Some(ty) = &param.type_annotation {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_method_signature<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    method: &'ast MethodSignature,
+) {
+    for param in &method.params {
+        visitor.visit_parameter(param);
+    }
+
+    if let Some(ty) = &method.return_type {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match &mut stmt.kind {
+        StmtKind::VariableDecl {
+            attributes,
+            pattern,
+            type_annotation,
+            initializer,
+            ..
+        } => {
+            for attribute in attributes {
+                visitor.visit_attribute(attribute);
+            }
+
+            visitor.visit_pattern(pattern);
+
+            if let Some(ty) = type_annotation {
+                visitor.visit_type(ty);
+            }
+
+            if let Some(initializer) = initializer {
+                visitor.visit_expr(initializer);
+            }
+        }
+
+        StmtKind::FunctionDecl {
+            attributes,
+            params,
+            return_type,
+            body,
+            ..
+        } => {
+            for attribute in attributes {
+                visitor.visit_attribute(attribute);
+            }
+
+            for param in params {
+                visitor.visit_parameter(param);
+            }
+
+            if let Some(ty) = return_type {
+                visitor.visit_type(ty);
+            }
+
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::ClassDecl {
+            attributes,
+            fields,
+            methods,
+            static_fields,
+            static_methods,
+            ..
+        } => {
+            for attribute in attributes {
+                visitor.visit_attribute(attribute);
+            }
+
+            for member in [fields, static_fields, static_methods, methods] {
+                for stmt in member {
+                    visitor.visit_stmt(stmt);
+                }
+            }
+        }
+
+        StmtKind::InterfaceDecl { methods, .. } => {
+            for method in methods {
+                visitor.visit_method_signature(method);
+            }
+        }
+
+        StmtKind::MixinDecl { methods: body, .. }
+        | StmtKind::ModuleDecl {
+            declarations: body, ..
+        } => {
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::TypeAlias { ty, .. } => visitor.visit_type(ty),
+
+        StmtKind::Assign { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(condition);
+
+            for stmt in then_branch
+                .iter_mut()
+                .chain(else_branch.iter_mut().flatten())
+            {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::While { condition, body } => {
+            visitor.visit_expr(condition);
+
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::For {
+            pattern,
+            iterable,
+            body,
+        } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expr(iterable);
+
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        StmtKind::Expression(expr) | StmtKind::Return(Some(expr)) => visitor.visit_expr(expr),
+
+        StmtKind::Import(_) | StmtKind::Return(None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Binary { left, right, .. }
+        | ExprKind::Range {
+            start: left,
+            end: right,
+            ..
+        } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+
+        ExprKind::Unary { expr, .. }
+        | ExprKind::Grouping(expr)
+        | ExprKind::PropertyAccess { object: expr, .. }
+        | ExprKind::OptionalPropertyAccess { object: expr, .. } => visitor.visit_expr(expr),
+
+        ExprKind::Tuple(items) => {
+            for item in items {
+                visitor.visit_expr(item);
+            }
+        }
+
+        ExprKind::StructLiteral { fields, .. } | ExprKind::Record(fields) => {
+            for (_, value) in fields {
+                visitor.visit_expr(value);
+            }
+        }
+
+        ExprKind::Block(body) => {
+            for stmt in body {
+                visitor.visit_stmt(stmt);
+            }
+        }
+
+        ExprKind::Call { callee, arguments } => {
+            visitor.visit_expr(callee);
+
+            for argument in arguments {
+                visitor.visit_expr(argument);
+            }
+        }
+
+        ExprKind::Lambda { params, body } => {
+            for param in params {
+                visitor.visit_parameter(param);
+            }
+
+            visitor.visit_expr(body);
+        }
+
+        ExprKind::Luau { return_type, .. } => {
+            if let Some(ty) = return_type {
+                visitor.visit_type(ty);
+            }
+        }
+
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+
+            for arm in arms {
+                visitor.visit_pattern(&mut arm.pattern);
+                visitor.visit_expr(&mut arm.body);
+            }
+        }
+
+        ExprKind::Literal(_) | ExprKind::Identifier(_) => {}
+    }
+}
+
+pub fn walk_pattern_mut<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    match &mut pattern.kind {
+        PatternKind::Tuple(items) | PatternKind::Constructor { args: items, .. } => {
+            for item in items {
+                visitor.visit_pattern(item);
+            }
+        }
+
+        PatternKind::Record { fields, .. } => {
+            for field in fields {
+                visitor.visit_pattern(&mut field.pattern);
+            }
+        }
+
+        PatternKind::Wildcard | PatternKind::Identifier(_) | PatternKind::Literal(_) => {}
+    }
+}
+
+pub fn walk_type_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ty: &mut Type) {
+    match ty {
+        Type::Function(params, return_type) => {
+            for param in params {
+                visitor.visit_type(param);
+            }
+
+            visitor.visit_type(return_type);
+        }
+
+        Type::Tuple(items)
+        | Type::Generic { params: items, .. }
+        | Type::Union(items)
+        | Type::Variant { fields: items, .. } => {
+            for item in items {
+                visitor.visit_type(item);
+            }
+        }
+
+        Type::Optional(inner) | Type::Array(inner) => visitor.visit_type(inner),
+
+        Type::Record { fields, indexer } => {
+            for field in fields {
+                visitor.visit_type(&mut field.ty);
+            }
+
+            if let Some(indexer) = indexer {
+                visitor.visit_type(&mut indexer.0);
+                visitor.visit_type(&mut indexer.1);
+            }
+        }
+
+        Type::Simple(_) | Type::Literal(_) => {}
+    }
+}
+
+pub fn walk_parameter_mut<V: VisitorMut + ?Sized>(visitor: &mut V, param: &mut Parameter) {
+    visitor.visit_pattern(&mut param.pattern);
+
+    if let Some(ty) = &mut param.type_annotation {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_method_signature_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    method: &mut MethodSignature,
+) {
+    for param in &mut method.params {
+        visitor.visit_parameter(param);
+    }
+
+    if let Some(ty) = &mut method.return_type {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_stmt_fold<F: Fold + ?Sized>(folder: &mut F, stmt: Stmt) -> Stmt {
+    let kind = match stmt.kind {
+        StmtKind::VariableDecl {
+            docs,
+            attributes,
+            is_mutable,
+            pattern,
+            type_annotation,
+            initializer,
+        } => StmtKind::VariableDecl {
+            docs,
+            attributes: fold_attributes(folder, attributes),
+            is_mutable,
+            pattern: folder.fold_pattern(pattern),
+            type_annotation: type_annotation.map(|ty| folder.fold_type(ty)),
+            initializer: initializer.map(|expr| folder.fold_expr(expr)),
+        },
+
+        StmtKind::FunctionDecl {
+            docs,
+            attributes,
+            name,
+            type_params,
+            bounds,
+            params,
+            return_type,
+            body,
+        } => StmtKind::FunctionDecl {
+            docs,
+            attributes: fold_attributes(folder, attributes),
+            name,
+            type_params,
+            bounds,
+            params: fold_parameters(folder, params),
+            return_type: return_type.map(|ty| folder.fold_type(ty)),
+            body: fold_stmts(folder, body),
+        },
+
+        StmtKind::ClassDecl {
+            docs,
+            attributes,
+            name,
+            implements,
+            includes,
+            fields,
+            methods,
+            static_fields,
+            static_methods,
+        } => StmtKind::ClassDecl {
+            docs,
+            attributes: fold_attributes(folder, attributes),
+            name,
+            implements,
+            includes,
+            fields: fold_stmts(folder, fields),
+            static_fields: fold_stmts(folder, static_fields),
+            static_methods: fold_stmts(folder, static_methods),
+            methods: fold_stmts(folder, methods),
+        },
+
+        StmtKind::InterfaceDecl {
+            docs,
+            name,
+            methods,
+        } => StmtKind::InterfaceDecl {
+            docs,
+            name,
+            methods: methods
+                .into_iter()
+                .map(|method| folder.fold_method_signature(method))
+                .collect(),
+        },
+
+        StmtKind::MixinDecl {
+            docs,
+            name,
+            methods,
+        } => StmtKind::MixinDecl {
+            docs,
+            name,
+            methods: fold_stmts(folder, methods),
+        },
+
+        StmtKind::ModuleDecl {
+            docs,
+            name,
+            declarations,
+        } => StmtKind::ModuleDecl {
+            docs,
+            name,
+            declarations: fold_stmts(folder, declarations),
+        },
+
+        StmtKind::TypeAlias {
+            docs,
+            name,
+            params,
+            ty,
+        } => StmtKind::TypeAlias {
+            docs,
+            name,
+            params,
+            ty: folder.fold_type(ty),
+        },
+
+        StmtKind::Assign { target, op, value } => StmtKind::Assign {
+            target: folder.fold_expr(target),
+            op,
+            value: folder.fold_expr(value),
+        },
+
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => StmtKind::If {
+            condition: folder.fold_expr(condition),
+            then_branch: fold_stmts(folder, then_branch),
+            else_branch: else_branch.map(|body| fold_stmts(folder, body)),
+        },
+
+        StmtKind::While { condition, body } => StmtKind::While {
+            condition: folder.fold_expr(condition),
+            body: fold_stmts(folder, body),
+        },
+
+        StmtKind::For {
+            pattern,
+            iterable,
+            body,
+        } => StmtKind::For {
+            pattern: folder.fold_pattern(pattern),
+            iterable: folder.fold_expr(iterable),
+            body: fold_stmts(folder, body),
+        },
+
+        StmtKind::Expression(expr) => StmtKind::Expression(folder.fold_expr(expr)),
+        StmtKind::Return(expr) => StmtKind::Return(expr.map(|expr| folder.fold_expr(expr))),
+
+        kind @ (StmtKind::Import(_) | StmtKind::Break | StmtKind::Continue) => kind,
+    };
+
+    Stmt { kind, ..stmt }
+}
+
+pub fn walk_expr_fold<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Expr {
+    let mut fold = |expr: Box<Expr>| Box::new(folder.fold_expr(*expr));
+
+    let kind = match expr.kind {
+        ExprKind::Binary { left, op, right } => {
+            let left = fold(left);
+
+            ExprKind::Binary {
+                left,
+                op,
+                right: fold(right),
+            }
+        }
+
+        ExprKind::Unary { op, expr } => ExprKind::Unary {
+            op,
+            expr: fold(expr),
+        },
+        ExprKind::Grouping(expr) => ExprKind::Grouping(fold(expr)),
+
+        ExprKind::PropertyAccess { object, name } => ExprKind::PropertyAccess {
+            object: fold(object),
+            name,
+        },
+
+        ExprKind::OptionalPropertyAccess { object, name } => ExprKind::OptionalPropertyAccess {
+            object: fold(object),
+            name,
+        },
+
+        ExprKind::Range {
+            start,
+            end,
+            inclusive,
+        } => {
+            let start = fold(start);
+
+            ExprKind::Range {
+                start,
+                end: fold(end),
+                inclusive,
+            }
+        }
+
+        ExprKind::Tuple(items) => ExprKind::Tuple(fold_exprs(folder, items)),
+
+        ExprKind::StructLiteral { name, fields } => ExprKind::StructLiteral {
+            name,
+            fields: fold_fields(folder, fields),
+        },
+
+        ExprKind::Record(fields) => ExprKind::Record(fold_fields(folder, fields)),
+        ExprKind::Block(body) => ExprKind::Block(fold_stmts(folder, body)),
+
+        ExprKind::Call { callee, arguments } => ExprKind::Call {
+            callee: Box::new(folder.fold_expr(*callee)),
+            arguments: fold_exprs(folder, arguments),
+        },
+
+        ExprKind::Lambda { params, body } => ExprKind::Lambda {
+            params: fold_parameters(folder, params),
+            body: Box::new(folder.fold_expr(*body)),
+        },
+
+        ExprKind::Luau {
+            return_type,
+            bindings,
+            body,
+            body_span,
+        } => ExprKind::Luau {
+            return_type: return_type.map(|ty| folder.fold_type(ty)),
+            bindings,
+            body,
+            body_span,
+        },
+
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: Box::new(folder.fold_expr(*scrutinee)),
+            arms: arms
+                .into_iter()
+                .map(|arm| MatchArm {
+                    pattern: folder.fold_pattern(arm.pattern),
+                    body: folder.fold_expr(arm.body),
+                    span: arm.span,
+                })
+                .collect(),
+        },
+
+        kind @ (ExprKind::Literal(_) | ExprKind::Identifier(_)) => kind,
+    };
+
+    Expr { kind, ..expr }
+}
+
+pub fn walk_pattern_fold<F: Fold + ?Sized>(folder: &mut F, pattern: Pattern) -> Pattern {
+    let mut fold = |items: Vec<Pattern>| -> Vec<Pattern> {
+        items
+            .into_iter()
+            .map(|item| folder.fold_pattern(item))
+            .collect()
+    };
+
+    let kind = match pattern.kind {
+        PatternKind::Tuple(items) => PatternKind::Tuple(fold(items)),
+
+        PatternKind::Constructor { name, args } => PatternKind::Constructor {
+            name,
+            args: fold(args),
+        },
+
+        PatternKind::Record { name, fields } => PatternKind::Record {
+            name,
+            fields: fields
+                .into_iter()
+                .map(|field| FieldPattern {
+                    name: field.name,
+                    pattern: folder.fold_pattern(field.pattern),
+                })
+                .collect(),
+        },
+
+        kind @ (PatternKind::Wildcard | PatternKind::Identifier(_) | PatternKind::Literal(_)) => {
+            kind
+        }
+    };
+
+    Pattern { kind, ..pattern }
+}
+
+pub fn walk_type_fold<F: Fold + ?Sized>(folder: &mut F, ty: Type) -> Type {
+    let mut fold = |types: Vec<Type>| -> Vec<Type> {
+        types.into_iter().map(|ty| folder.fold_type(ty)).collect()
+    };
+
+    match ty {
+        Type::Function(params, return_type) => {
+            let params = fold(params);
+
+            Type::Function(params, Box::new(folder.fold_type(*return_type)))
+        }
+
+        Type::Tuple(items) => Type::Tuple(fold(items)),
+
+        Type::Generic { name, params } => Type::Generic {
+            name,
+            params: fold(params),
+        },
+
+        Type::Optional(inner) => Type::Optional(Box::new(folder.fold_type(*inner))),
+        Type::Array(element) => Type::Array(Box::new(folder.fold_type(*element))),
+        Type::Union(members) => Type::Union(fold(members)),
+        Type::Variant { name, fields } => Type::Variant {
+            name,
+            fields: fold(fields),
+        },
+
+        Type::Record { fields, indexer } => Type::Record {
+            fields: fields
+                .into_iter()
+                .map(|field| FieldType {
+                    ty: folder.fold_type(field.ty),
+                    ..field
+                })
+                .collect(),
+            indexer: indexer.map(|indexer| {
+                let (key, value) = *indexer;
+
+                Box::new((folder.fold_type(key), folder.fold_type(value)))
+            }),
+        },
+
+        ty @ (Type::Simple(_) | Type::Literal(_)) => ty,
+    }
+}
+
+pub fn walk_parameter_fold<F: Fold + ?Sized>(folder: &mut F, param: Parameter) -> Parameter {
+    Parameter {
+        pattern: folder.fold_pattern(param.pattern),
+        type_annotation: param.type_annotation.map(|ty| folder.fold_type(ty)),
+    }
+}
+
+pub fn walk_method_signature_fold<F: Fold + ?Sized>(
+    folder: &mut F,
+    method: MethodSignature,
+) -> MethodSignature {
+    MethodSignature {
+        params: fold_parameters(folder, method.params),
+        return_type: method.return_type.map(|ty| folder.fold_type(ty)),
+        ..method
+    }
+}
+
+fn fold_stmts<F: Fold + ?Sized>(folder: &mut F, stmts: Vec<Stmt>) -> Vec<Stmt> {
+    stmts
+        .into_iter()
+        .map(|stmt| folder.fold_stmt(stmt))
+        .collect()
+}
+
+fn fold_exprs<F: Fold + ?Sized>(folder: &mut F, exprs: Vec<Expr>) -> Vec<Expr> {
+    exprs
+        .into_iter()
+        .map(|expr| folder.fold_expr(expr))
+        .collect()
+}
+
+fn fold_fields<F: Fold + ?Sized>(folder: &mut F, fields: Vec<(Ident, Expr)>) -> Vec<(Ident, Expr)> {
+    fields
+        .into_iter()
+        .map(|(name, value)| (name, folder.fold_expr(value)))
+        .collect()
+}
+
+fn fold_parameters<F: Fold + ?Sized>(folder: &mut F, params: Vec<Parameter>) -> Vec<Parameter> {
+    params
+        .into_iter()
+        .map(|param| folder.fold_parameter(param))
+        .collect()
+}
+
+fn fold_attributes<F: Fold + ?Sized>(folder: &mut F, attributes: Vec<Attribute>) -> Vec<Attribute> {
+    attributes
+        .into_iter()
+        .map(|attribute| folder.fold_attribute(attribute))
+        .collect()
+}
@@ -0,0 +1,1285 @@
+use exx::{
+    ast::StmtKind,
+    checker::{self, Ty},
+    diagnostic::Diagnostic,
+    lexer::Lexer,
+    node::Tables,
+    parser::{Parser, ParserToken},
+};
+use pretty_assertions::assert_eq;
+
+fn check(source: &str) -> Vec<Diagnostic> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse");
+
+    checker::check(&program)
+}
+
+const SHAPE: &str = "interface Shape { fn area(self) -> number; }\n";
+
+#[test]
+fn test_class_implementing_interface() {
+    let source = format!(
+        "{}class Circle implements Shape {{
+    let radius: number;
+
+    fn area(self) -> number {{
+        return self.radius * self.radius * 3;
+    }}
+}}",
+        SHAPE
+    );
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_unknown_interface() {
+    let diagnostics = check("class Circle implements Shape { }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E200");
+    assert_eq!(diagnostics[0].message, "Cannot find interface `Shape`");
+}
+
+#[test]
+fn test_implementing_a_class() {
+    let diagnostics = check("class Base { } class Circle implements Base { }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E200");
+    assert_eq!(
+        diagnostics[0].message,
+        "`Base` is a class, not an interface"
+    );
+}
+
+#[test]
+fn test_missing_interface_method() {
+    let source = format!("{}class Circle implements Shape {{ }}", SHAPE);
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E201");
+    assert_eq!(
+        diagnostics[0].message,
+        "Class `Circle` is missing method `area` required by interface `Shape`"
+    );
+
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 45);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 18);
+}
+
+#[test]
+fn test_mismatched_method_signature() {
+    let source = format!(
+        "{}class Circle implements Shape {{ fn area(self, scale: number) -> number {{ return scale; }} }}",
+        SHAPE
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E202");
+    assert_eq!(
+        diagnostics[0].message,
+        "Method `area` of class `Circle` does not match interface `Shape`: expected `(self) -> number`, found `(self, number) -> number`"
+    );
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "found `(self, number) -> number`"
+    );
+    assert_eq!(
+        diagnostics[0].labels[1].message,
+        "expected `(self) -> number`"
+    );
+}
+
+#[test]
+fn test_mismatched_return_type() {
+    let source = format!(
+        "{}class Circle implements Shape {{ fn area(self) -> string {{ return \"\"; }} }}",
+        SHAPE
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E202");
+    assert_eq!(
+        diagnostics[0].message,
+        "Method `area` of class `Circle` does not match interface `Shape`: expected `(self) -> number`, found `(self) -> string`"
+    );
+}
+
+#[test]
+fn test_class_without_implements_is_not_an_interface_value() {
+    let source = format!(
+        "{}class Square {{ let side: number; fn area(self) -> number {{ return 1; }} }}
+let shape: Shape = Square {{ side: 2 }};",
+        SHAPE
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E203");
+    assert_eq!(
+        diagnostics[0].message,
+        "`Square` does not satisfy interface `Shape`"
+    );
+
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "class `Square` does not declare `implements Shape`"
+    );
+}
+
+#[test]
+fn test_records_satisfy_interfaces_structurally() {
+    let source = format!(
+        "{}let shape: Shape = {{ area: (self) -> 1 }};
+let broken: Shape = {{ perimeter: (self) -> 4 }};",
+        SHAPE
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].labels[0].message, "missing method `area`");
+}
+
+#[test]
+fn test_interface_arguments_and_returns() {
+    let source = format!(
+        "{}fn total(shape: Shape) -> number {{ return shape.area(); }}
+fn make() -> Shape {{ return 5; }}
+let x = total(\"circle\");",
+        SHAPE
+    );
+
+    let diagnostics = check(&source);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            "`number` does not satisfy interface `Shape`",
+            "`string` does not satisfy interface `Shape`",
+        ]
+    );
+}
+
+const MIXINS: &str = "mixin Named { fn name(self) -> string { return \"\"; } }
+mixin Labeled { fn name(self) -> string { return \"\"; } }
+";
+
+#[test]
+fn test_mixin_methods_satisfy_interfaces() {
+    let source = "interface HasName { fn name(self) -> string; }
+mixin Named { fn name(self) -> string { return \"\"; } }
+class Person implements HasName includes Named { }";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_unknown_mixin() {
+    let diagnostics = check("class Person includes Named { }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E204");
+    assert_eq!(diagnostics[0].message, "Cannot find mixin `Named`");
+}
+
+#[test]
+fn test_mixin_conflicting_with_class() {
+    let source = format!(
+        "{}class Person includes Named {{ fn name(self) -> string {{ return \"\"; }} }}",
+        MIXINS
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E205");
+    assert_eq!(
+        diagnostics[0].message,
+        "Method `name` of class `Person` conflicts with mixin `Named`"
+    );
+
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 142);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 14);
+}
+
+#[test]
+fn test_conflicting_mixins() {
+    let source = format!("{}class Person includes Named, Labeled {{ }}", MIXINS);
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Mixins `Named` and `Labeled` both define method `name` included by class `Person`"
+    );
+
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 14);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 71);
+}
+
+const COUNTER: &str = "class Counter {
+    var count: number;
+
+    static fn new() -> Counter { return Counter { count: 0 }; }
+    fn get(self) -> number { return self.count; }
+}
+";
+
+#[test]
+fn test_receivers_are_checked() {
+    let source = format!("{}let c = Counter.new(); let n = c.get();", COUNTER);
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_instance_method_without_receiver() {
+    let source = format!("{}let n = Counter.get();", COUNTER);
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E206");
+    assert_eq!(
+        diagnostics[0].message,
+        "Instance method `get` of class `Counter` cannot be called without a receiver"
+    );
+}
+
+#[test]
+fn test_bare_method_call_inside_class() {
+    let diagnostics = check("class Counter { fn get(self) { } fn twice(self) { get(); } }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E206");
+    assert_eq!(diagnostics[0].notes, vec!["help: call it as `self.get()`"]);
+}
+
+#[test]
+fn test_static_method_through_instance() {
+    let source = format!("{}let c = Counter.new(); let d = c.new();", COUNTER);
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E207");
+}
+
+#[test]
+fn test_self_has_the_class_type() {
+    let source = "interface Shape { fn area(self) -> number; }
+fn measure(shape: Shape) -> number { return shape.area(); }
+class Square {
+    let side: number;
+    fn area(self) -> number { return measure(self); }
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "`Square` does not satisfy interface `Shape`"
+    );
+}
+
+#[test]
+fn test_luau_block_bindings_and_type() {
+    let source = "interface Shape { fn area(self) -> number; }
+let side = 2;
+let shape: Shape = luau<number>(side, missing) { return side };";
+
+    let diagnostics = check(source);
+    let codes: Vec<_> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+
+    assert_eq!(codes, vec!["E208", "E203"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot find local `missing` to pass into luau block"
+    );
+}
+
+#[test]
+fn test_deprecation_warnings() {
+    let source = "@deprecated(\"use area\") fn size() -> number { return 1; }
+class Grid {
+    @deprecated static fn old() -> Grid { return Grid { }; }
+}
+let a = size();
+let b = Grid.old();
+fn shadowed(size: number) -> number { return size; }";
+
+    let diagnostics = check(source);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+    assert_eq!(
+        messages,
+        vec!["`size` is deprecated: use area", "`old` is deprecated"]
+    );
+
+    assert!(!diagnostics[0].is_error());
+    assert_eq!(diagnostics[0].code, "W001");
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 0);
+}
+
+#[test]
+fn test_invalid_inline_and_test_functions() {
+    let source = "@inline fn twice(x: number) -> number { let y = x * 2; return y; }
+@test fn checks(x: number) { }
+class Grid { @inline fn size(self) -> number { return 1; } }";
+
+    let diagnostics = check(source);
+    let labels: Vec<_> = diagnostics
+        .iter()
+        .map(|d| d.labels[0].message.as_str())
+        .collect();
+
+    assert_eq!(
+        labels,
+        vec![
+            "an inlined function's body must be a single `return`",
+            "tests can't take parameters",
+            "methods can't be inlined",
+        ]
+    );
+}
+
+const POINT: &str = "class Point { let x: number; }\n";
+
+#[test]
+fn test_field_of_optional_requires_narrowing() {
+    let diagnostics = check(&format!("{}fn f(p: Point?) {{ p.x; }}", POINT));
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E210");
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot access `x` on optional type `Point?` without narrowing"
+    );
+}
+
+#[test]
+fn test_optional_access_and_narrowing() {
+    let source = format!(
+        "{}fn f(p: Point?, q: Point?) -> number {{
+    let a = p?.x;
+    if p != nil {{
+        return p.x;
+    }}
+    if q == nil {{
+        return 0;
+    }} else {{
+        return q.x;
+    }}
+}}",
+        POINT
+    );
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_optional_values_need_narrowing_to_fit_plain_ones() {
+    let source = "fn find(n: number) -> number? { return nil; }
+fn g(n: number) { }
+let a: number = find(1);
+let b = find(1) + 1;
+g(find(2));
+let c: number? = 1;";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics.iter().all(|d| d.code == "E212"));
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.labels[0].message == "expected `number`, found `number?`")
+    );
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|d| d.labels[0].span.start.line)
+            .collect::<Vec<_>>(),
+        [3, 4, 5]
+    );
+}
+
+#[test]
+fn test_optional_chain_result_is_optional() {
+    let source = format!("{}fn f(p: Point?) {{ let a = p?.x; a.y; }}", POINT);
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot access `y` on optional type `number?` without narrowing"
+    );
+}
+
+#[test]
+fn test_coalesce_unwraps_optional() {
+    let source = format!(
+        "{}fn f(p: Point?) -> number {{ return (p ?? Point {{ x: 0 }}).x; }}",
+        POINT
+    );
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_readonly_record_field() {
+    let source = "type User = { readonly name: string, age?: number };
+fn rename(user: User) {
+    user.age = 3;
+    user.name = \"bob\";
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E211");
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot assign to readonly field `name`"
+    );
+}
+
+#[test]
+fn test_aliases_resolve_through_generics_and_indexers() {
+    let source = "type Box<T> = { value: T };
+type Scores = { [string]: Box<number?> };
+fn f(scores: Scores) { scores.alice.value.x; }";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot access `x` on optional type `number?` without narrowing"
+    );
+}
+
+#[test]
+fn test_recursive_alias_terminates() {
+    assert!(check("type Node = { next: Node? };\nfn f(n: Node) { n.next?.next; }").is_empty());
+}
+
+#[test]
+fn test_functions_are_generalised() {
+    let source = "fn identity(x) { return x; }
+fn typed<T>(x: T) -> T { return x; }
+let a: number = identity(10);
+let b: string = identity(\"hi\");
+let c: string = typed(\"hi\");
+let d: number = typed(1);";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_let_bindings_are_generalised() {
+    assert!(
+        check("let id = (x) -> x; let a: number = id(1); let b: string = id(\"s\");").is_empty()
+    );
+}
+
+#[test]
+fn test_var_bindings_are_not_generalised() {
+    let diagnostics = check("var id = (x) -> x; id(1); id(\"s\");");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E212");
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `number`, found `string`"
+    );
+}
+
+#[test]
+fn test_mismatch_reports_both_types() {
+    let diagnostics = check("let x: number = \"hi\";");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E212");
+    assert_eq!(diagnostics[0].message, "Mismatched types");
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `number`, found `string`"
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 16);
+}
+
+#[test]
+fn test_parameter_types_are_inferred_from_their_use() {
+    let diagnostics = check("fn double(n) { return n * 2; }\ndouble(\"two\");");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `number`, found `string`"
+    );
+}
+
+#[test]
+fn test_type_parameters_are_rigid() {
+    let diagnostics = check("fn broken<T>(x: T) -> T { return 1; }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `T`, found `number`"
+    );
+}
+
+#[test]
+fn test_lambda_types_are_inferred() {
+    let source = "let apply = (f, x) -> f(x);";
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens).parse_program().unwrap();
+    let mut tables = Tables::new(&program);
+
+    assert!(checker::check_with(&program, &mut tables).is_empty());
+
+    let StmtKind::VariableDecl {
+        initializer: Some(lambda),
+        ..
+    } = &program[0].kind
+    else {
+        panic!("expected a declaration");
+    };
+
+    // `((a) -> b, a) -> b`
+    let Some(Ty::Function(params, result)) = tables.types.get(lambda.id) else {
+        panic!("expected a function type");
+    };
+    let Ty::Function(f_params, f_result) = &params[0] else {
+        panic!("expected `f` to be a function");
+    };
+
+    assert_eq!(f_params[..], params[1..]);
+    assert_eq!(f_result, result);
+    assert!(matches!(params[1], Ty::Var(_)));
+    assert!(matches!(**result, Ty::Var(_)));
+}
+
+#[test]
+fn test_self_application_is_rejected() {
+    let diagnostics = check("let f = (x) -> x(x);");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E212");
+}
+
+#[test]
+fn test_plus_joins_strings() {
+    let diagnostics = check("let a: string = \"n = \" + \"1\";\nlet b = \"n = \" + 1;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `string`, found `number`"
+    );
+}
+
+#[test]
+fn test_let_fields_are_immutable_outside_the_constructor() {
+    let source = "class Account {
+    let id: number;
+    var balance: number;
+    static let bank: string = \"exx\";
+
+    static fn new(id: number) -> Account {
+        var account = Account { id: 0, balance: 0 };
+        account.id = id;
+        return account;
+    }
+
+    fn deposit(self, amount: number) {
+        self.balance += amount;
+        self.id = 0;
+    }
+}
+
+Account.bank = \"other\";";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].code, "E213");
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot assign to immutable field `id` of class `Account`"
+    );
+    assert_eq!(
+        diagnostics[0].notes[0],
+        "`let` fields can only be assigned in the constructor, `Account.new`"
+    );
+    assert_eq!(
+        diagnostics[1].message,
+        "Cannot assign to immutable field `bank` of class `Account`"
+    );
+}
+
+const OPTION: &str = "type Option<T> = Some(T) | None;\n";
+
+#[test]
+fn test_exhaustive_match_over_a_data_type() {
+    let source = format!(
+        "{}fn unwrap(value: Option<number>) -> number {{
+    return match value {{
+        Some(n) => n,
+        None => 0,
+    }};
+}}",
+        OPTION
+    );
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_non_exhaustive_match_lists_the_missing_constructor() {
+    let source = format!(
+        "{}fn unwrap(value: Option<number>) -> number {{
+    return match value {{ Some(n) => n }};
+}}",
+        OPTION
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E214");
+    assert_eq!(diagnostics[0].message, "Non-exhaustive match");
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "pattern `None` not covered"
+    );
+    assert_eq!(
+        diagnostics[0].notes[0],
+        "the value being matched has type `Option<number>`"
+    );
+}
+
+#[test]
+fn test_match_over_a_string_literal_union() {
+    let source = "type Direction = \"north\" | \"south\" | \"east\" | \"west\";
+fn turn(direction: Direction) -> number {
+    return match direction {
+        \"north\" => 0,
+        \"east\" => 90,
+    };
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "patterns `\"south\"` and `\"west\"` not covered"
+    );
+}
+
+#[test]
+fn test_match_over_nested_patterns() {
+    let source = format!(
+        "{}fn both(pair: (Option<boolean>, boolean)) -> number {{
+    return match pair {{
+        (Some(true), _) => 1,
+        (None, true) => 2,
+    }};
+}}",
+        OPTION
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "patterns `(Some(false), _)` and `(None, false)` not covered"
+    );
+}
+
+#[test]
+fn test_unreachable_match_arm() {
+    let source = "fn describe(flag: boolean) -> string {
+    return match flag {
+        true => \"yes\",
+        _ => \"no\",
+        false => \"never\",
+    };
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "W002");
+    assert_eq!(diagnostics[0].message, "Unreachable pattern");
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "this arm is never reached"
+    );
+}
+
+#[test]
+fn test_string_literal_union_rejects_other_strings() {
+    let diagnostics = check("type Direction = \"north\" | \"south\";\nlet d: Direction = \"up\";");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E212");
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `\"north\" | \"south\"`, found `\"up\"`"
+    );
+}
+
+const PERSON: &str = "class Person {
+    let name: string;
+    var age: number;
+    var nickname = \"\";
+    static let species = \"human\";
+
+    fn greet(self) -> string { return self.name + self.nickname; }
+}
+";
+
+#[test]
+fn test_struct_literal_fields_are_checked() {
+    let source = format!(
+        "{}let a = Person {{ name: \"Ada\", age: 36 }};
+let b = Person {{ name: \"Ada\", age: \"36\", nickname: \"A\" }};
+let c = Person {{ name: \"Ada\", agee: 36, species: \"cat\" }};
+let d = Person {{ }};",
+        PERSON
+    );
+
+    let diagnostics = check(&source);
+    let messages: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.code.as_str(), diagnostic.message.as_str()))
+        .collect();
+
+    assert_eq!(
+        messages,
+        [
+            ("E212", "Mismatched types"),
+            ("E217", "Class `Person` has no field `agee`"),
+            ("E217", "Class `Person` has no field `species`"),
+            ("E218", "Missing field `age` in `Person`"),
+            ("E218", "Missing fields `name` and `age` in `Person`"),
+        ]
+    );
+    assert_eq!(diagnostics[1].notes, ["help: did you mean `age`?"]);
+    assert_eq!(
+        diagnostics[2].notes,
+        ["`species` is a static field, which belongs to the class rather than each instance"]
+    );
+}
+
+#[test]
+fn test_field_types_through_self_and_instances() {
+    let source = format!(
+        "{}fn f(p: Person) {{
+    let n: number = p.name;
+    let k: number = p.nickname;
+    let s: string = Person.species;
+}}
+class Greeter {{
+    let prefix: string;
+    fn greet(self, p: Person) -> number {{ return self.prefix + p.greet(); }}
+}}",
+        PERSON
+    );
+
+    let diagnostics = check(&source);
+    let labels: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.labels[0].message.as_str())
+        .collect();
+
+    assert_eq!(
+        labels,
+        [
+            "expected `number`, found `string`",
+            "expected `number`, found `string`",
+            "expected `number`, found `string`",
+        ]
+    );
+}
+
+#[test]
+fn test_unknown_members() {
+    let source = format!(
+        "{}{}fn f(p: Person, s: Shape) {{
+    p.nme;
+    p.greet();
+    p.wave();
+    Person.specie;
+    s.perimeter();
+}}",
+        PERSON, SHAPE
+    );
+
+    let diagnostics = check(&source);
+    let messages: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect();
+
+    assert_eq!(
+        messages,
+        [
+            "Class `Person` has no member named `nme`",
+            "Class `Person` has no member named `wave`",
+            "Class `Person` has no member named `specie`",
+            "Interface `Shape` has no member named `perimeter`",
+        ]
+    );
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code == "E216")
+    );
+    assert_eq!(diagnostics[0].notes, ["help: did you mean `name`?"]);
+    assert_eq!(diagnostics[2].notes, ["help: did you mean `species`?"]);
+}
+
+#[test]
+fn test_bare_field_inside_class() {
+    let diagnostics =
+        check("class Counter { var count: number; fn get(self) -> number { return count; } }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E206");
+    assert_eq!(diagnostics[0].notes, ["help: use it as `self.count`"]);
+}
+
+#[test]
+fn test_classes_are_nominal() {
+    let source = "class Cat { let name: string; }
+class Dog { let name: string; }
+fn pet(cat: Cat) { }
+pet(Dog { name: \"Rex\" });";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `Cat`, found `Dog`"
+    );
+    assert_eq!(
+        diagnostics[0].notes,
+        ["classes only match themselves, so a `Dog` is never a `Cat`, even with the same fields"]
+    );
+}
+
+#[test]
+fn test_records_accept_extra_and_more_specific_fields() {
+    let source = "type Named = { name: string, nickname: string? };
+type Status = { state: \"on\" | \"off\" };
+fn greet(person: Named) -> string { return person.name; }
+fn show(status: Status) { }
+greet({ name: \"Ada\", age: 36 });
+show({ state: \"on\", since: 3 });
+let labels: { [string]: string } = { a: \"x\", b: \"y\" };";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_nested_field_mismatch_names_its_path() {
+    let source = "type Address = { street: string, zip: string };
+type Person = { name: string, address: Address };
+fn send(person: Person) { }
+send({ name: \"Ada\", address: { street: \"Main\", zip: 12345 } });
+send({ name: \"Ada\", address: { zip: \"12345\" } });";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        ".address.zip: expected `string`, found `number`"
+    );
+    assert_eq!(diagnostics[0].notes.len(), 1);
+    assert!(diagnostics[0].notes[0].starts_with("the value has type `{"));
+    assert_eq!(
+        diagnostics[1].labels[0].message,
+        ".address.street: missing, expected `string`"
+    );
+}
+
+#[test]
+fn test_function_parameters_are_contravariant() {
+    let source = "type Named = { name: string };
+type Person = { name: string, age: number };
+fn apply(f: (Person) -> Named) { }
+apply((p: Named) -> { name: p.name, age: 1 });
+apply((p: { name: string, email: string }) -> p);";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E212");
+    assert!(
+        diagnostics[0].labels[0]
+            .message
+            .starts_with("expected `({ name: string, age: number }) -> { name: string }`")
+    );
+}
+
+#[test]
+fn test_instances_fit_records_of_their_fields() {
+    let source = "class Point { let x: number; let y: number; }
+fn first(p: { x: number }) -> number { return p.x; }
+fn label(p: { label: string }) { }
+first(Point { x: 1, y: 2 });
+label(Point { x: 1, y: 2 });";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        ".label: missing, expected `string`"
+    );
+}
+
+#[test]
+fn test_optional_fields_and_parameters_keep_their_variance() {
+    let source = "type Address = { zip: string };
+type Draft = { zip: string? };
+fn send(address: Address) { }
+fn apply(f: (number?) -> number) { }
+fn draft(d: Draft) { send(d); }
+apply((n: number) -> n + 1);
+apply((n: number?) -> 1);";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        ".zip: expected `string`, found `string?`"
+    );
+    assert!(
+        diagnostics[1].labels[0]
+            .message
+            .starts_with("expected `(number?) -> number`, found `(number) -> number`")
+    );
+}
+
+const STATUS: &str = "type Status = \"loading\" | \"success\" | \"error\";\n";
+
+#[test]
+fn test_comparisons_narrow_literal_unions() {
+    let source = format!(
+        "{}fn f(s: Status) {{
+    if s == \"loading\" {{
+        let a: \"loading\" = s;
+    }} else {{
+        let b: \"success\" | \"error\" = s;
+        let c: \"success\" = s;
+    }}
+}}",
+        STATUS
+    );
+
+    let diagnostics = check(&source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 7);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `\"success\"`, found `\"success\" | \"error\"`"
+    );
+}
+
+#[test]
+fn test_early_returns_narrow_the_rest_of_the_block() {
+    let source = format!(
+        "{}fn f(s: Status?) -> Status {{
+    if s == nil {{
+        return \"error\";
+    }}
+
+    if s != \"loading\" && s != \"success\" {{
+        let e: \"error\" = s;
+    }}
+
+    return s;
+}}
+
+type Point = {{ x: number }};
+fn g(p: Point?) -> number {{
+    if !p {{ return 0; }}
+    return p.x;
+}}",
+        STATUS
+    );
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_match_arms_narrow_the_matched_local() {
+    let source = format!(
+        "{}fn f(s: Status?) -> number {{
+    return match s {{
+        nil => 0,
+        \"loading\" => {{ let l: \"loading\" = s; return 1; }},
+        rest => {{ let r: \"success\" | \"error\" = rest; let t: Status = s; return 2; }},
+    }};
+}}",
+        STATUS
+    );
+
+    assert!(check(&source).is_empty());
+}
+
+#[test]
+fn test_assignment_undoes_narrowing() {
+    let source = "type Point = { x: number };
+fn f(p: Point?) {
+    var q = p;
+    if q == nil { return; }
+    q.x;
+    q = nil;
+    q.x;
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot access `x` on optional type `{ x: number }?` without narrowing"
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 7);
+}
+
+#[test]
+fn test_bounded_type_parameters_allow_their_operations() {
+    let source = "interface Named { fn name(self) -> string; }
+fn add<T: Num>(a: T, b: T) -> T { return a + b; }
+fn max<T>(a: T, b: T) -> T where T: Ord { if a > b { return a; } return b; }
+fn show<T: Show>(x: T) -> string { return \"value: \" + x; }
+fn greet<T: Named>(x: T) -> string { return x.name(); }
+fn double<U: Num>(u: U) -> U { return add(u, u); }
+add(1, 2);
+max(\"a\", \"b\");
+show(true);";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_unbounded_type_parameters_are_opaque() {
+    let source = "fn less<T>(a: T, b: T) -> boolean { return a < b; }
+fn sum<T: Num>(a: T, b: T) -> T { return a + b; }
+fn double<U>(u: U) -> U { return sum(u, u); }
+fn sorted<T: Sortable>(xs: T) -> T { return xs; }";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].code, "E220");
+    assert_eq!(
+        diagnostics[0].message,
+        "Type `T` does not satisfy constraint `Ord`"
+    );
+    assert_eq!(diagnostics[0].labels[0].message, "this needs `T: Ord`");
+    assert_eq!(diagnostics[1].code, "E219");
+    assert_eq!(diagnostics[1].message, "Cannot find constraint `Sortable`");
+    assert_eq!(
+        diagnostics[2].message,
+        "Type `U` does not satisfy constraint `Num`"
+    );
+    assert_eq!(
+        diagnostics[2].notes,
+        ["help: add the bound to the type parameter, as in `U: Num`"]
+    );
+}
+
+#[test]
+fn test_sum_over_an_array_of_addables() {
+    let source = "interface Addable { fn add(self, other: Addable) -> Addable; }
+class Money implements Addable {
+    let cents: number;
+    fn add(self, other: Addable) -> Addable { return self; }
+}
+fn sum<T: Addable>(xs: [T]) -> T {
+    let first = luau<T>(xs) { return xs[1] };
+    for x in xs {
+        x.add(first);
+    }
+    return first;
+}
+fn wallet(coins: [Money]) -> Money { return sum(coins); }
+fn tally(counts: [number]) -> number { return sum(counts); }
+let names: [string] = 1;";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `[string]`, found `number`"
+    );
+    assert_eq!(
+        diagnostics[1].message,
+        "Type `number` does not satisfy constraint `Addable`"
+    );
+}
+
+#[test]
+fn test_call_sites_meet_constraints() {
+    let source = format!(
+        "{}fn sum<T: Num>(a: T, b: T) -> T {{ return a + b; }}
+fn max<T: Ord>(a: T, b: T) -> T {{ return a; }}
+fn show<T: Show>(x: T) -> string {{ return \"\" + x; }}
+fn area<T: Shape>(shape: T) -> number {{ return shape.area(); }}
+sum(\"a\", \"b\");
+max(true, false);
+show((x: number) -> x);
+area(1);",
+        SHAPE
+    );
+
+    let diagnostics = check(&source);
+
+    let messages: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.message.as_str(),
+                diagnostic.labels[0].message.as_str(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        messages,
+        [
+            (
+                "Type `string` does not satisfy constraint `Num`",
+                "`sum` requires `T: Num`"
+            ),
+            (
+                "Type `boolean` does not satisfy constraint `Ord`",
+                "`max` requires `T: Ord`"
+            ),
+            (
+                "Type `(number) -> number` does not satisfy constraint `Show`",
+                "`show` requires `T: Show`"
+            ),
+            (
+                "Type `number` does not satisfy constraint `Shape`",
+                "`area` requires `T: Shape`"
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].notes,
+        ["`Num` is only met by numbers, which arithmetic works on"]
+    );
+}
+
+#[test]
+fn test_partial_application_leaves_a_function_of_the_rest() {
+    let source =
+        "fn addThreeNumbers(a: number, b: number, c: number) -> number { return a + b + c; }
+let addOne: (number, number) -> number = addThreeNumbers(1);
+let total: number = addOne(2, 3);
+let wrong: string = addThreeNumbers(1, 2);
+addThreeNumbers(\"1\");";
+
+    let diagnostics = check(source);
+
+    let labels: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.labels[0].message.as_str())
+        .collect();
+
+    assert_eq!(
+        labels,
+        [
+            "expected `string`, found `(number) -> number`",
+            "expected `number`, found `string`"
+        ]
+    );
+}
+
+#[test]
+fn test_calls_pass_no_more_arguments_than_parameters() {
+    let source = format!(
+        "{}fn add(a: number, b: number) -> number {{ return a + b; }}
+fn label(s: string, suffix: string?) -> string {{ return s; }}
+fn f(c: Counter?, r: {{ run: (number) -> number }}?) {{
+    add(1, 2, 3);
+    add();
+    label();
+    label(\"a\");
+    c?.get(1);
+    r?.run();
+}}",
+        COUNTER
+    );
+
+    let diagnostics = check(&source);
+
+    assert!(diagnostics.iter().all(|d| d.code == "E221"));
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "Expected 2 arguments, found 3",
+            "Expected 2 arguments, found 0",
+            "Expected 1 argument, found 0",
+            "Expected 0 arguments, found 1",
+            "Expected 1 argument, found 0"
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "this call passes 3 arguments"
+    );
+}
+
+#[test]
+fn test_class_declared_inside_a_function() {
+    let source = "fn f() -> number {
+    class Inner {
+        let a: number;
+        fn get(self) -> number { return self.a; }
+    }
+
+    let i = Inner { a: 1 };
+    let s: string = i.get();
+    return i.get();
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "expected `string`, found `number`"
+    );
+}
@@ -0,0 +1,844 @@
+use exx::{
+    checker,
+    codegen::Codegen,
+    lexer::Lexer,
+    node::Tables,
+    parser::{Parser, ParserToken},
+};
+use pretty_assertions::assert_eq;
+
+fn compile(source: &str) -> String {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse");
+
+    Codegen::new().generate(&program)
+}
+
+// like `compile`, but with the checker's types, as the compiler runs it
+fn compile_checked(source: &str) -> String {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse");
+
+    let mut tables = Tables::new(&program);
+    let diagnostics = checker::check_with(&program, &mut tables);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    Codegen::new().with_types(&tables.types).generate(&program)
+}
+
+#[test]
+fn test_tuple_literal_destructuring() {
+    assert_eq!(compile("let (a, b) = (1, 2);"), "local a, b = 1, 2\n");
+}
+
+#[test]
+fn test_record_destructuring_uses_field_paths() {
+    let source = "let { name, age: years, address: { zip } } = person;";
+
+    assert_eq!(
+        compile(source),
+        "local name, years, zip = person.name, person.age, person.address.zip\n"
+    );
+}
+
+#[test]
+fn test_destructuring_call_evaluates_once() {
+    let source = "var (p, (q, _)) = make();";
+
+    assert_eq!(
+        compile(source),
+        "local _exx1 = make()\nlocal p, q = _exx1[1], _exx1[2][1]\n"
+    );
+}
+
+#[test]
+fn test_wildcard_keeps_side_effects() {
+    assert_eq!(
+        compile("let (_, b) = (f(), g());"),
+        "local _, b = f(), g()\n"
+    );
+
+    assert_eq!(compile("let (_, b) = (x, y);"), "local b = y\n");
+}
+
+#[test]
+fn test_parameter_destructuring() {
+    let source = "fn dist({ x, y }: Point, (dx, dy)) -> number { return x * dx + y * dy; }";
+
+    assert_eq!(
+        compile(source),
+        "local function dist(_exx1, _exx2)
+    local x, y = _exx1.x, _exx1.y
+    local dx, dy = _exx2[1], _exx2[2]
+    return x * dx + y * dy
+end
+"
+    );
+}
+
+#[test]
+fn test_lambda_parameter_destructuring() {
+    assert_eq!(
+        compile("let fst = ((a, _)) -> a;"),
+        "local fst = function(_exx1)
+    local a = _exx1[1]
+    return a
+end
+"
+    );
+
+    assert_eq!(
+        compile("let add = (a, b) -> a + b;"),
+        "local add = function(a, b) return a + b end\n"
+    );
+}
+
+#[test]
+fn test_luau_keywords_are_mangled() {
+    assert_eq!(
+        compile("let end = node.end;"),
+        "local end_ = node[\"end\"]\n"
+    );
+}
+
+#[test]
+fn test_range_loops() {
+    assert_eq!(
+        compile("for i in 1..=n { print(i); }"),
+        "for i = 1, n do\n    print(i)\nend\n"
+    );
+
+    assert_eq!(
+        compile("for i in 0..10 { print(i); }"),
+        "for i = 0, 9 do\n    print(i)\nend\n"
+    );
+
+    assert_eq!(
+        compile("for _ in 0..n + 1 { tick(); }"),
+        "for _ = 0, n + 1 - 1 do\n    tick()\nend\n"
+    );
+}
+
+#[test]
+fn test_iterator_loops() {
+    assert_eq!(
+        compile("for item in items { print(item); }"),
+        "for _, item in ipairs(items) do\n    print(item)\nend\n"
+    );
+
+    assert_eq!(
+        compile("for (key, { x }) in points { print(key, x); }"),
+        "for key, _exx1 in pairs(points) do
+    local x = _exx1.x
+    print(key, x)
+end
+"
+    );
+}
+
+#[test]
+fn test_while_with_break_and_continue() {
+    let source = "var i = 0;
+while true {
+    i += 1;
+    if i % 2 == 0 {
+        continue;
+    } else if i > 10 {
+        break;
+    }
+}";
+
+    assert_eq!(
+        compile(source),
+        "local i = 0
+while true do
+    i += 1
+    if i % 2 == 0 then
+        continue
+    elseif i > 10 then
+        break
+    end
+end
+"
+    );
+}
+
+#[test]
+fn test_block_expressions_run_in_place() {
+    // `break` and `return` mean the same in a block used as a value as in one on its own
+    let source = "fn h() -> number {
+    while true {
+        let v = { break; };
+    }
+    { return 1; };
+}";
+
+    assert_eq!(
+        compile(source),
+        "local function h()
+    while true do
+        do
+            break
+        end
+        local v = nil
+    end
+    do
+        return 1
+    end
+end
+"
+    );
+}
+
+#[test]
+fn test_interfaces_and_record_literals() {
+    let source = "interface Shape { fn area(self) -> number; }
+let square: Shape = { area: (self) -> 4 };";
+
+    assert_eq!(
+        compile(source),
+        "local square = { area = function(self) return 4 end }\n"
+    );
+}
+
+#[test]
+fn test_mixin_methods_are_copied_into_class() {
+    let source = "mixin Named {
+    fn name(self) -> string {
+        return self.first;
+    }
+}
+
+class Person includes Named { }";
+
+    assert_eq!(
+        compile(source),
+        "local Named = {}
+
+function Named:name()
+    return self.first
+end
+
+local Person = {}
+Person.__index = Person
+for key, value in pairs(Named) do
+    Person[key] = value
+end
+"
+    );
+}
+
+#[test]
+fn test_instance_and_static_members() {
+    let source = "class Counter {
+    var count: number;
+    static let zero = 0;
+
+    static fn new() -> Counter {
+        return Counter { count: Counter.zero };
+    }
+
+    fn increment(self, by: number) {
+        self.count += by;
+        self.report();
+    }
+
+    fn report(self) { }
+}
+
+let counter = Counter.new();
+counter.increment(2);";
+
+    assert_eq!(
+        compile(source),
+        "local Counter = {}
+Counter.__index = Counter
+Counter.zero = 0
+
+function Counter.new()
+    return setmetatable({ count = Counter.zero }, Counter)
+end
+
+function Counter:increment(by)
+    self.count += by
+    self:report()
+end
+
+function Counter:report()
+end
+
+local counter = Counter.new()
+counter:increment(2)
+"
+    );
+}
+
+#[test]
+fn test_luau_blocks() {
+    let source = "let end = 2;
+let n = luau<number>(end) { return end_ * 2 };
+fn log(message: string) {
+    luau {
+        if message ~= \"\" then
+            print(message)
+        end
+    }
+}
+let t = luau<number>() {
+    local x = 1
+    return x
+};";
+
+    assert_eq!(
+        compile(source),
+        "local end_ = 2
+local n = (function(end_) return end_ * 2 end)(end_)
+
+local function log(message)
+    if message ~= \"\" then
+        print(message)
+    end
+end
+
+local t = (function()
+    local x = 1
+    return x
+end)()
+"
+    );
+}
+
+#[test]
+fn test_inline_functions() {
+    let source = "@inline fn area(w: number, h: number) -> number { return w * h; }
+let a = area(2, 3);
+let b = area(a, f(1));";
+
+    assert_eq!(
+        compile(source),
+        "local function area(w, h)
+    return w * h
+end
+
+local a = (2 * 3)
+local b = area(a, f(1))
+"
+    );
+}
+
+#[test]
+fn test_inlining_does_not_capture_names() {
+    let source = "let x = 1;
+@inline fn cap(a: number) -> number { return a + x; }
+@inline fn adder(a: number) -> (number) -> number { return (n) -> n + a; }
+fn k() -> number {
+    let x = 100;
+    return cap(1);
+}
+fn m(n: number, y: number) -> number {
+    return cap(y) + adder(n)(1) + adder(y)(2);
+}";
+
+    assert_eq!(
+        compile(source),
+        "local x = 1
+
+local function cap(a)
+    return a + x
+end
+
+local function adder(a)
+    return function(n) return n + a end
+end
+
+local function k()
+    local x = 100
+    return cap(1)
+end
+
+local function m(n, y)
+    return (y + x) + adder(n)(1) + (function(n) return n + y end)(2)
+end
+"
+    );
+}
+
+#[test]
+fn test_native_attribute_marks_script() {
+    assert_eq!(
+        compile("@native fn hot() { }"),
+        "--!native
+
+local function hot()
+end
+"
+    );
+}
+
+#[test]
+fn test_doc_comments_become_luau_comments() {
+    assert_eq!(
+        compile(
+            "/// The answer.
+let answer = 42;
+
+/**
+ * Squares `x`.
+ *
+ * ```
+ * square(2);
+ * ```
+ */
+fn square(x: number) -> number { return x * x; }
+
+class Point {
+    /// Makes a point.
+    static fn new() { }
+}"
+        ),
+        "-- The answer.
+local answer = 42
+
+-- Squares `x`.
+--
+-- ```
+-- square(2);
+-- ```
+local function square(x)
+    return x * x
+end
+
+local Point = {}
+Point.__index = Point
+
+-- Makes a point.
+function Point.new()
+end
+"
+    );
+}
+
+#[test]
+fn test_optional_chaining_guards_each_step() {
+    assert_eq!(
+        compile("let x = a?.b?.c;"),
+        "local x = a and a.b and a.b.c\n"
+    );
+}
+
+#[test]
+fn test_optional_chaining_on_a_call_evaluates_once() {
+    assert_eq!(
+        compile("let x = find()?.name;"),
+        "local _exx1 = find()\nlocal x = _exx1 and _exx1.name\n"
+    );
+    assert_eq!(
+        compile("let x = find()?.size + 1;"),
+        "local _exx1 = find()\nlocal x = (_exx1 and _exx1.size) + 1\n"
+    );
+}
+
+#[test]
+fn test_optional_method_call_statement() {
+    let source = "class C { fn f(self) { } }
+fn g(c: C?) {
+    c?.f();
+    return c?.f() == 1;
+}";
+
+    assert!(compile(source).ends_with(
+        "local function g(c)
+    if c then c:f() end
+    return (c and c:f()) == 1
+end
+"
+    ));
+}
+
+#[test]
+fn test_coalesce() {
+    assert_eq!(
+        compile("let x = a ?? b ?? 0;"),
+        "local x = if a ~= nil then a else if b ~= nil then b else 0\n"
+    );
+    assert_eq!(
+        compile("let x = (a ?? 1) + 2;"),
+        "local x = (if a ~= nil then a else 1) + 2\n"
+    );
+    assert_eq!(
+        compile("let x = f() ?? 1;"),
+        "local _exx1 = f()\nlocal x = if _exx1 ~= nil then _exx1 else 1\n"
+    );
+    assert_eq!(
+        compile("print(f() ?? 1);"),
+        "local _exx1 = f()\nprint(if _exx1 ~= nil then _exx1 else 1)\n"
+    );
+}
+
+#[test]
+fn test_lines_an_operand_needs_only_run_when_it_does() {
+    // `f` must only be called when `ok` is true
+    assert_eq!(
+        compile("let x = ok && (f() ?? 1);"),
+        "local _exx2 = ok
+if _exx2 then
+    local _exx1 = f()
+    _exx2 = (if _exx1 ~= nil then _exx1 else 1)
+end
+local x = _exx2
+"
+    );
+
+    // and only when the `if` before it didn't match
+    assert_eq!(
+        compile("if a { } else if f() ?? true { print(1); }"),
+        "if a then
+else
+    local _exx1 = f()
+    if if _exx1 ~= nil then _exx1 else true then
+        print(1)
+    end
+end
+"
+    );
+}
+
+#[test]
+fn test_loop_conditions_run_their_lines_every_iteration() {
+    assert_eq!(
+        compile("while f() ?? false { g(); }"),
+        "while true do
+    local _exx1 = f()
+    if not (if _exx1 ~= nil then _exx1 else false) then break end
+    g()
+end
+"
+    );
+}
+
+#[test]
+fn test_operands_still_run_left_to_right() {
+    assert_eq!(
+        compile("let y = g(1) + (f() ?? 3);"),
+        "local _exx2 = g(1)
+local _exx1 = f()
+local y = _exx2 + (if _exx1 ~= nil then _exx1 else 3)
+"
+    );
+    assert_eq!(
+        compile("print(n, f() ?? 3);"),
+        "local _exx1 = f()
+print(n, if _exx1 ~= nil then _exx1 else 3)
+"
+    );
+}
+
+#[test]
+fn test_type_aliases_map_to_luau_types() {
+    let source = "type User = { readonly name: string, age?: number, [string]: (number, string) };
+type Handler = ((User) -> boolean)?;
+type Box<T> = { value: T };
+type Names = [string?];";
+
+    assert_eq!(
+        compile(source),
+        "type User = { read name: string, age: number?, [string]: { number | string } }
+type Handler = ((User) -> boolean)?
+type Box<T> = { value: T }
+type Names = { string? }
+"
+    );
+}
+
+#[test]
+fn test_data_types_become_tagged_tables() {
+    let source = "type Option<T> = Some(T) | None;
+type Direction = \"north\" | \"south\";";
+
+    assert_eq!(
+        compile(source),
+        "type Option<T> = { tag: \"Some\", [number]: T } | { tag: \"None\" }
+local Some = function(...) return { tag = \"Some\", ... } end
+local None = { tag = \"None\" }
+type Direction = \"north\" | \"south\"
+"
+    );
+}
+
+#[test]
+fn test_match_tests_each_arm_in_turn() {
+    let source = "type Option<T> = Some(T) | None;
+let n = match value {
+    Some((x, 0)) => x,
+    None => -1,
+    _ => 0,
+};";
+
+    assert!(compile(source).ends_with(
+        "local _exx1
+if value.tag == \"Some\" and value[1][2] == 0 then
+    local x = value[1][1]
+    _exx1 = x
+elseif value.tag == \"None\" then
+    _exx1 = -1
+else
+    _exx1 = 0
+end
+local n = _exx1
+"
+    ));
+}
+
+#[test]
+fn test_match_arms_can_leave_the_loop_or_function() {
+    let source = "type Option<T> = Some(T) | None;
+fn first(items: [Option<number>]) -> number {
+    for item in items {
+        match item {
+            Some(0) => { continue; },
+            Some(n) => { return n; },
+            None => { break; },
+        }
+    }
+    return 0;
+}";
+
+    assert!(compile(source).ends_with(
+        "local function first(items)
+    for _, item in ipairs(items) do
+        if item.tag == \"Some\" and item[1] == 0 then
+            continue
+        elseif item.tag == \"Some\" then
+            local n = item[1]
+            return n
+        elseif item.tag == \"None\" then
+            break
+        end
+    end
+    return 0
+end
+"
+    ));
+}
+
+#[test]
+fn test_struct_literals_fill_in_field_defaults() {
+    let source = "class Person {
+    let name: string;
+    var tags = {};
+}
+let a = Person { name: \"Ada\" };
+let b = Person { tags: t, name: \"Bo\" };";
+
+    assert!(compile(source).ends_with(
+        "local a = setmetatable({ name = \"Ada\", tags = {} }, Person)
+local b = setmetatable({ tags = t, name = \"Bo\" }, Person)
+"
+    ));
+}
+
+#[test]
+fn test_partial_application_becomes_a_closure() {
+    let source = "fn add(a: number, b: number, c: number) -> number { return a + b + c; }
+let full = add(1, 2, 3);
+let addOne = add(1);
+var n = 5;
+let addN = add(n, 2);
+let label = (s: string, suffix: string?) -> s;
+let plain = label(\"a\");";
+
+    assert_eq!(
+        compile_checked(source),
+        "local function add(a, b, c)
+    return a + b + c
+end
+
+local full = add(1, 2, 3)
+local addOne = function(_exx1, _exx2) return add(1, _exx1, _exx2) end
+local n = 5
+local _exx3 = n
+local addN = function(_exx4) return add(_exx3, 2, _exx4) end
+local label = function(s, suffix) return s end
+local plain = label(\"a\")
+"
+    );
+}
+
+#[test]
+fn test_partial_application_evaluates_the_receiver_once() {
+    let source = "class Counter {
+    fn add(self, a: number, b: number) -> number { return a + b; }
+}
+fn make() -> Counter { return Counter {}; }
+let p = make().add(1);";
+
+    assert!(compile_checked(source).ends_with(
+        "local _exx1 = make()
+local p = function(_exx2) return _exx1:add(1, _exx2) end
+"
+    ));
+}
+
+#[test]
+fn test_method_calls_need_a_class_instance() {
+    let source = "class Text {
+    let body: string;
+    fn len(self) -> number { return 0; }
+    fn twice(self) -> number { return self.len() * 2; }
+}
+let util = { len: (s: string) -> 1 };
+let n = util.len(\"abc\");
+let t = Text { body: \"x\" };
+let m = t.len();
+let k = string.len(t.body);";
+
+    assert_eq!(
+        compile_checked(source),
+        "local Text = {}
+Text.__index = Text
+
+function Text:len()
+    return 0
+end
+
+function Text:twice()
+    return self:len() * 2
+end
+
+local util = { len = function(s) return 1 end }
+local n = util.len(\"abc\")
+local t = setmetatable({ body = \"x\" }, Text)
+local m = t:len()
+local k = string.len(t.body)
+"
+    );
+}
+
+#[test]
+fn test_methods_taken_as_values_keep_their_receiver() {
+    let source = "class Circle {
+    let r: number;
+    fn area(self) -> number { return self.r * self.r; }
+}
+fn make() -> Circle { return Circle { r: 1 }; }
+let c = Circle { r: 2 };
+let g = c.area;
+let h = make().area;
+let n = g() + h();";
+
+    assert!(compile_checked(source).ends_with(
+        "local g = function(...) return c:area(...) end
+local _exx1 = make()
+local h = function(...) return _exx1:area(...) end
+local n = g() + h()
+"
+    ));
+}
+
+#[test]
+fn test_plus_on_strings_becomes_concatenation() {
+    let source = "fn show<T: Show>(value: T) -> string { return \"value: \" + value; }
+let name = \"Ada\";
+let count = 1 + 2;
+let greeting = \"hi \" + name + \"!\";
+let same = \"a\" + name == \"aAda\";
+var log = \"\";
+log += greeting;
+var total = 0;
+total += count;";
+
+    assert_eq!(
+        compile_checked(source),
+        "local function show(value)
+    return \"value: \" .. tostring(value)
+end
+
+local name = \"Ada\"
+local count = 1 + 2
+local greeting = \"hi \" .. name .. \"!\"
+local same = \"a\" .. name == \"aAda\"
+local log = \"\"
+log ..= greeting
+local total = 0
+total += count
+"
+    );
+}
+
+#[test]
+fn test_items_used_before_their_declaration_are_declared_up_front() {
+    let source = "fn later() -> number { return soon(); }
+fn soon() -> number { return 1; }
+fn origin() -> Point { return Point { x: 0 }; }
+class Point { let x: number; }";
+
+    assert_eq!(
+        compile(source),
+        "local soon, Point
+
+local function later()
+    return soon()
+end
+
+function soon()
+    return 1
+end
+
+local function origin()
+    return setmetatable({ x = 0 }, Point)
+end
+
+Point = {}
+Point.__index = Point
+"
+    );
+}
+
+#[test]
+fn test_static_fields_do_not_shadow_outer_locals() {
+    let source = "let zero = \"five\";
+class Limits {
+    static let zero = 0;
+    static let (low, _, high) = (1, print(\"x\"), 10);
+}
+print(zero);";
+
+    assert_eq!(
+        compile(source),
+        "local zero = \"five\"
+
+local Limits = {}
+Limits.__index = Limits
+Limits.zero = 0
+local _exx1, _, _exx2 = 1, print(\"x\"), 10
+Limits.low = _exx1
+Limits.high = _exx2
+
+print(zero)
+"
+    );
+}
+
+#[test]
+fn test_methods_of_bounded_type_parameters_take_self() {
+    let source = "interface Named { fn name(self) -> string; }
+fn names<T: Named>(xs: [T]) {
+    for x in xs {
+        print(x.name());
+    }
+}";
+
+    assert_eq!(
+        compile_checked(source),
+        "local function names(xs)
+    for _, x in ipairs(xs) do
+        print(x:name())
+    end
+end
+"
+    );
+}
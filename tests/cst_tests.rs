@@ -0,0 +1,240 @@
+use exx::{
+    ast::{ExprKind, StmtKind},
+    cst::{self, AstNode, SyntaxElement, SyntaxKind},
+};
+use pretty_assertions::assert_eq;
+
+const SOURCE: &str = "// a comment before everything
+@inline
+fn add(a: number, b: number) -> number {
+    return a + /* inline */ b;
+}
+
+class Point {
+    let x: number; // trailing
+}
+
+let total = add(1, 2) ?? 0;
+";
+
+#[test]
+fn test_round_trips_source_text() {
+    let parse = cst::parse(SOURCE);
+
+    assert_eq!(parse.syntax.text(), SOURCE);
+    assert_eq!(parse.syntax.text_range(), 0..SOURCE.len());
+    assert_eq!(parse.syntax.kind(), SyntaxKind::Program);
+    assert!(parse.errors.is_empty());
+}
+
+#[test]
+fn test_typed_nodes_read_their_parts_from_the_tree() {
+    let parse = cst::parse(SOURCE);
+    let statements: Vec<_> = parse.tree().statements().collect();
+
+    let cst::Stmt::FunctionDecl(function) = &statements[0] else {
+        panic!("Expected function declaration");
+    };
+
+    assert_eq!(function.name().unwrap().text(), "add");
+    assert!(function.text().starts_with("fn add("));
+
+    let Some(cst::Stmt::Return(ret)) = function.body().next() else {
+        panic!("Expected return");
+    };
+
+    let Some(cst::Expr::Binary(sum)) = ret.value() else {
+        panic!("Expected binary expression");
+    };
+
+    assert_eq!(sum.text(), "a + /* inline */ b");
+    assert_eq!(sum.operator().unwrap().text(), "+");
+    assert_eq!(sum.right().unwrap().text(), "b");
+
+    let comments: Vec<_> = sum
+        .syntax()
+        .tokens()
+        .filter(|token| token.is_trivia() && token.text().starts_with("/*"))
+        .collect();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].text_range(), 95..107);
+}
+
+#[test]
+fn test_ast_nodes_find_their_syntax() {
+    let parse = cst::parse(SOURCE);
+
+    let function = parse.node(parse.program[0].id).unwrap();
+    assert_eq!(function.kind(), SyntaxKind::FunctionDecl);
+
+    let StmtKind::FunctionDecl { body, .. } = &parse.program[0].kind else {
+        panic!("Expected function declaration");
+    };
+
+    let StmtKind::Return(Some(sum)) = &body[0].kind else {
+        panic!("Expected return");
+    };
+
+    let node = parse.node(sum.id).unwrap();
+    assert_eq!(node.text(), "a + /* inline */ b");
+    assert_eq!(node.parent().unwrap().kind(), SyntaxKind::Return);
+}
+
+#[test]
+fn test_trivia_between_nodes_belongs_to_the_parent() {
+    let parse = cst::parse(SOURCE);
+
+    let class = parse.node(parse.program[1].id).unwrap();
+    let trailing = class
+        .children_with_tokens()
+        .find_map(|element| match element {
+            SyntaxElement::Token(token) if token.text() == "// trailing" => Some(token),
+            _ => None,
+        });
+
+    assert!(trailing.is_some());
+    assert_eq!(
+        class.children().map(|node| node.kind()).collect::<Vec<_>>(),
+        vec![SyntaxKind::VariableDecl]
+    );
+}
+
+#[test]
+fn test_covering_node() {
+    let parse = cst::parse(SOURCE);
+    let StmtKind::VariableDecl {
+        initializer: Some(initializer),
+        ..
+    } = &parse.program[2].kind
+    else {
+        panic!("Expected variable declaration");
+    };
+
+    let ExprKind::Binary { left, .. } = &initializer.kind else {
+        panic!("Expected binary expression");
+    };
+
+    // `add` in `add(1, 2)`
+    let start = left.span.start.absolute;
+    let node = parse.syntax.covering_node(start..start + 1);
+
+    assert_eq!(node.kind(), SyntaxKind::Identifier);
+    assert_eq!(
+        node.ancestors().map(|node| node.kind()).collect::<Vec<_>>(),
+        vec![
+            SyntaxKind::Identifier,
+            SyntaxKind::Call,
+            SyntaxKind::Binary,
+            SyntaxKind::VariableDecl,
+            SyntaxKind::Program,
+        ]
+    );
+}
+
+#[test]
+fn test_types_have_nodes() {
+    let source = "fn first(pairs: [(number, string?)]) -> number | nil { return nil; }";
+    let parse = cst::parse(source);
+
+    let types: Vec<_> = parse
+        .syntax
+        .descendants()
+        .into_iter()
+        .filter(|node| node.kind() == SyntaxKind::Type)
+        .map(|node| node.text())
+        .collect();
+
+    assert_eq!(
+        types,
+        vec![
+            "[(number, string?)]",
+            "(number, string?)",
+            "number",
+            "string?",
+            "string",
+            "number | nil",
+            "number",
+            "nil",
+        ]
+    );
+    assert!(parse.errors.is_empty());
+}
+
+#[test]
+fn test_broken_statements_become_error_nodes() {
+    let source = "fn f() {
+    let x = ;
+    return 1;
+}
+
+let y = 2;
+";
+    let parse = cst::parse(source);
+
+    assert_eq!(parse.syntax.text(), source);
+    assert_eq!(parse.errors.len(), 1);
+    assert_eq!(parse.errors[0].message, "Unexpected token: Semicolon");
+    assert_eq!(parse.program.len(), 2);
+
+    let errors: Vec<_> = parse
+        .syntax
+        .descendants()
+        .into_iter()
+        .filter(|node| node.kind() == SyntaxKind::Error)
+        .collect();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].text(), "let x = ;");
+    assert_eq!(errors[0].parent().unwrap().kind(), SyntaxKind::FunctionDecl);
+
+    let StmtKind::FunctionDecl { body, .. } = &parse.program[0].kind else {
+        panic!("Expected function declaration");
+    };
+
+    assert_eq!(body.len(), 1);
+    assert!(matches!(body[0].kind, StmtKind::Return(_)));
+}
+
+#[test]
+fn test_broken_declarations_skip_to_the_next_one() {
+    let source = "let = 1;
+class A {
+    fn area() -> number { return 0; }
+}
+fn g() {}
+}
+";
+    let parse = cst::parse(source);
+
+    assert_eq!(parse.syntax.text(), source);
+    assert_eq!(
+        parse
+            .errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "Expected pattern",
+            "Instance method `area` must take `self` as its first parameter; declare it `static` if it doesn't need an instance",
+            "Unexpected token: RightBrace",
+        ]
+    );
+
+    let errors: Vec<_> = parse.tree().errors().map(|error| error.text()).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "let = 1;",
+            "class A {\n    fn area() -> number { return 0; }\n}",
+            "}",
+        ]
+    );
+
+    let names: Vec<_> = parse
+        .tree()
+        .statements()
+        .map(|stmt| stmt.syntax().kind())
+        .collect();
+    assert_eq!(names, vec![SyntaxKind::FunctionDecl]);
+    assert_eq!(parse.program.len(), 1);
+}
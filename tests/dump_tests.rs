@@ -0,0 +1,64 @@
+#![cfg(feature = "serde")]
+
+use exx::{
+    ast::Stmt,
+    dump,
+    lexer::Lexer,
+    parser::{Parser, ParserToken},
+    printer,
+};
+use pretty_assertions::assert_eq;
+use serde_json::{Value, json};
+
+fn parse(source: &str) -> Result<Vec<Stmt>, exx::parser::ParseError> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+
+    Parser::new(tokens).parse_program()
+}
+
+#[test]
+fn test_token_dump() {
+    let tokens: Vec<_> = Lexer::new("x;").collect();
+    let dump: Value = serde_json::from_str(&dump::tokens(&tokens).unwrap()).unwrap();
+
+    assert_eq!(dump["format"], "exx-tokens");
+    assert_eq!(dump["version"], dump::VERSION);
+    assert_eq!(
+        dump["tokens"][0],
+        json!({
+            "token_type": { "Identifier": "x" },
+            "span": {
+                "start": { "line": 1, "column": 1, "absolute": 0 },
+                "end": { "line": 1, "column": 2, "absolute": 1 },
+            },
+            "errors": [],
+        })
+    );
+    assert_eq!(dump["tokens"][1]["token_type"], "Semicolon");
+}
+
+#[test]
+fn test_ast_dump_round_trips() {
+    let source = "type Point = { readonly x: number, y?: number };
+fn norm(p: Point?) -> number { return p?.x ?? 0; }
+";
+    let program = parse(source).unwrap();
+    let dump: Value = serde_json::from_str(&dump::ast(Ok(&program)).unwrap()).unwrap();
+
+    assert_eq!(dump["format"], "exx-ast");
+    assert_eq!(dump["version"], dump::VERSION);
+    assert_eq!(dump["program"][1]["kind"]["FunctionDecl"]["name"], "norm");
+
+    let decoded: Vec<Stmt> = serde_json::from_value(dump["program"].clone()).unwrap();
+    assert_eq!(printer::print(&decoded), printer::print(&program));
+}
+
+#[test]
+fn test_ast_dump_of_parse_error() {
+    let error = parse("let = 1;").unwrap_err();
+    let dump: Value = serde_json::from_str(&dump::ast(Err(&error)).unwrap()).unwrap();
+
+    assert_eq!(dump["error"]["message"], error.message.as_str());
+    assert_eq!(dump["error"]["span"]["start"]["absolute"], 4);
+    assert!(dump.get("program").is_none());
+}
@@ -0,0 +1,248 @@
+use exx::{
+    diagnostic::{Diagnostic, Severity},
+    flow,
+    lexer::Lexer,
+    node::Tables,
+    parser::{Parser, ParserToken},
+    resolver,
+};
+use pretty_assertions::assert_eq;
+
+fn check(source: &str) -> Vec<Diagnostic> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse");
+
+    let mut tables = Tables::new(&program);
+    resolver::resolve(&program, &mut tables);
+
+    flow::check(&program, &tables)
+}
+
+fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.code.as_str())
+        .collect()
+}
+
+#[test]
+fn test_every_path_returns() {
+    let source = "fn sign(n: number) -> number {
+    if n < 0 {
+        return -1;
+    } else if n > 0 {
+        return 1;
+    } else {
+        return 0;
+    }
+}
+
+fn forever() -> number {
+    while true {
+        if ready() { return 1; }
+    }
+}
+
+fn nothing() {
+    if ready() { return; }
+}";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_missing_return_on_some_path() {
+    let source = "fn abs(n: number) -> number {
+    if n < 0 {
+        return -n;
+    }
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["E400"]);
+    assert_eq!(diagnostics[0].message, "Not all code paths return a value");
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "`abs` can reach its end here without returning a `number`"
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 2);
+}
+
+#[test]
+fn test_loops_and_methods_can_fall_through() {
+    let source = "fn find(items: Items) -> number {
+    for item in items {
+        return item;
+    }
+}
+
+class Counter {
+    fn next(self) -> number {
+        while self.more() { return 1; }
+    }
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["E400", "E400"]);
+    assert_eq!(
+        diagnostics[1].labels[0].message,
+        "`next` can reach its end here without returning a `number`"
+    );
+}
+
+#[test]
+fn test_code_after_return_and_break() {
+    let source = "fn f() -> number {
+    while true {
+        break;
+        log(1);
+        log(2);
+    }
+    return 1;
+    log(3);
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["W003", "W003"]);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].message, "Unreachable code");
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 4);
+    assert_eq!(
+        diagnostics[0].labels[1].message,
+        "any code after this is skipped"
+    );
+    assert_eq!(diagnostics[1].labels[0].span.start.line, 8);
+}
+
+#[test]
+fn test_code_after_an_if_that_always_returns() {
+    let source = "fn f(c: boolean) -> number {
+    if c { return 1; } else { return 2; }
+    log(1);
+    if c { log(2); }
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["W003"]);
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 3);
+    assert_eq!(
+        diagnostics[0].notes,
+        ["every path leading here returns, breaks or continues first"]
+    );
+}
+
+#[test]
+fn test_read_before_definite_assignment() {
+    let source = "fn f(c: boolean) -> number {
+    let x: number;
+    let y: number;
+
+    if c {
+        x = 1;
+    } else {
+        x = 2;
+    }
+
+    if c {
+        y = 1;
+    }
+
+    return x + y;
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["E401"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "`y` is read before it is definitely assigned"
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 15);
+    assert_eq!(
+        diagnostics[0].labels[1].message,
+        "declared without a value here"
+    );
+}
+
+#[test]
+fn test_deferred_let_is_assigned_once() {
+    let source = "let a: number;
+a = 1;
+
+let b: number;
+b = 1;
+b = 2;
+
+let c: number;
+while more() {
+    c = 1;
+}
+
+var d: number;
+d = 1;
+d = 2;
+let e = (x) -> x ?? d;";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["E100", "E100"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot assign twice to immutable binding `b`"
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 6);
+    assert_eq!(diagnostics[1].labels[0].span.start.line, 10);
+}
+
+#[test]
+fn test_reads_in_short_circuits_and_matches() {
+    let source = "var x: number;
+let ok = ready() && { x = 1; };
+log(x);
+
+var y: number;
+match ok {
+    true => { y = 1; },
+    false => { y = 2; },
+}
+log(y);";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["E401"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "`x` is read before it is definitely assigned"
+    );
+}
+
+#[test]
+fn test_blocks_used_as_values_leave_loops_and_functions() {
+    let source = "fn f() {
+    var i = 1;
+    while true {
+        { break; };
+    }
+    i = 2;
+}
+
+fn g() -> number {
+    { return 1; };
+}
+
+fn h() -> number {
+    let n = { return 1; };
+    log(n);
+}";
+
+    let diagnostics = check(source);
+
+    assert_eq!(codes(&diagnostics), ["W003"]);
+    assert_eq!(diagnostics[0].labels[0].span.start.line, 15);
+}
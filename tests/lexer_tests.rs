@@ -181,6 +181,188 @@ fn test_single_char_tokens() {
     assert_eq!(tokens[7].token_type, TokenType::Colon);
 }
 
+#[test]
+fn test_range_tokens() {
+    let source = "1..10 0..=n a.b";
+    let tokens = lex_all(source);
+
+    let types: Vec<_> = tokens.iter().map(|t| t.token_type.clone()).collect();
+
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Number("1".into()),
+            TokenType::DotDot,
+            TokenType::Number("10".into()),
+            TokenType::Number("0".into()),
+            TokenType::DotDotEqual,
+            TokenType::Identifier("n".into()),
+            TokenType::Identifier("a".into()),
+            TokenType::Dot,
+            TokenType::Identifier("b".into()),
+            TokenType::Eof,
+        ]
+    );
+
+    assert_eq!(tokens[4].span.start.absolute, 7);
+    assert_eq!(tokens[4].span.end.absolute, 10);
+}
+
+#[test]
+fn test_optional_tokens() {
+    let types: Vec<_> = lex_all("a?.b ?? nil: T?")
+        .into_iter()
+        .map(|t| t.token_type)
+        .collect();
+
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Identifier("a".into()),
+            TokenType::QuestionDot,
+            TokenType::Identifier("b".into()),
+            TokenType::QuestionQuestion,
+            TokenType::Nil,
+            TokenType::Colon,
+            TokenType::Identifier("T".into()),
+            TokenType::Question,
+            TokenType::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_comments_are_skipped() {
+    let types: Vec<_> = lex_all("a // line\n/* block\n */ b/* */+c")
+        .into_iter()
+        .map(|t| t.token_type)
+        .collect();
+
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Identifier("a".into()),
+            TokenType::Identifier("b".into()),
+            TokenType::Plus,
+            TokenType::Identifier("c".into()),
+            TokenType::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_doc_comments_are_kept() {
+    let types: Vec<_> = lex_all(
+        "/// Adds *two* numbers.\n//// not docs\n/**\n * Block\n *\n *   indented\n */ /**/ fn",
+    )
+    .into_iter()
+    .map(|t| t.token_type)
+    .collect();
+
+    assert_eq!(
+        types,
+        vec![
+            TokenType::DocComment("Adds *two* numbers.".into()),
+            TokenType::DocComment("Block\n\n  indented".into()),
+            TokenType::Keyword("fn".into()),
+            TokenType::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_trivia_tokens_cover_the_source() {
+    let source = "let x = 1; // one\n";
+    let tokens: Vec<_> = Lexer::with_trivia(source).collect();
+
+    assert_eq!(tokens[1].token_type, TokenType::Whitespace);
+    assert_eq!(
+        tokens[tokens.len() - 3].token_type,
+        TokenType::Comment("// one".into())
+    );
+
+    let text: String = tokens
+        .iter()
+        .map(|t| &source[t.span.start.absolute..t.span.end.absolute])
+        .collect();
+    assert_eq!(text, source);
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let tokens = lex_all("a /* never closed");
+
+    assert!(matches!(
+        tokens[1].errors[0].kind,
+        ErrorKind::UnterminatedBlockComment
+    ));
+    assert_eq!(tokens[1].errors[0].kind.code(), "E005");
+}
+
+#[test]
+fn test_trailing_whitespace_still_emits_eof() {
+    let tokens = lex_all("let \n");
+
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[1].token_type, TokenType::Eof);
+}
+
+#[test]
+fn test_luau_block_is_captured_raw() {
+    let source = "luau<number>(a) { local t = { \"}\" } -- }\n return #t + a }";
+    let tokens = lex_all(source);
+
+    let types: Vec<_> = tokens.iter().map(|t| t.token_type.clone()).collect();
+
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Keyword("luau".into()),
+            TokenType::Less,
+            TokenType::Identifier("number".into()),
+            TokenType::Greater,
+            TokenType::LeftParen,
+            TokenType::Identifier("a".into()),
+            TokenType::RightParen,
+            TokenType::LuauBlock(" local t = { \"}\" } -- }\n return #t + a ".into()),
+            TokenType::Eof,
+        ]
+    );
+
+    assert_eq!(tokens[7].span.start.absolute, 16);
+    assert_eq!(tokens[7].span.end.absolute, source.len());
+}
+
+#[test]
+fn test_luau_block_long_brackets() {
+    let tokens = lex_all("luau { print([[ } ]]) --[==[ } ]==] }");
+
+    assert_eq!(
+        tokens[1].token_type,
+        TokenType::LuauBlock(" print([[ } ]]) --[==[ } ]==] ".into())
+    );
+}
+
+#[test]
+fn test_unterminated_luau_block() {
+    let tokens = lex_all("luau { print(1)");
+
+    assert!(
+        tokens[1]
+            .errors
+            .iter()
+            .any(|e| matches!(e.kind, ErrorKind::UnterminatedLuauBlock))
+    );
+}
+
+#[test]
+fn test_operator_before_paren() {
+    let tokens = lex_all("a>(b)");
+
+    assert_eq!(tokens[1].token_type, TokenType::Greater);
+    assert_eq!(tokens[2].token_type, TokenType::LeftParen);
+}
+
 #[test]
 fn test_skip_whitespace() {
     let source = "   \t\n  let";
@@ -233,6 +415,11 @@ fn test_keywords() {
         "implements",
         "includes",
         "luau",
+        "while",
+        "for",
+        "in",
+        "break",
+        "continue",
     ];
 
     for kw in keywords {
@@ -253,6 +440,7 @@ fn test_valid_operators() {
         ("+=", TokenType::PlusEqual),
         ("-=", TokenType::MinusEqual),
         ("->", TokenType::Arrow),
+        ("=>", TokenType::FatArrow),
         ("&&", TokenType::And),
         ("||", TokenType::Or),
         ("!", TokenType::Bang),
@@ -349,7 +537,7 @@ fn test_invalid_escape() {
 
 #[test]
 fn test_unexpected_character() {
-    let source = "@";
+    let source = "$";
     let tokens = lex_all(source);
 
     assert!(matches!(&tokens[0].token_type, TokenType::Error(_)));
@@ -357,8 +545,20 @@ fn test_unexpected_character() {
         tokens[0]
             .errors
             .iter()
-            .any(|e| matches!(e.kind, ErrorKind::UnexpectedCharacter('@')))
+            .any(|e| matches!(e.kind, ErrorKind::UnexpectedCharacter('$')))
+    );
+}
+
+#[test]
+fn test_attribute_token() {
+    let tokens = lex_all("@deprecated(\"old\")");
+
+    assert_eq!(tokens[0].token_type, TokenType::At);
+    assert_eq!(
+        tokens[1].token_type,
+        TokenType::Identifier("deprecated".into())
     );
+    assert_eq!(tokens[0].span.end.absolute, 1);
 }
 
 #[test]
@@ -400,6 +600,12 @@ fn test_error_kind_messages() {
             "Unterminated escape sequence",
             "Unterminated escape sequence",
         ),
+        (
+            ErrorKind::UnterminatedLuauBlock,
+            "E007",
+            "Unterminated luau block",
+            "Expected a closing '}'",
+        ),
     ];
 
     for (error, code, message, label) in test_cases {
@@ -0,0 +1,122 @@
+use exx::{
+    diagnostic::Diagnostic,
+    lexer::Lexer,
+    mutability,
+    parser::{Parser, ParserToken},
+};
+use pretty_assertions::assert_eq;
+
+fn check(source: &str) -> Vec<Diagnostic> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse");
+
+    mutability::check(&program)
+}
+
+#[test]
+fn test_loop_mutating_var_is_allowed() {
+    let diagnostics = check("var total = 0; for i in 1..=10 { total += i; }");
+
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_loop_mutating_let_is_rejected() {
+    let source = "let total = 0; while total < 10 { total = total + 1; }";
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E100");
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot mutate immutable binding `total` inside a loop"
+    );
+
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 34);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 4);
+}
+
+#[test]
+fn test_loop_variable_is_immutable() {
+    let diagnostics = check("for (k, v) in items { v = 1; }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E101");
+    assert_eq!(diagnostics[0].message, "Cannot reassign loop variable `v`");
+}
+
+#[test]
+fn test_shadowing_var_inside_loop() {
+    let diagnostics = check("let x = 0; while true { var x = 1; x += 1; break; }");
+
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_function_in_loop_is_not_a_loop_body() {
+    let diagnostics = check("for i in 0..3 { fn f() { let y = 1; y = 2; } }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot assign twice to immutable binding `y`"
+    );
+}
+
+#[test]
+fn test_let_is_immutable_outside_loops() {
+    let diagnostics = check("let x = 10; x = 20; var y = 1; y += 1;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E100");
+    assert_eq!(
+        diagnostics[0].notes,
+        vec!["help: declare `x` with `var` to make it mutable"]
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 12);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 4);
+}
+
+#[test]
+fn test_compound_assignment_to_let() {
+    let diagnostics = check("let total = 0; total += 1;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E100");
+}
+
+#[test]
+fn test_parameters_cannot_be_reassigned() {
+    let diagnostics = check("fn f(n) { n = n - 1; }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Cannot assign to parameter `n`");
+}
+
+#[test]
+fn test_field_mutation_through_let() {
+    let source = "let point = { x: 1 };
+point.x = 2;
+var other = { inner: { x: 1 } };
+other.inner.x = 2;
+fn move(p) { p.x = 3; }";
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E102");
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot mutate field `x` through immutable binding `point`"
+    );
+}
+
+#[test]
+fn test_let_without_a_value_can_be_assigned() {
+    // whether it's assigned only once is up to the flow pass
+    let diagnostics = check("let x: number; x = 1; let p: Point; p = make(); p.x = 2;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E102");
+}
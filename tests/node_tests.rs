@@ -0,0 +1,169 @@
+use exx::{
+    ast::{Expr, ExprKind, Stmt, StmtKind},
+    checker::{self, Ty},
+    lexer::Lexer,
+    node::{self, Ident, Interner, NodeId, NodeMap, Tables},
+    parser::{Parser, ParserToken},
+    visit::{Fold, Visitor, walk_expr, walk_expr_fold, walk_pattern, walk_stmt},
+};
+use pretty_assertions::assert_eq;
+
+fn parse(source: &str) -> Vec<Stmt> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+
+    Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse")
+}
+
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl Visitor<'_> for Ids {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.0.push(stmt.id);
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.0.push(expr.id);
+        walk_expr(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &exx::ast::Pattern) {
+        self.0.push(pattern.id);
+        walk_pattern(self, pattern);
+    }
+}
+
+fn ids(program: &[Stmt]) -> Vec<NodeId> {
+    let mut ids = Ids::default();
+
+    for stmt in program {
+        ids.visit_stmt(stmt);
+    }
+
+    ids.0
+}
+
+#[test]
+fn test_parser_numbers_nodes_in_pre_order() {
+    let program = parse("let (a, b) = (1, 2); fn f(x: number) { return x + a; }");
+
+    let expected: Vec<_> = (0..13).map(NodeId).collect();
+    assert_eq!(ids(&program), expected);
+
+    let tables = Tables::new(&program);
+    assert_eq!(tables.spans.get(NodeId(0)), Some(&program[0].span));
+    assert_eq!(tables.spans.iter().count(), 13);
+}
+
+struct Double;
+
+// rewrites `x` to `x + x`, building nodes the parser never numbered
+impl Fold for Double {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = walk_expr_fold(self, expr);
+
+        match &expr.kind {
+            ExprKind::Identifier(name) => {
+                let copy = Expr::new(ExprKind::Identifier(*name), expr.span.clone());
+                let span = expr.span.clone();
+
+                Expr::new(
+                    ExprKind::Binary {
+                        left: Box::new(expr),
+                        op: exx::ast::BinaryOp::Add,
+                        right: Box::new(copy),
+                    },
+                    span,
+                )
+            }
+
+            _ => expr,
+        }
+    }
+}
+
+#[test]
+fn test_renumbering_after_a_fold() {
+    let mut program: Vec<_> = parse("let y = x;")
+        .into_iter()
+        .map(|stmt| Double.fold_stmt(stmt))
+        .collect();
+
+    assert!(ids(&program).contains(&NodeId::DUMMY));
+    assert_eq!(node::number(&mut program), 5);
+
+    let expected: Vec<_> = (0..5).map(NodeId).collect();
+    assert_eq!(ids(&program), expected);
+}
+
+#[test]
+fn test_checker_fills_the_type_table() {
+    let program = parse("let n = 1 + 2; let s = \"a\";");
+    let mut tables = Tables::new(&program);
+
+    assert!(checker::check_with(&program, &mut tables).is_empty());
+
+    let StmtKind::VariableDecl {
+        initializer: Some(sum),
+        ..
+    } = &program[0].kind
+    else {
+        panic!("Expected variable declaration");
+    };
+
+    assert_eq!(tables.types.get(sum.id), Some(&Ty::Number));
+    assert_eq!(tables.types.get(program[0].id), None);
+
+    let strings: Vec<_> = tables
+        .types
+        .iter()
+        .filter(|(_, ty)| **ty == Ty::String)
+        .collect();
+    assert_eq!(strings.len(), 1);
+}
+
+#[test]
+fn test_node_map_and_interner() {
+    let mut map = NodeMap::new();
+
+    assert_eq!(map.insert(NodeId(3), "three"), None);
+    assert_eq!(map.insert(NodeId(3), "drei"), Some("three"));
+    assert_eq!(map.insert(NodeId::DUMMY, "nothing"), None);
+    assert_eq!(map.get(NodeId(3)), Some(&"drei"));
+    assert!(!map.contains(NodeId(0)));
+    assert_eq!(map.iter().count(), 1);
+
+    let mut interner = Interner::new();
+    let a = interner.intern("apple");
+    let b = interner.intern("banana");
+
+    assert_eq!(interner.intern("apple"), a);
+    assert_ne!(a, b);
+    assert_eq!(interner.resolve(b), "banana");
+    assert_eq!(interner.get("cherry"), None);
+}
+
+#[test]
+fn test_identifiers_are_interned() {
+    let program = parse("let total = 1; total = total + 1;");
+
+    let StmtKind::Assign { target, value, .. } = &program[1].kind else {
+        unreachable!()
+    };
+    let (ExprKind::Identifier(a), ExprKind::Binary { left, .. }) = (&target.kind, &value.kind)
+    else {
+        unreachable!()
+    };
+    let ExprKind::Identifier(b) = &left.kind else {
+        unreachable!()
+    };
+
+    assert!(std::ptr::eq(a.as_str(), b.as_str()));
+    assert_eq!(*a, Ident::new("total"));
+    assert_eq!(*a, "total");
+    assert_ne!(*a, Ident::new("totals"));
+    assert!(Ident::new("apple") < Ident::new("banana"));
+}
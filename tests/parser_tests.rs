@@ -0,0 +1,787 @@
+use exx::{
+    ast::{AttributeKind, BinaryOp, ExprKind, PatternKind, Stmt, StmtKind, Type, doc_examples},
+    lexer::Lexer,
+    parser::{ParseError, Parser, ParserToken},
+};
+use pretty_assertions::assert_eq;
+
+fn parse(source: &str) -> Result<Vec<Stmt>, ParseError> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+
+    Parser::new(tokens).parse_program()
+}
+
+fn initializer(stmt: &Stmt) -> &ExprKind {
+    match &stmt.kind {
+        StmtKind::VariableDecl {
+            initializer: Some(expr),
+            ..
+        } => &expr.kind,
+
+        _ => panic!("Expected variable declaration with an initializer"),
+    }
+}
+
+#[test]
+fn test_identifier_binding() {
+    let program = parse("let x: number = 42;").unwrap();
+
+    match &program[0].kind {
+        StmtKind::VariableDecl {
+            is_mutable,
+            pattern,
+            type_annotation,
+            ..
+        } => {
+            assert_eq!(*is_mutable, false);
+            assert!(matches!(&pattern.kind, PatternKind::Identifier(name) if name == "x"));
+            assert!(matches!(type_annotation, Some(Type::Simple(name)) if name == "number"));
+        }
+
+        _ => panic!("Expected variable declaration"),
+    }
+}
+
+#[test]
+fn test_nested_destructuring() {
+    let source = "let { name, address: { zip: code }, pair: (a, _) } = person;";
+    let program = parse(source).unwrap();
+
+    match &program[0].kind {
+        StmtKind::VariableDecl { pattern, .. } => {
+            assert!(matches!(
+                &pattern.kind,
+                PatternKind::Record { name: None, .. }
+            ));
+            assert_eq!(pattern.bindings(), vec!["name", "code", "a"]);
+            assert_eq!(pattern.span.start.absolute, 4);
+            assert_eq!(pattern.span.end.absolute, 50);
+        }
+
+        _ => panic!("Expected variable declaration"),
+    }
+}
+
+#[test]
+fn test_parameter_patterns() {
+    let program =
+        parse("fn f((a, b): (number, number), Point { x, y }) -> number { return a; }").unwrap();
+
+    match &program[0].kind {
+        StmtKind::FunctionDecl { params, .. } => {
+            assert_eq!(params.len(), 2);
+            assert!(
+                matches!(&params[0].pattern.kind, PatternKind::Tuple(items) if items.len() == 2)
+            );
+            assert!(
+                matches!(&params[0].type_annotation, Some(Type::Tuple(types)) if types.len() == 2)
+            );
+            assert!(matches!(
+                &params[1].pattern.kind,
+                PatternKind::Record { name: Some(name), .. } if name == "Point"
+            ));
+        }
+
+        _ => panic!("Expected function declaration"),
+    }
+}
+
+#[test]
+fn test_lambda_patterns() {
+    let program = parse("let add = ((a, b)) -> a + b;").unwrap();
+
+    match initializer(&program[0]) {
+        ExprKind::Lambda { params, .. } => {
+            assert_eq!(params[0].pattern.bindings(), vec!["a", "b"]);
+        }
+
+        _ => panic!("Expected lambda initializer"),
+    }
+}
+
+#[test]
+fn test_call_on_identifier() {
+    let program = parse("let y = f(x).z;").unwrap();
+
+    match initializer(&program[0]) {
+        ExprKind::PropertyAccess { object, name } => {
+            assert_eq!(name, "z");
+            assert!(matches!(object.kind, ExprKind::Call { .. }));
+            assert_eq!(object.span.start.absolute, 8);
+            assert_eq!(object.span.end.absolute, 12);
+        }
+
+        _ => panic!("Expected property access on a call"),
+    }
+}
+
+#[test]
+fn test_refutable_let_pattern() {
+    let err = parse("let (x, 1) = pair;").unwrap_err();
+
+    assert!(
+        err.message
+            .contains("Refutable pattern in variable binding")
+    );
+    assert_eq!(err.span.start.absolute, 8);
+    assert_eq!(err.span.end.absolute, 9);
+}
+
+#[test]
+fn test_refutable_parameter_pattern() {
+    let err = parse("fn f(Some(x)) { return x; }").unwrap_err();
+
+    assert!(
+        err.message
+            .contains("Refutable pattern in parameter binding")
+    );
+    assert_eq!(err.span.start.absolute, 5);
+    assert_eq!(err.span.end.absolute, 12);
+}
+
+#[test]
+fn test_duplicate_pattern_binding() {
+    let err = parse("let (a, a) = pair;").unwrap_err();
+
+    assert!(err.message.contains("`a` is bound more than once"));
+}
+
+#[test]
+fn test_destructuring_requires_initializer() {
+    let err = parse("let (a, b): (number, number);").unwrap_err();
+
+    assert_eq!(
+        err.message,
+        "Destructuring declarations require an initializer"
+    );
+}
+
+#[test]
+fn test_while_and_assignment() {
+    let program = parse("var i = 0; while i < n { i += 1; }").unwrap();
+
+    match &program[1].kind {
+        StmtKind::While { condition, body } => {
+            assert!(matches!(condition.kind, ExprKind::Binary { .. }));
+            assert!(matches!(
+                body[0].kind,
+                StmtKind::Assign {
+                    op: Some(BinaryOp::Add),
+                    ..
+                }
+            ));
+        }
+
+        _ => panic!("Expected while loop"),
+    }
+}
+
+#[test]
+fn test_for_range() {
+    let program = parse("for i in 1..=n { continue; }").unwrap();
+
+    match &program[0].kind {
+        StmtKind::For {
+            pattern, iterable, ..
+        } => {
+            assert_eq!(pattern.bindings(), vec!["i"]);
+            assert!(matches!(
+                iterable.kind,
+                ExprKind::Range {
+                    inclusive: true,
+                    ..
+                }
+            ));
+        }
+
+        _ => panic!("Expected for loop"),
+    }
+}
+
+#[test]
+fn test_for_iterable_is_not_struct_literal() {
+    let program = parse("for (key, value) in items { break; }").unwrap();
+
+    match &program[0].kind {
+        StmtKind::For { iterable, body, .. } => {
+            assert!(matches!(&iterable.kind, ExprKind::Identifier(name) if name == "items"));
+            assert!(matches!(body[0].kind, StmtKind::Break));
+        }
+
+        _ => panic!("Expected for loop"),
+    }
+}
+
+#[test]
+fn test_else_if_chain() {
+    let program = parse("if a { x(); } else if b { y(); } else { z(); }").unwrap();
+
+    match &program[0].kind {
+        StmtKind::If {
+            else_branch: Some(else_branch),
+            ..
+        } => {
+            assert!(matches!(
+                else_branch[0].kind,
+                StmtKind::If {
+                    else_branch: Some(_),
+                    ..
+                }
+            ));
+        }
+
+        _ => panic!("Expected if statement"),
+    }
+}
+
+#[test]
+fn test_break_outside_loop() {
+    let err = parse("fn f() { break; }").unwrap_err();
+
+    assert_eq!(err.message, "`break` outside of a loop");
+    assert_eq!(err.span.start.absolute, 9);
+}
+
+#[test]
+fn test_continue_does_not_cross_lambda() {
+    let err = parse("while true { let f = () -> { continue; }; }").unwrap_err();
+
+    assert_eq!(err.message, "`continue` outside of a loop");
+}
+
+#[test]
+fn test_range_loop_cannot_destructure() {
+    let err = parse("for (a, b) in 0..10 { }").unwrap_err();
+
+    assert!(err.message.contains("cannot be destructured"));
+}
+
+#[test]
+fn test_invalid_assignment_target() {
+    let err = parse("f() = 1;").unwrap_err();
+
+    assert_eq!(err.message, "Invalid assignment target");
+}
+
+#[test]
+fn test_interface_and_implements() {
+    let program =
+        parse("interface Shape { fn area(self) -> number; } class Circle implements Shape, Eq { }")
+            .unwrap();
+
+    match &program[0].kind {
+        StmtKind::InterfaceDecl { name, methods, .. } => {
+            assert_eq!(name, "Shape");
+            assert_eq!(methods.len(), 1);
+            assert_eq!(methods[0].name, "area");
+            assert_eq!(methods[0].return_type, Some(Type::Simple("number".into())));
+        }
+
+        _ => panic!("Expected interface declaration"),
+    }
+
+    match &program[1].kind {
+        StmtKind::ClassDecl { implements, .. } => {
+            assert_eq!(implements, &vec!["Shape".to_string(), "Eq".to_string()]);
+        }
+
+        _ => panic!("Expected class declaration"),
+    }
+}
+
+#[test]
+fn test_record_literal() {
+    let program = parse("let p = { x: 1, y };").unwrap();
+
+    match initializer(&program[0]) {
+        ExprKind::Record(fields) => {
+            let names: Vec<_> = fields.iter().map(|(name, _)| name.as_str()).collect();
+
+            assert_eq!(names, vec!["x", "y"]);
+            assert!(matches!(fields[1].1.kind, ExprKind::Identifier(_)));
+        }
+
+        other => panic!("Expected record literal, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_literal_with_field_values() {
+    let program = parse("let c = Circle { radius: 2 };").unwrap();
+
+    match initializer(&program[0]) {
+        ExprKind::StructLiteral { name, fields } => {
+            assert_eq!(name, "Circle");
+            assert!(matches!(fields[0].1.kind, ExprKind::Literal(_)));
+        }
+
+        other => panic!("Expected struct literal, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_mixin_and_includes() {
+    let program =
+        parse("mixin Named { fn name(self) -> string { return \"\"; } } class Person includes Named implements HasName { }")
+            .unwrap();
+
+    match &program[0].kind {
+        StmtKind::MixinDecl { name, methods, .. } => {
+            assert_eq!(name, "Named");
+            assert_eq!(methods.len(), 1);
+        }
+
+        _ => panic!("Expected mixin declaration"),
+    }
+
+    match &program[1].kind {
+        StmtKind::ClassDecl {
+            implements,
+            includes,
+            ..
+        } => {
+            assert_eq!(implements, &vec!["HasName".to_string()]);
+            assert_eq!(includes, &vec!["Named".to_string()]);
+        }
+
+        _ => panic!("Expected class declaration"),
+    }
+}
+
+#[test]
+fn test_duplicate_includes_clause() {
+    let err = parse("class Person includes A includes B { }").unwrap_err();
+
+    assert_eq!(err.message, "Duplicate 'includes' clause");
+}
+
+#[test]
+fn test_static_members() {
+    let program =
+        parse("class Counter { var count: number; static let zero = 0; static fn new() { } fn get(self) { } }")
+            .unwrap();
+
+    match &program[0].kind {
+        StmtKind::ClassDecl {
+            fields,
+            methods,
+            static_fields,
+            static_methods,
+            ..
+        } => {
+            assert_eq!(fields.len(), 1);
+            assert_eq!(methods.len(), 1);
+            assert_eq!(static_fields.len(), 1);
+            assert_eq!(static_methods.len(), 1);
+        }
+
+        _ => panic!("Expected class declaration"),
+    }
+}
+
+#[test]
+fn test_instance_method_requires_self() {
+    let err = parse("class Counter { fn get() { } }").unwrap_err();
+
+    assert_eq!(
+        err.message,
+        "Instance method `get` must take `self` as its first parameter; declare it `static` if it doesn't need an instance"
+    );
+
+    assert_eq!(err.span.start.absolute, 19);
+}
+
+#[test]
+fn test_self_outside_instance_methods() {
+    let err = parse("class Counter { static fn new(self) { } }").unwrap_err();
+    assert_eq!(err.message, "Static method `new` cannot take `self`");
+    assert_eq!(err.span.start.absolute, 30);
+
+    let err = parse("fn get(self) { }").unwrap_err();
+    assert_eq!(
+        err.message,
+        "Function `get` cannot take `self` outside of a class"
+    );
+
+    let err = parse("class Counter { fn add(self, other, self) { } }").unwrap_err();
+    assert_eq!(err.message, "`self` must be the first parameter");
+}
+
+#[test]
+fn test_luau_block() {
+    let program = parse("let n = luau<number>(a, b) { return a + b }; luau { print(n) }").unwrap();
+
+    match initializer(&program[0]) {
+        ExprKind::Luau {
+            return_type,
+            bindings,
+            body,
+            body_span,
+        } => {
+            let names: Vec<_> = bindings.iter().map(|(name, _)| name.as_str()).collect();
+
+            assert_eq!(return_type, &Some(Type::Simple("number".into())));
+            assert_eq!(names, vec!["a", "b"]);
+            assert_eq!(body, " return a + b ");
+            assert_eq!(body_span.start.absolute, 27);
+        }
+
+        other => panic!("Expected luau block, got {:?}", other),
+    }
+
+    assert!(matches!(
+        &program[1].kind,
+        StmtKind::Expression(expr) if matches!(expr.kind, ExprKind::Luau { return_type: None, .. })
+    ));
+}
+
+#[test]
+fn test_attributes() {
+    let program = parse(
+        "@deprecated(\"use area\") @inline fn size() -> number { return 1; }
+@native class Grid { @deprecated static fn old() { } }
+@deprecated let legacy = 1;",
+    )
+    .unwrap();
+
+    let kinds: Vec<_> = program[0]
+        .attributes()
+        .iter()
+        .map(|attribute| attribute.kind.clone())
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            AttributeKind::Deprecated(Some("use area".into())),
+            AttributeKind::Inline,
+        ]
+    );
+
+    assert_eq!(program[0].deprecation(), Some(Some("use area")));
+    assert!(program[1].has_attribute(&AttributeKind::Native));
+    assert_eq!(program[2].deprecation(), Some(None));
+
+    match &program[1].kind {
+        StmtKind::ClassDecl { static_methods, .. } => {
+            assert_eq!(static_methods[0].deprecation(), Some(None));
+        }
+
+        _ => panic!("Expected class declaration"),
+    }
+}
+
+#[test]
+fn test_invalid_attributes() {
+    let cases = [
+        ("@pure fn f() { }", "Unknown attribute `@pure`"),
+        (
+            "@inline class C { }",
+            "`@inline` cannot be applied to a class",
+        ),
+        (
+            "@native let x = 1;",
+            "`@native` cannot be applied to a variable",
+        ),
+        ("@test(1) fn t() { }", "`@test` takes no arguments"),
+        (
+            "@inline @inline fn f() { }",
+            "Duplicate attribute `@inline`",
+        ),
+        (
+            "@deprecated while true { }",
+            "Attributes can only be applied to functions, classes and variables",
+        ),
+    ];
+
+    for (source, message) in cases {
+        assert_eq!(parse(source).unwrap_err().message, message, "{}", source);
+    }
+}
+
+#[test]
+fn test_doc_comments() {
+    let program = parse(
+        "/// Squares a number.
+///
+/// ```
+/// square(2);
+/// ```
+@inline fn square(x: number) -> number { return x * x; }
+
+/** A point. */
+class Point {
+    /// The horizontal position.
+    let x: number;
+}",
+    )
+    .unwrap();
+
+    assert_eq!(
+        program[0].docs(),
+        Some("Squares a number.\n\n```\nsquare(2);\n```")
+    );
+    assert_eq!(program[0].deprecation(), None);
+    assert_eq!(
+        doc_examples(program[0].docs().unwrap()),
+        vec!["square(2);\n"]
+    );
+
+    assert_eq!(program[1].docs(), Some("A point."));
+
+    match &program[1].kind {
+        StmtKind::ClassDecl { fields, .. } => {
+            assert_eq!(fields[0].docs(), Some("The horizontal position."));
+        }
+
+        _ => panic!("Expected class declaration"),
+    }
+}
+
+#[test]
+fn test_invalid_doc_comments() {
+    let cases = [
+        (
+            "/// loops\nwhile true { }",
+            "Doc comments can only be applied to declarations",
+        ),
+        (
+            "@inline /// inlined\nfn f() { }",
+            "Doc comments must come before attributes",
+        ),
+    ];
+
+    for (source, message) in cases {
+        assert_eq!(parse(source).unwrap_err().message, message, "{}", source);
+    }
+}
+
+#[test]
+fn test_optional_type() {
+    let program = parse("let f: (number?) -> string? = g;").unwrap();
+
+    match &program[0].kind {
+        StmtKind::VariableDecl {
+            type_annotation: Some(ty),
+            ..
+        } => assert_eq!(ty.to_string(), "(number?) -> string?"),
+
+        _ => panic!("Expected variable declaration"),
+    }
+}
+
+#[test]
+fn test_optional_chaining() {
+    let program = parse("let x = a?.b.c?.f();").unwrap();
+
+    let ExprKind::Call { callee, .. } = initializer(&program[0]) else {
+        panic!("Expected call");
+    };
+
+    let ExprKind::OptionalPropertyAccess { object, name } = &callee.kind else {
+        panic!("Expected optional property access");
+    };
+
+    assert_eq!(name, "f");
+    assert!(matches!(&object.kind, ExprKind::PropertyAccess { name, .. } if name == "c"));
+}
+
+#[test]
+fn test_coalesce_is_right_associative_and_loosest() {
+    let program = parse("let x = a || b ?? c ?? d;").unwrap();
+
+    let ExprKind::Binary { left, op, right } = initializer(&program[0]) else {
+        panic!("Expected binary expression");
+    };
+
+    assert_eq!(*op, BinaryOp::Coalesce);
+    assert!(matches!(
+        left.kind,
+        ExprKind::Binary {
+            op: BinaryOp::Or,
+            ..
+        }
+    ));
+    assert!(matches!(
+        right.kind,
+        ExprKind::Binary {
+            op: BinaryOp::Coalesce,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_type_alias_with_record_type() {
+    let program =
+        parse("type Entry<T> = { readonly key: string, value?: T; [number]: boolean, };").unwrap();
+
+    match &program[0].kind {
+        StmtKind::TypeAlias {
+            name, params, ty, ..
+        } => {
+            assert_eq!(name, "Entry");
+            assert_eq!(params, &vec!["T".to_string()]);
+            assert_eq!(
+                ty.to_string(),
+                "{ readonly key: string, value?: T, [number]: boolean }"
+            );
+        }
+
+        _ => panic!("Expected type alias"),
+    }
+}
+
+#[test]
+fn test_array_types() {
+    let program = parse("fn sum<T: Addable>(xs: [T], grid: [[number]?]) -> T { }").unwrap();
+
+    let StmtKind::FunctionDecl { params, .. } = &program[0].kind else {
+        panic!("Expected function declaration");
+    };
+
+    let types: Vec<_> = params
+        .iter()
+        .map(|param| param.type_annotation.as_ref().unwrap().to_string())
+        .collect();
+
+    assert_eq!(types, ["[T]", "[[number]?]"]);
+
+    let err = parse("let xs: [number;").unwrap_err();
+    assert_eq!(err.message, "Expected ']' after array element type");
+}
+
+#[test]
+fn test_readonly_can_be_a_field_name() {
+    let program = parse("type Flags = { readonly: boolean };").unwrap();
+
+    match &program[0].kind {
+        StmtKind::TypeAlias { ty, .. } => assert_eq!(ty.to_string(), "{ readonly: boolean }"),
+
+        _ => panic!("Expected type alias"),
+    }
+}
+
+#[test]
+fn test_record_type_errors() {
+    let duplicate = parse("type P = { x: number, x: string };").unwrap_err();
+    assert_eq!(duplicate.message, "Duplicate field `x` in record type");
+
+    let indexers = parse("type P = { [string]: number, [number]: number };").unwrap_err();
+    assert_eq!(
+        indexers.message,
+        "Record type can only have one index signature"
+    );
+}
+
+#[test]
+fn test_function_type_parameters() {
+    let program = parse("fn pair<A, B>(a: A, b: B) -> (A, B) { return (a, b); }").unwrap();
+
+    let StmtKind::FunctionDecl {
+        type_params,
+        return_type,
+        ..
+    } = &program[0].kind
+    else {
+        panic!("Expected function declaration");
+    };
+
+    assert_eq!(type_params, &["A", "B"]);
+    assert_eq!(return_type.as_ref().unwrap().to_string(), "(A, B)");
+}
+
+#[test]
+fn test_type_parameter_bounds_and_where_clauses() {
+    let program =
+        parse("fn f<T: Num + Show, U>(t: T, u: U) -> T where U: Eq, T: Ord { return t; }").unwrap();
+
+    let StmtKind::FunctionDecl { bounds, .. } = &program[0].kind else {
+        panic!("Expected function declaration");
+    };
+
+    let bounds: Vec<_> = bounds
+        .iter()
+        .map(|bound| (bound.param.as_str(), bound.constraints.join(" + ")))
+        .collect();
+
+    assert_eq!(
+        bounds,
+        [
+            ("T", "Num + Show".to_string()),
+            ("U", "Eq".to_string()),
+            ("T", "Ord".to_string())
+        ]
+    );
+
+    let err = parse("fn f<T>(t: T) where V: Eq { }").unwrap_err();
+    assert_eq!(err.message, "Cannot find type parameter `V`");
+}
+
+#[test]
+fn test_union_and_variant_types() {
+    let program = parse(
+        "type Option<T> = Some(T) | None;\ntype Direction = \"north\" | \"south\" | -1;\ntype Maybe = string? | nil;",
+    )
+    .unwrap();
+
+    let types: Vec<_> = program
+        .iter()
+        .map(|stmt| match &stmt.kind {
+            StmtKind::TypeAlias { ty, .. } => ty.to_string(),
+            _ => panic!("Expected type alias"),
+        })
+        .collect();
+
+    assert_eq!(
+        types,
+        [
+            "Some(T) | None",
+            "\"north\" | \"south\" | -1",
+            "string? | nil"
+        ]
+    );
+
+    let StmtKind::TypeAlias { ty, .. } = &program[0].kind else {
+        unreachable!()
+    };
+
+    let variants: Vec<_> = ty
+        .variants()
+        .unwrap()
+        .into_iter()
+        .map(|(name, fields)| (name.as_str(), fields.len()))
+        .collect();
+
+    assert_eq!(variants, [("Some", 1), ("None", 0)]);
+}
+
+#[test]
+fn test_match_expression() {
+    let program = parse(
+        "let n = match value { Some(x) => x, None => { log(); return 0; } _ => 1, };\nmatch p { P { x } => x }",
+    )
+    .unwrap();
+
+    let ExprKind::Match { scrutinee, arms } = initializer(&program[0]) else {
+        panic!("Expected match expression");
+    };
+
+    assert!(matches!(&scrutinee.kind, ExprKind::Identifier(name) if name == "value"));
+    assert_eq!(arms.len(), 3);
+    assert!(matches!(
+        &arms[0].pattern.kind,
+        PatternKind::Constructor { name, args } if name == "Some" && args.len() == 1
+    ));
+    assert!(matches!(arms[1].body.kind, ExprKind::Block(_)));
+    assert!(matches!(arms[2].pattern.kind, PatternKind::Wildcard));
+
+    // the scrutinee is a condition, so `p {` starts the arms rather than a struct literal
+    assert!(matches!(
+        &program[1].kind,
+        StmtKind::Expression(expr) if matches!(expr.kind, ExprKind::Match { .. })
+    ));
+
+    let missing = parse("let n = match x { 1 2 };").unwrap_err();
+    assert_eq!(missing.message, "Expected '=>' after match pattern");
+}
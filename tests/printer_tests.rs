@@ -0,0 +1,240 @@
+use exx::{
+    ast::{Expr, ExprKind, Stmt, StmtKind},
+    lexer::Lexer,
+    parser::{Parser, ParserToken},
+    printer,
+    visit::{Fold, walk_expr_fold},
+};
+use pretty_assertions::assert_eq;
+
+fn parse(source: &str) -> Vec<Stmt> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+
+    Parser::new(tokens)
+        .parse_program()
+        .unwrap_or_else(|err| panic!("{:?} in:\n{}", err, source))
+}
+
+// parse → print → parse → print must settle on the first printout
+fn round_trip(source: &str) -> String {
+    let printed = printer::print(&parse(source));
+    assert_eq!(printer::print(&parse(&printed)), printed);
+
+    printed
+}
+
+#[test]
+fn test_round_trip_declarations() {
+    let source = r#"/// A point.
+///
+/// ```
+/// Point.new(1, 2);
+/// ```
+class Point implements Shape includes Describable {
+    let x: number;
+    static var count: number = 0;
+    @deprecated("use make") static fn new(x: number) -> Point { return Point { x }; }
+    fn area(self) -> number { return 0; }
+}
+interface Shape { /// Its area.
+fn area(self) -> number; }
+mixin Describable { fn describe(self) -> string { return "a \"shape\"\n"; } }
+type Pair<T> = { readonly first: T, second?: (T) -> T?, [string]: number };
+@inline fn add(a: number, b: number) -> number { return a + b; }
+let (a, { b, c: (d, _) }) = (1, { b: 2, c: (3, 4) });"#;
+
+    assert_eq!(
+        round_trip(source),
+        r#"/// A point.
+///
+/// ```
+/// Point.new(1, 2);
+/// ```
+class Point implements Shape includes Describable {
+    let x: number;
+    static var count: number = 0;
+
+    @deprecated("use make")
+    static fn new(x: number) -> Point {
+        return Point { x };
+    }
+
+    fn area(self) -> number {
+        return 0;
+    }
+}
+
+interface Shape {
+    /// Its area.
+    fn area(self) -> number;
+}
+
+mixin Describable {
+    fn describe(self) -> string {
+        return "a \"shape\"\n";
+    }
+}
+
+type Pair<T> = { readonly first: T, second?: (T) -> T?, [string]: number };
+
+@inline
+fn add(a: number, b: number) -> number {
+    return a + b;
+}
+
+let (a, { b, c: (d, _) }) = (1, { b: 2, c: (3, 4) });
+"#
+    );
+}
+
+#[test]
+fn test_round_trip_statements() {
+    let source = r#"fn main(items: Items) {
+    var total = 0;
+    for i in 1..=10 { total += i; }
+    for (key, value) in items { if key == "skip" { continue; } else if value > 2 { break; } else { total -= 1; } }
+    while total > 0 && !done { total = total - 1; }
+    if (Point { x: 1 }).x == 1 { }
+    luau { print("hi") }
+    let n = luau<number>(total) { return total };
+    let f = (x: number) -> { return x * 2; };
+    user?.greet();
+    return f(n ?? 1)?.value;
+}"#;
+
+    assert_eq!(
+        round_trip(source),
+        r#"fn main(items: Items) {
+    var total = 0;
+    for i in 1..=10 {
+        total += i;
+    }
+    for (key, value) in items {
+        if key == "skip" {
+            continue;
+        } else if value > 2 {
+            break;
+        } else {
+            total -= 1;
+        }
+    }
+    while total > 0 && !done {
+        total = total - 1;
+    }
+    if (Point { x: 1 }).x == 1 {}
+    luau { print("hi") }
+    let n = luau<number>(total) { return total };
+    let f = (x: number) -> {
+        return x * 2;
+    };
+    user?.greet();
+    return f(n ?? 1)?.value;
+}
+"#
+    );
+}
+
+#[test]
+fn test_round_trip_keeps_source_grouping() {
+    assert_eq!(
+        round_trip("let x = (a + b) * -(c - d) ?? e ?? f;"),
+        "let x = (a + b) * -(c - d) ?? e ?? f;\n"
+    );
+}
+
+// drops every `( )` the source had, so the printer has to put back the ones that matter
+struct Ungroup;
+
+impl Fold for Ungroup {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = walk_expr_fold(self, expr);
+
+        match expr.kind {
+            ExprKind::Grouping(inner) => *inner,
+
+            kind => Expr::new(kind, expr.span),
+        }
+    }
+}
+
+#[test]
+fn test_parentheses_follow_precedence() {
+    let cases = [
+        ("(a + b) * c", "(a + b) * c"),
+        ("a - (c - d)", "a - (c - d)"),
+        ("(c - d) - a", "c - d - a"),
+        ("(a ?? b) ?? c", "(a ?? b) ?? c"),
+        ("a ?? (b ?? c)", "a ?? b ?? c"),
+        ("!(a && b) || c", "!(a && b) || c"),
+        ("-(-x)", "- -x"),
+        ("(a + b).x", "(a + b).x"),
+        ("(1).x", "(1).x"),
+        ("((() -> a))()", "(() -> a)()"),
+        ("f(((x) -> x + 1))", "f((x) -> x + 1)"),
+    ];
+
+    for (source, expected) in cases {
+        let program = parse(&format!("{};", source));
+        let StmtKind::Expression(expr) =
+            Ungroup.fold_stmt(program.into_iter().next().unwrap()).kind
+        else {
+            panic!("Expected expression statement");
+        };
+
+        assert_eq!(expr.to_string(), expected);
+    }
+}
+
+#[test]
+fn test_struct_literals_in_conditions_are_parenthesized() {
+    let program = parse("while (P { x: 1 }).x == p { }");
+    let stmt = Ungroup.fold_stmt(program.into_iter().next().unwrap());
+
+    assert_eq!(stmt.to_string(), "while (P { x: 1 }).x == p {}");
+}
+
+#[test]
+fn test_round_trip_match_and_union_types() {
+    let source = r#"type Option<T> = Some(T) | None;
+type Direction = "north" | "south" | ((number) -> number)?;
+
+fn f(o: Option<number>) -> number {
+    return match o { Some((x, _)) => x, None => { log(); return -1; } P { y } => y };
+}"#;
+
+    assert_eq!(
+        round_trip(source),
+        r#"type Option<T> = Some(T) | None;
+type Direction = "north" | "south" | ((number) -> number)?;
+
+fn f(o: Option<number>) -> number {
+    return match o {
+        Some((x, _)) => x,
+        None => {
+            log();
+            return -1;
+        },
+        P { y } => y,
+    };
+}
+"#
+    );
+}
+
+#[test]
+fn test_round_trip_bounds_move_into_type_parameters() {
+    let source = "interface Named { fn name<T: Show>(self, t: T) -> string; }
+fn f<T: Num, U>(t: T, u: U) -> T where U: Eq + Ord, T: Show { return t; }";
+
+    assert_eq!(
+        round_trip(source),
+        "interface Named {
+    fn name<T: Show>(self, t: T) -> string;
+}
+
+fn f<T: Num + Show, U: Eq + Ord>(t: T, u: U) -> T {
+    return t;
+}
+"
+    );
+}
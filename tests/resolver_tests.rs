@@ -0,0 +1,231 @@
+use exx::{
+    ast::{ExprKind, Stmt, StmtKind},
+    diagnostic::Diagnostic,
+    lexer::Lexer,
+    node::{DefKind, Tables},
+    parser::{Parser, ParserToken},
+    resolver,
+};
+use pretty_assertions::assert_eq;
+
+fn parse(source: &str) -> Vec<Stmt> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+
+    Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse")
+}
+
+fn check(source: &str) -> Vec<Diagnostic> {
+    let program = parse(source);
+
+    resolver::resolve(&program, &mut Tables::new(&program))
+}
+
+#[test]
+fn test_misspelled_recursive_call() {
+    // the README's factorial, as it was once written
+    let source = "fn factorial(n: number) -> number {
+    if n == 0 {
+        return 1;
+    } else {
+        return n * fctorial(n - 1);
+    }
+}";
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E300");
+    assert_eq!(
+        diagnostics[0].message,
+        "Cannot find `fctorial` in this scope"
+    );
+    assert_eq!(
+        diagnostics[0].notes,
+        vec!["help: did you mean `factorial`?"]
+    );
+
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 102);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 0);
+}
+
+#[test]
+fn test_suggests_builtins() {
+    let diagnostics = check("prnt(1);");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].notes, vec!["help: did you mean `print`?"]);
+    assert_eq!(diagnostics[0].labels.len(), 1);
+}
+
+#[test]
+fn test_no_suggestion_for_distant_names() {
+    let diagnostics = check("let total = 1; quux;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].notes.is_empty());
+}
+
+#[test]
+fn test_let_is_not_visible_before_its_declaration() {
+    let diagnostics = check("let a = b; let b = 1; let c = b;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Cannot find `b` in this scope");
+}
+
+#[test]
+fn test_items_are_hoisted() {
+    let source = "fn isEven(n) { return n == 0 || isOdd(n - 1); }
+fn isOdd(n) { return n != 0 && isEven(n - 1); }
+let origin = () -> Point { x: 1 };
+class Point { let x: number; }";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_items_used_before_their_declaration_runs() {
+    let source = "let p = Point { x: 1 };
+let n = later();
+class Point { let x: number; static let origin = Point { x: 0 }; }
+fn later() -> number { return sooner(); }
+fn sooner() -> number { return 1; }";
+
+    let diagnostics = check(source);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|d| d.code == "E302"));
+    assert_eq!(
+        diagnostics[0].message,
+        "`Point` is used before its declaration"
+    );
+    assert_eq!(diagnostics[0].labels[1].message, "declared later here");
+    assert_eq!(
+        diagnostics[1].message,
+        "`later` is used before its declaration"
+    );
+}
+
+#[test]
+fn test_block_scopes_end_with_the_block() {
+    let diagnostics = check("if true { let inner = 1; } inner;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Cannot find `inner` in this scope");
+}
+
+#[test]
+fn test_lambda_and_loop_bindings() {
+    let source = "let add = (a, b) -> a + b;
+for (k, v) in pairs({}) { add(k, v); }
+let f = (x) -> { let y = x; return y; };";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_duplicate_definitions() {
+    let diagnostics = check("fn f() {} let x = 1; let f = 2;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E301");
+    assert_eq!(
+        diagnostics[0].message,
+        "`f` is defined more than once in this scope"
+    );
+    assert_eq!(diagnostics[0].labels[0].span.start.absolute, 25);
+    assert_eq!(diagnostics[0].labels[1].span.start.absolute, 0);
+}
+
+#[test]
+fn test_shadowing_in_nested_scopes_is_allowed() {
+    let source = "let x = 1; fn f(x) { let x = x + 1; return x; }";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_types_and_values_are_separate_namespaces() {
+    let diagnostics = check("type Id = number; let Id = 1; type Id = string;");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E301");
+}
+
+#[test]
+fn test_class_members_are_in_scope_for_methods() {
+    let source = "class Counter {
+    var count: number;
+
+    fn increment(self) { count; }
+    fn reset(self) { increment(); }
+}";
+
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_identifiers_link_to_their_definitions() {
+    let program = parse("let x = 1; fn f(x) { return x; } f(x);");
+    let mut tables = Tables::new(&program);
+
+    assert!(resolver::resolve(&program, &mut tables).is_empty());
+
+    let StmtKind::Expression(call) = &program[2].kind else {
+        panic!("expected a call");
+    };
+    let ExprKind::Call { callee, arguments } = &call.kind else {
+        panic!("expected a call");
+    };
+
+    let callee = tables.resolution(callee.id).unwrap();
+    assert_eq!(callee.name, "f");
+    assert_eq!(callee.kind, DefKind::Function);
+
+    let arg = tables.resolution(arguments[0].id).unwrap();
+    assert_eq!(arg.kind, DefKind::Variable);
+    assert_eq!(arg.span.start.absolute, 4);
+
+    let StmtKind::FunctionDecl { body, .. } = &program[1].kind else {
+        panic!("expected a function");
+    };
+    let StmtKind::Return(Some(x)) = &body[0].kind else {
+        panic!("expected a return");
+    };
+
+    assert_eq!(tables.resolution(x.id).unwrap().kind, DefKind::Parameter);
+}
+
+#[test]
+fn test_constructors_are_matched_not_bound() {
+    let source = "type Option<T> = Some(T) | None;
+let n = match Some(1) { Some(x) => x, None => 0 };
+let m = match n { Nothing => Nothing };
+let o = x;";
+
+    let program = parse(source);
+    let mut tables = Tables::new(&program);
+    let diagnostics = resolver::resolve(&program, &mut tables);
+
+    // arm bindings end with their arm
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Cannot find `x` in this scope");
+
+    let StmtKind::VariableDecl {
+        initializer: Some(initializer),
+        ..
+    } = &program[1].kind
+    else {
+        panic!("expected a let");
+    };
+    let ExprKind::Match { arms, .. } = &initializer.kind else {
+        panic!("expected a match");
+    };
+
+    let some = tables.resolution(arms[0].pattern.id).unwrap();
+    assert_eq!((some.name.as_str(), some.kind), ("Some", DefKind::Variant));
+
+    let none = tables.resolution(arms[1].pattern.id).unwrap();
+    assert_eq!((none.name.as_str(), none.kind), ("None", DefKind::Variant));
+}
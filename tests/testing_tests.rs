@@ -0,0 +1,21 @@
+use exx::{
+    lexer::Lexer,
+    parser::{Parser, ParserToken},
+    testing,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_discovers_test_functions() {
+    let source = "@test fn adds() { } fn helper() { } @test fn subtracts() { }";
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+    let program = Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse");
+
+    let tests = testing::discover(&program);
+    let names: Vec<_> = tests.iter().map(|test| test.name).collect();
+
+    assert_eq!(names, vec!["adds", "subtracts"]);
+    assert_eq!(tests[1].span.start.absolute, 42);
+}
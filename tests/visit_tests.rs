@@ -0,0 +1,124 @@
+use exx::{
+    ast::{BinaryOp, Expr, ExprKind, Literal, Stmt, Type},
+    codegen::Codegen,
+    lexer::Lexer,
+    parser::{Parser, ParserToken},
+    visit::{Fold, Visitor, VisitorMut, walk_expr, walk_expr_fold, walk_expr_mut},
+};
+use pretty_assertions::assert_eq;
+
+fn parse(source: &str) -> Vec<Stmt> {
+    let tokens: Vec<ParserToken> = Lexer::new(source).map(|t| t.into()).collect();
+
+    Parser::new(tokens)
+        .parse_program()
+        .expect("source should parse")
+}
+
+#[derive(Default)]
+struct Names {
+    identifiers: Vec<String>,
+    types: Vec<String>,
+}
+
+impl<'ast> Visitor<'ast> for Names {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let ExprKind::Identifier(name) = &expr.kind {
+            self.identifiers.push(name.to_string());
+        }
+
+        walk_expr(self, expr);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        self.types.push(ty.to_string());
+    }
+}
+
+#[test]
+fn test_visitor_reaches_nested_nodes() {
+    let program = parse(
+        "class C {
+    static fn f(x: number?) -> { [string]: number } {
+        let g = (y: string) -> { return x ?? y; };
+        return g(a.b);
+    }
+}
+interface I { fn area(self) -> number; }",
+    );
+
+    let mut names = Names::default();
+
+    for stmt in &program {
+        names.visit_stmt(stmt);
+    }
+
+    assert_eq!(names.identifiers, vec!["x", "y", "g", "a"]);
+    assert_eq!(
+        names.types,
+        vec!["number?", "{ [string]: number }", "string", "number"]
+    );
+}
+
+struct Rename;
+
+impl VisitorMut for Rename {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let ExprKind::Identifier(name) = &mut expr.kind {
+            *name = name.to_ascii_uppercase().into();
+        }
+
+        walk_expr_mut(self, expr);
+    }
+}
+
+#[test]
+fn test_visitor_mut_rewrites_in_place() {
+    let mut program = parse("while a { b = c + d; }");
+
+    for stmt in &mut program {
+        Rename.visit_stmt(stmt);
+    }
+
+    assert_eq!(
+        Codegen::new().generate(&program),
+        "while A do\n    B = C + D\nend\n"
+    );
+}
+
+struct ConstantFolding;
+
+impl Fold for ConstantFolding {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = walk_expr_fold(self, expr);
+
+        match &expr.kind {
+            ExprKind::Binary {
+                left,
+                op: BinaryOp::Add,
+                right,
+            } => match (&left.kind, &right.kind) {
+                (ExprKind::Literal(Literal::Number(a)), ExprKind::Literal(Literal::Number(b))) => {
+                    Expr::new(ExprKind::Literal(Literal::Number(a + b)), expr.span)
+                }
+
+                _ => expr,
+            },
+
+            _ => expr,
+        }
+    }
+}
+
+#[test]
+fn test_fold_rebuilds_bottom_up() {
+    let program: Vec<_> = parse("fn f() -> number { return 1 + 2 + x + (3 + 4); }")
+        .into_iter()
+        .map(|stmt| ConstantFolding.fold_stmt(stmt))
+        .collect();
+
+    assert_eq!(
+        Codegen::new().generate(&program),
+        "local function f()\n    return 3 + x + (7)\nend\n"
+    );
+}